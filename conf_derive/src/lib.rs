@@ -32,11 +32,14 @@ fn derive_conf(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
             let gen = GenConfStruct::new(ident, &input.attrs, fields)?;
             let conf_impl = gen.gen_conf_impl(&input.generics)?;
             let maybe_serde = gen.maybe_gen_conf_serde_impl(&input.generics)?;
+            let maybe_to_args = gen.maybe_gen_conf_to_args_impl(&input.generics)?;
 
             Ok(quote! {
                 #conf_impl
 
                 #maybe_serde
+
+                #maybe_to_args
             })
         }
 
@@ -64,11 +67,14 @@ fn derive_subcommands(input: &DeriveInput) -> Result<TokenStream, syn::Error> {
             let gen = GenSubcommandsEnum::new(ident, &input.attrs, variants.into_iter())?;
             let subcommands_impl = gen.gen_subcommands_impl(&input.generics)?;
             let maybe_serde = gen.maybe_gen_subcommands_serde_impl(&input.generics)?;
+            let maybe_to_args = gen.maybe_gen_subcommands_to_args_impl(&input.generics)?;
 
             Ok(quote! {
                 #subcommands_impl
 
                 #maybe_serde
+
+                #maybe_to_args
             })
         }
 
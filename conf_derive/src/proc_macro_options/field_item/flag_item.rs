@@ -58,6 +58,10 @@ pub struct FlagItem {
     aliases: Option<LitStrArray>,
     env_name: Option<LitStr>,
     env_aliases: Option<LitStrArray>,
+    deprecated_env_aliases: Option<LitStrArray>,
+    deprecated: Option<LitStr>,
+    hide: bool,
+    hide_env_value: bool,
     serde: Option<FlagSerdeItem>,
     doc_string: Option<String>,
 }
@@ -76,6 +80,10 @@ impl FlagItem {
             aliases: None,
             env_name: None,
             env_aliases: None,
+            deprecated_env_aliases: None,
+            deprecated: None,
+            hide: false,
+            hide_env_value: false,
             serde: None,
             doc_string: None,
         };
@@ -115,11 +123,19 @@ impl FlagItem {
                                 .or(make_env(&result.field_name, path.span())),
                         )
                     } else if path.is_ident("env_aliases") {
-                        set_once(
-                            &path,
+                        parse_env_aliases(
+                            meta,
                             &mut result.env_aliases,
-                            Some(parse_required_value::<LitStrArray>(meta)?),
+                            &mut result.deprecated_env_aliases,
                         )
+                    } else if path.is_ident("hide") {
+                        result.hide = true;
+                        Ok(())
+                    } else if path.is_ident("hide_env_value") {
+                        result.hide_env_value = true;
+                        Ok(())
+                    } else if path.is_ident("deprecated") {
+                        set_once(&path, &mut result.deprecated, Some(parse_deprecated(meta)?))
                     } else if path.is_ident("serde") {
                         set_once(&path, &mut result.serde, Some(FlagSerdeItem::new(meta)?))
                     } else {
@@ -144,11 +160,10 @@ impl FlagItem {
         }
 
         if result.env_name.is_none()
-            && !result
-                .env_aliases
-                .as_ref()
-                .map(LitStrArray::is_empty)
-                .unwrap_or(true)
+            && [&result.env_aliases, &result.deprecated_env_aliases]
+                .into_iter()
+                .flatten()
+                .any(|env_aliases| !env_aliases.is_empty())
         {
             return Err(Error::new(
                 field.span(),
@@ -183,6 +198,10 @@ impl FlagItem {
         self.serde.as_ref().map(|serde| serde.skip).unwrap_or(false)
     }
 
+    pub fn get_document_value_type(&self) -> TokenStream {
+        quote! { ::conf::DocumentValueType::Bool }
+    }
+
     pub fn gen_push_program_options(
         &self,
         program_options_ident: &Ident,
@@ -193,10 +212,15 @@ impl FlagItem {
         let long_form = quote_opt_into(&self.long_switch);
         let aliases = self.aliases.as_ref().map(LitStrArray::quote_elements_into);
         let env_form = quote_opt_into(&self.env_name);
-        let env_aliases = self
-            .env_aliases
+        let env_aliases =
+            quote_all_elements_into([&self.env_aliases, &self.deprecated_env_aliases]);
+        let deprecated_env_aliases = self
+            .deprecated_env_aliases
             .as_ref()
             .map(LitStrArray::quote_elements_into);
+        let deprecated = quote_opt_into(&self.deprecated);
+        let hide = self.hide;
+        let hide_env_value = self.hide_env_value;
 
         Ok(quote! {
             #program_options_ident.push(::conf::ProgramOption {
@@ -208,10 +232,18 @@ impl FlagItem {
                 aliases: vec![#aliases],
                 env_form: #env_form,
                 env_aliases: vec![#env_aliases],
+                env_cmd: None,
                 default_value: None,
                 is_required: false,
                 allow_hyphen_values: false,
                 secret: Some(false),
+                secret_allow_cli: false,
+                secret_warn_cli: false,
+                deprecated: #deprecated,
+                deprecated_env_aliases: vec![#deprecated_env_aliases],
+                help_heading: None,
+                hidden: #hide,
+                hide_env_value: #hide_env_value,
             });
         })
     }
@@ -242,7 +274,7 @@ impl FlagItem {
     pub fn gen_initializer_with_doc_val(
         &self,
         conf_context_ident: &Ident,
-        _doc_name: &Ident,
+        doc_name: &Ident,
         doc_val: &Ident,
     ) -> Result<(TokenStream, bool), Error> {
         let id = self.field_name.to_string();
@@ -251,6 +283,11 @@ impl FlagItem {
             quote! {
                 let (src, val) = #conf_context_ident.get_boolean_opt(#id)?;
                 if src.is_default() {
+                    #conf_context_ident.record_document_value(
+                        #id,
+                        #doc_name,
+                        || ::conf::report::ReportValue::Bool(#doc_val)
+                    );
                     Ok(#doc_val)
                 } else {
                     Ok(val)
@@ -259,4 +296,38 @@ impl FlagItem {
             false,
         ))
     }
+
+    pub fn gen_push_changes(
+        &self,
+        old_ident: &Ident,
+        new_ident: &Ident,
+        changes_ident: &Ident,
+        program_options_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+
+        Ok(quote! {
+            ::conf::report::ConfChange::push_option(
+                #changes_ident,
+                #program_options_ident,
+                #id,
+                #old_ident.map(|o| ::conf::report::ReportValue::Bool(o.#field_name)).unwrap_or(::conf::report::ReportValue::Unset),
+                #new_ident.map(|n| ::conf::report::ReportValue::Bool(n.#field_name)).unwrap_or(::conf::report::ReportValue::Unset),
+                None,
+            );
+        })
+    }
+
+    pub fn gen_push_formatted_values(
+        &self,
+        values_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+
+        Ok(quote! {
+            #values_ident.push_flag(#id, self.#field_name);
+        })
+    }
 }
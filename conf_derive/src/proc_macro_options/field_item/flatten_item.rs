@@ -57,6 +57,9 @@ pub struct FlattenItem {
     long_prefix: Option<LitStr>,
     env_prefix: Option<LitStr>,
     description_prefix: Option<String>,
+    help_heading: Option<String>,
+    hide: bool,
+    hide_env_value: bool,
     skip_short: Option<LitCharArray>,
     serde: Option<FlattenSerdeItem>,
 }
@@ -87,6 +90,9 @@ impl FlattenItem {
             long_prefix: None,
             env_prefix: None,
             description_prefix: None,
+            help_heading: None,
+            hide: false,
+            hide_env_value: false,
             skip_short: None,
             serde: None,
         };
@@ -96,6 +102,10 @@ impl FlattenItem {
         // If help_prefix is set, this is Some
         // If help_prefix sets an explicit value, this is Some(Some(...))
         let mut help_prefix: Option<Option<LitStr>> = None;
+        // Similarly for help_heading, which uses the first line of the doc string if no value
+        // is given.
+        let mut help_heading: Option<Option<LitStr>> = None;
+        let mut help_heading_span = field.span();
 
         for attr in &field.attrs {
             maybe_append_doc_string(&mut doc_string, &attr.meta)?;
@@ -124,6 +134,19 @@ impl FlattenItem {
                             &mut help_prefix,
                             Some(parse_optional_value::<LitStr>(meta)?),
                         )
+                    } else if path.is_ident("help_heading") {
+                        help_heading_span = path.span();
+                        set_once(
+                            &path,
+                            &mut help_heading,
+                            Some(parse_optional_value::<LitStr>(meta)?),
+                        )
+                    } else if path.is_ident("hide") {
+                        result.hide = true;
+                        Ok(())
+                    } else if path.is_ident("hide_env_value") {
+                        result.hide_env_value = true;
+                        Ok(())
                     } else if path.is_ident("prefix") {
                         let (long_prefix, env_prefix) = match parse_optional_value::<LitStr>(meta)?
                         {
@@ -154,6 +177,23 @@ impl FlattenItem {
             }
         }
 
+        result.help_heading = match help_heading {
+            None => None,
+            Some(Some(heading)) => Some(heading.value()),
+            Some(None) => {
+                let first_line = doc_string
+                    .as_deref()
+                    .and_then(|doc| doc.lines().map(str::trim).find(|line| !line.is_empty()))
+                    .ok_or_else(|| {
+                        Error::new(
+                            help_heading_span,
+                            "help_heading needs a value, or a doc comment on this field",
+                        )
+                    })?;
+                Some(first_line.trim_end_matches('.').to_owned())
+            }
+        };
+
         // If help prefix was not requested, then doc_string should be ignored. If help_prefix was
         // explicitly assigned, then doc_string is shadowed. unwrap_or_default is used to
         // flatten the two levels of Option.
@@ -223,8 +263,19 @@ impl FlattenItem {
 
         // Common modifications we have to make to program options whether the flatten is optional
         // or required
+        let apply_help_heading = self
+            .help_heading
+            .as_ref()
+            .map(|heading| quote! { .apply_help_heading(#heading) });
+        let make_hidden = self.hide.then(|| quote! { .make_hidden() });
+        let make_env_value_hidden = self
+            .hide_env_value
+            .then(|| quote! { .make_env_value_hidden() });
         let common_program_option_modifications = quote! {
           .apply_flatten_prefixes(#id_prefix, #long_prefix, #env_prefix, #description_prefix)
+          #apply_help_heading
+          #make_hidden
+          #make_env_value_hidden
           .skip_short_forms(&[#skip_short], &mut #was_skipped_ident[..])
         };
 
@@ -427,7 +478,7 @@ impl FlattenItem {
               );
             } else {
               let __seed__ = <#inner_type as ConfSerde>::Seed::from(
-                #ctxt.for_flattened(#id_prefix)
+                #ctxt.for_flattened(#id_prefix, #serde_name_str)
               );
               #field_name = Some(match #map_access.next_value_seed(__seed__) {
                 Ok(Ok(__val__)) => {
@@ -453,6 +504,96 @@ impl FlattenItem {
         };
         Ok((match_arm, vec![serde_name_str]))
     }
+
+    // This is used by ConfSerde
+    //
+    // Pushes a DocumentField describing the nested table for this field
+    //
+    // Arguments:
+    // * document_fields_ident: identifier of a mut Vec<DocumentField> to which we can push
+    pub fn gen_push_document_fields(
+        &self,
+        document_fields_ident: &Ident,
+    ) -> Result<TokenStream, Error> {
+        let serde_name_str = self.get_serde_name();
+        let is_optional = self.is_optional_type.is_some();
+        let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+
+        Ok(quote! {
+            #document_fields_ident.push(::conf::DocumentField::Flatten {
+                serde_name: #serde_name_str,
+                is_optional: #is_optional,
+                inner: <#inner_type as ::conf::ConfSerde>::get_document_struct()?,
+            });
+        })
+    }
+
+    pub fn gen_push_changes(
+        &self,
+        old_ident: &Ident,
+        new_ident: &Ident,
+        changes_ident: &Ident,
+        program_options_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id_prefix = self.get_id_prefix();
+        let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+
+        // The options of an optional flattened structure are all unset when it is None
+        let get_value = if self.is_optional_type.is_some() {
+            quote! { and_then(|s| s.#field_name.as_ref()) }
+        } else {
+            quote! { map(|s| &s.#field_name) }
+        };
+
+        Ok(quote! {
+            {
+                let mut __inner_changes__ = Vec::new();
+                <#inner_type as ::conf::Conf>::diff_helper(
+                    #old_ident.#get_value,
+                    #new_ident.#get_value,
+                    &mut __inner_changes__,
+                );
+                ::conf::report::ConfChange::push_flattened(
+                    #changes_ident,
+                    #program_options_ident,
+                    #id_prefix,
+                    __inner_changes__,
+                );
+            }
+        })
+    }
+
+    pub fn gen_push_formatted_values(
+        &self,
+        values_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id_prefix = self.get_id_prefix();
+        let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+
+        let push_flattened = quote! {
+            let mut __inner_values__ = ::conf::FormattedValues::default();
+            <#inner_type as ::conf::ConfToArgs>::format_values(__inner__, &mut __inner_values__);
+            #values_ident.push_flattened(#id_prefix, __inner_values__);
+        };
+
+        // The options of an optional flattened structure have no values when it is None
+        Ok(if self.is_optional_type.is_some() {
+            quote! {
+                if let Some(__inner__) = self.#field_name.as_ref() {
+                    #push_flattened
+                }
+            }
+        } else {
+            quote! {
+                {
+                    let __inner__ = &self.#field_name;
+                    #push_flattened
+                }
+            }
+        })
+    }
 }
 
 #[cfg(test)]
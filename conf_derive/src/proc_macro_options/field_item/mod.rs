@@ -18,6 +18,7 @@ use repeat_item::RepeatItem;
 use subcommands_item::SubcommandsItem;
 
 /// #[conf(...)] options listed in a field of a struct which has `#[derive(Conf)]`
+#[allow(clippy::large_enum_variant)]
 pub enum FieldItem {
     Flag(FlagItem),
     Parameter(ParameterItem),
@@ -122,6 +123,46 @@ impl FieldItem {
         }
     }
 
+    /// Generate code that compares this field in two `Option<&Self>` and pushes any changes onto
+    /// changes_ident. program_options_ident is the program options of the struct.
+    pub fn gen_push_changes(
+        &self,
+        old_ident: &Ident,
+        new_ident: &Ident,
+        changes_ident: &Ident,
+        program_options_ident: &Ident,
+    ) -> Result<TokenStream, Error> {
+        match self {
+            Self::Flag(item) => {
+                item.gen_push_changes(old_ident, new_ident, changes_ident, program_options_ident)
+            }
+            Self::Parameter(item) => {
+                item.gen_push_changes(old_ident, new_ident, changes_ident, program_options_ident)
+            }
+            Self::Repeat(item) => {
+                item.gen_push_changes(old_ident, new_ident, changes_ident, program_options_ident)
+            }
+            Self::Flatten(item) => {
+                item.gen_push_changes(old_ident, new_ident, changes_ident, program_options_ident)
+            }
+            Self::Subcommands(item) => {
+                item.gen_push_changes(old_ident, new_ident, changes_ident, program_options_ident)
+            }
+        }
+    }
+
+    /// Generate code that formats the value of this field in `self` and pushes it onto
+    /// values_ident, which is a `&mut conf::FormattedValues`
+    pub fn gen_push_formatted_values(&self, values_ident: &Ident) -> Result<TokenStream, Error> {
+        match self {
+            Self::Flag(item) => item.gen_push_formatted_values(values_ident),
+            Self::Parameter(item) => item.gen_push_formatted_values(values_ident),
+            Self::Repeat(item) => item.gen_push_formatted_values(values_ident),
+            Self::Flatten(item) => item.gen_push_formatted_values(values_ident),
+            Self::Subcommands(item) => item.gen_push_formatted_values(values_ident),
+        }
+    }
+
     /// Generate code for a struct initializer for this field, reading from conf_context
     ///
     /// Returns:
@@ -372,6 +413,21 @@ impl FieldItem {
             errors_ident,
         )?;
 
+        // References in the document value are resolved as it is read. If the value parser is used,
+        // the string is resolved just before the value parser instead, like values from any
+        // other source.
+        let next_doc_val = if self.get_serde_use_value_parser() {
+            quote! {
+              #map_access.next_value::<#serde_type>().map_err(|__err__| {
+                InnerError::serde(#ctxt.document_name, #field_name_str, __err__)
+              })
+            }
+        } else {
+            quote! {
+              #ctxt.next_document_value::<#serde_type, _>(&mut #map_access, #field_name_str)
+            }
+        };
+
         let match_arm = quote! {
           #serde_name_str => {
             if #field_name.is_some() {
@@ -383,20 +439,14 @@ impl FieldItem {
                 )
               );
             } else {
-              #field_name = Some(match #map_access.next_value::<#serde_type>() {
+              #field_name = Some(match #next_doc_val {
                 Ok(#doc_val_ident) => {
                   let #conf_context_ident: &::conf::ConfContext = &#ctxt.conf_context;
                   let #doc_name_ident: &str = #ctxt.document_name;
                   #initializer
                 }
                 Err(__err__) => {
-                  #errors_ident.push(
-                    InnerError::serde(
-                      #ctxt.document_name,
-                      #field_name_str,
-                      __err__
-                    )
-                  );
+                  #errors_ident.push(__err__);
                   None
                 }
               });
@@ -406,6 +456,44 @@ impl FieldItem {
         Ok((match_arm, vec![serde_name_str]))
     }
 
+    /// Generate code that pushes a `conf::DocumentField` describing this field onto
+    /// document_fields_ident, unless this field is serde(skip).
+    pub fn gen_push_document_fields(
+        &self,
+        document_fields_ident: &Ident,
+    ) -> Result<TokenStream, Error> {
+        if self.get_serde_skip() {
+            return Ok(quote! {});
+        }
+        match self {
+            Self::Flag(_) | Self::Parameter(_) | Self::Repeat(_) => {
+                let serde_name = self.get_serde_name();
+                let id = self.get_field_name().to_string();
+                let value_type = self.get_document_value_type();
+                Ok(quote! {
+                    #document_fields_ident.push(::conf::DocumentField::Option {
+                        serde_name: #serde_name,
+                        id: #id,
+                        value_type: #value_type,
+                    });
+                })
+            }
+            Self::Flatten(item) => item.gen_push_document_fields(document_fields_ident),
+            Self::Subcommands(item) => item.gen_push_document_fields(document_fields_ident),
+        }
+    }
+
+    /// Get the document value type (only when "is_single_option" is true)
+    fn get_document_value_type(&self) -> TokenStream {
+        match self {
+            Self::Flag(item) => item.get_document_value_type(),
+            Self::Parameter(item) => item.get_document_value_type(),
+            Self::Repeat(item) => item.get_document_value_type(),
+            Self::Flatten(_item) => unimplemented!(),
+            Self::Subcommands(_item) => unimplemented!(),
+        }
+    }
+
     /// Get the serde name (only when "is_single_option" is true)
     fn get_serde_name(&self) -> LitStr {
         match self {
@@ -428,6 +516,17 @@ impl FieldItem {
         }
     }
 
+    /// Get the serde(use_value_parser) option
+    fn get_serde_use_value_parser(&self) -> bool {
+        match self {
+            Self::Flag(_) => false,
+            Self::Parameter(item) => item.get_serde_use_value_parser(),
+            Self::Repeat(item) => item.get_serde_use_value_parser(),
+            Self::Flatten(_item) => unimplemented!(),
+            Self::Subcommands(_item) => unimplemented!(),
+        }
+    }
+
     /// Get the serde(skip) option
     fn get_serde_skip(&self) -> bool {
         match self {
@@ -62,13 +62,20 @@ pub struct ParameterItem {
     is_optional_type: Option<Type>,
     allow_hyphen_values: bool,
     secret: Option<LitBool>,
+    secret_options: SecretOptions,
     short_switch: Option<LitChar>,
     long_switch: Option<LitStr>,
     aliases: Option<LitStrArray>,
     env_name: Option<LitStr>,
     env_aliases: Option<LitStrArray>,
+    deprecated_env_aliases: Option<LitStrArray>,
+    deprecated: Option<LitStr>,
+    hide: bool,
+    hide_env_value: bool,
+    env_cmd: Option<LitStr>,
     default_value: Option<LitStr>,
     value_parser: Option<Expr>,
+    value_formatter: Option<Expr>,
     serde: Option<ParameterSerdeItem>,
     doc_string: Option<String>,
 }
@@ -90,13 +97,20 @@ impl ParameterItem {
             is_optional_type,
             allow_hyphen_values,
             secret: None,
+            secret_options: SecretOptions::default(),
             short_switch: None,
             long_switch: None,
             aliases: None,
             env_name: None,
             env_aliases: None,
+            deprecated_env_aliases: None,
+            deprecated: None,
+            hide: false,
+            hide_env_value: false,
+            env_cmd: None,
             default_value: None,
             value_parser: None,
+            value_formatter: None,
             serde: None,
             doc_string: None,
         };
@@ -136,10 +150,27 @@ impl ParameterItem {
                                 .or(make_env(&result.field_name, path.span())),
                         )
                     } else if path.is_ident("env_aliases") {
+                        parse_env_aliases(
+                            meta,
+                            &mut result.env_aliases,
+                            &mut result.deprecated_env_aliases,
+                        )
+                    } else if path.is_ident("hide") {
+                        result.hide = true;
+                        Ok(())
+                    } else if path.is_ident("hide_env_value") {
+                        result.hide_env_value = true;
+                        Ok(())
+                    } else if path.is_ident("deprecated") {
+                        set_once(&path, &mut result.deprecated, Some(parse_deprecated(meta)?))
+                    } else if path.is_ident("env_cmd") {
+                        // If no name is given, it is based on the env name, which is resolved
+                        // after all attributes are parsed. Empty string marks that case.
                         set_once(
                             &path,
-                            &mut result.env_aliases,
-                            Some(parse_required_value::<LitStrArray>(meta)?),
+                            &mut result.env_cmd,
+                            parse_optional_value::<LitStr>(meta)?
+                                .or(Some(LitStr::new("", path.span()))),
                         )
                     } else if path.is_ident("default_value") {
                         let val = meta.value()?.parse::<LitStr>()?;
@@ -150,18 +181,18 @@ impl ParameterItem {
                             &mut result.value_parser,
                             Some(parse_required_value::<Expr>(meta)?),
                         )
+                    } else if path.is_ident("value_formatter") {
+                        set_once(
+                            &path,
+                            &mut result.value_formatter,
+                            Some(parse_required_value::<Expr>(meta)?),
+                        )
                     } else if path.is_ident("allow_hyphen_values") {
                         result.allow_hyphen_values = true;
                         Ok(())
                     } else if path.is_ident("secret") {
-                        set_once(
-                            &path,
-                            &mut result.secret,
-                            Some(
-                                parse_optional_value::<LitBool>(meta)?
-                                    .unwrap_or(LitBool::new(true, path.span())),
-                            ),
-                        )
+                        let secret = parse_secret(meta, &mut result.secret_options)?;
+                        set_once(&path, &mut result.secret, Some(secret))
                     } else if path.is_ident("serde") {
                         set_once(
                             &path,
@@ -179,6 +210,7 @@ impl ParameterItem {
             && result.short_switch.is_none()
             && result.long_switch.is_none()
             && result.env_name.is_none()
+            && result.env_cmd.is_none()
             && result.default_value.is_none()
             && struct_item.serde.is_none()
         {
@@ -205,11 +237,10 @@ impl ParameterItem {
         }
 
         if result.env_name.is_none()
-            && !result
-                .env_aliases
-                .as_ref()
-                .map(LitStrArray::is_empty)
-                .unwrap_or(true)
+            && [&result.env_aliases, &result.deprecated_env_aliases]
+                .into_iter()
+                .flatten()
+                .any(|env_aliases| !env_aliases.is_empty())
         {
             return Err(Error::new(
                 field.span(),
@@ -218,6 +249,31 @@ impl ParameterItem {
             ));
         }
 
+        if let Some(env_cmd) = result.env_cmd.as_mut() {
+            if env_cmd.value().is_empty() {
+                let Some(env_name) = result.env_name.as_ref() else {
+                    return Err(Error::new(
+                        env_cmd.span(),
+                        "Setting env_cmd without a name requires setting an env, \
+                        or specify the env var name with env_cmd = \"...\".",
+                    ));
+                };
+                *env_cmd = LitStr::new(&(env_name.value() + "_CMD"), env_cmd.span());
+            }
+        }
+
+        // conf::Secret<T> implies secret, unless secret was explicitly set
+        let inner_type = result
+            .is_optional_type
+            .as_ref()
+            .unwrap_or(&result.field_type);
+        if result.secret.is_none() && type_is_secret(inner_type) {
+            if result.short_switch.is_some() || result.long_switch.is_some() {
+                return Err(secret_type_with_switch_error(field));
+            }
+            result.secret = Some(LitBool::new(true, field.ty.span()));
+        }
+
         Ok(result)
     }
 
@@ -240,14 +296,15 @@ impl ParameterItem {
             .unwrap_or_else(|| LitStr::new(&self.field_name.to_string(), self.field_name.span()))
     }
 
-    pub fn get_serde_type(&self) -> Type {
-        let use_value_parser = self
-            .serde
+    pub fn get_serde_use_value_parser(&self) -> bool {
+        self.serde
             .as_ref()
             .map(|serde| serde.use_value_parser)
-            .unwrap_or(false);
+            .unwrap_or(false)
+    }
 
-        if use_value_parser {
+    pub fn get_serde_type(&self) -> Type {
+        if self.get_serde_use_value_parser() {
             parse_quote! { ::std::string::String }
         } else {
             self.field_type.clone()
@@ -258,6 +315,21 @@ impl ParameterItem {
         self.serde.as_ref().map(|serde| serde.skip).unwrap_or(false)
     }
 
+    pub fn get_document_value_type(&self) -> TokenStream {
+        let use_value_parser = self
+            .serde
+            .as_ref()
+            .map(|serde| serde.use_value_parser)
+            .unwrap_or(false);
+
+        if use_value_parser {
+            quote! { ::conf::DocumentValueType::String }
+        } else {
+            let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+            gen_document_value_type(inner_type)
+        }
+    }
+
     pub fn gen_push_program_options(
         &self,
         program_options_ident: &Ident,
@@ -269,13 +341,21 @@ impl ParameterItem {
         let long_form = quote_opt_into(&self.long_switch);
         let aliases = self.aliases.as_ref().map(LitStrArray::quote_elements_into);
         let env_form = quote_opt_into(&self.env_name);
-        let env_aliases = self
-            .env_aliases
+        let env_aliases =
+            quote_all_elements_into([&self.env_aliases, &self.deprecated_env_aliases]);
+        let deprecated_env_aliases = self
+            .deprecated_env_aliases
             .as_ref()
             .map(LitStrArray::quote_elements_into);
+        let deprecated = quote_opt_into(&self.deprecated);
+        let hide = self.hide;
+        let hide_env_value = self.hide_env_value;
+        let env_cmd = quote_opt_into(&self.env_cmd);
         let default_value = quote_opt_into(&self.default_value);
         let allow_hyphen_values = self.allow_hyphen_values;
         let secret = quote_opt(&self.secret);
+        let secret_allow_cli = self.secret_options.allow_cli;
+        let secret_warn_cli = self.secret_options.warn_cli;
 
         Ok(quote! {
             #program_options_ident.push(::conf::ProgramOption {
@@ -287,10 +367,18 @@ impl ParameterItem {
                 aliases: vec![#aliases],
                 env_form: #env_form,
                 env_aliases: vec![#env_aliases],
+                env_cmd: #env_cmd,
                 default_value: #default_value,
                 is_required: #is_required,
                 allow_hyphen_values: #allow_hyphen_values,
                 secret: #secret,
+                secret_allow_cli: #secret_allow_cli,
+                secret_warn_cli: #secret_warn_cli,
+                deprecated: #deprecated,
+                deprecated_env_aliases: vec![#deprecated_env_aliases],
+                help_heading: None,
+                hidden: #hide,
+                hide_env_value: #hide_env_value,
             });
         })
     }
@@ -368,7 +456,9 @@ impl ParameterItem {
                 #if_no_conf_context_val
               };
             #before_value_parser
-            match __value_parser__(val_str) {
+            // If val_str is a reference to be resolved, the error still shows the reference
+            let resolved = #conf_context_ident.resolve(&value_source, val_str, opt)?;
+            match __value_parser__(&resolved) {
               #value_parser_ok_arm
               Err(err) => Err(
                 InnerError::invalid_value(
@@ -411,12 +501,21 @@ impl ParameterItem {
             .map(|serde| serde.use_value_parser)
             .unwrap_or(false);
 
+        let id = self.field_name.to_string();
+
         if use_value_parser {
             // When use_value_parser is true, then #doc_val has type String.
             // To pick this value for the field, we have to set value_source and val_str
             // to indicate that we are selecting the document value.
             let if_no_conf_context_val = quote! {
-              (ConfValueSource::Document(#doc_name), #doc_val.as_str())
+              {
+                #conf_context_ident.record_document_value(
+                  #id,
+                  #doc_name,
+                  || ::conf::report::ReportValue::String(#doc_val.clone())
+                );
+                (ConfValueSource::Document(#doc_name), #doc_val.as_str())
+              }
             };
 
             // When the value source is a default, but we have a doc val,
@@ -437,7 +536,13 @@ impl ParameterItem {
         } else {
             // When use_value_parser is false, then #doc_val has type #field_type.
             // To pick this value for the field, we just return it.
+            // There is no string form of this value to record.
             let if_no_conf_context_val = quote! {
+              #conf_context_ident.record_document_value(
+                #id,
+                #doc_name,
+                || ::conf::report::ReportValue::Opaque
+              );
               return Ok(#doc_val);
             };
 
@@ -456,4 +561,56 @@ impl ParameterItem {
             )
         }
     }
+
+    pub fn gen_push_changes(
+        &self,
+        old_ident: &Ident,
+        new_ident: &Ident,
+        changes_ident: &Ident,
+        program_options_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+
+        // Optional parameters are unset when they are None
+        let get_value = if self.is_optional_type.is_some() {
+            quote! { and_then(|s| s.#field_name.as_ref()) }
+        } else {
+            quote! { map(|s| &s.#field_name) }
+        };
+
+        Ok(quote! {
+            ::conf::report::ConfChange::push_option(
+                #changes_ident,
+                #program_options_ident,
+                #id,
+                #old_ident.#get_value.map(|v| (&&&&DiffValue(v)).diff_render()).unwrap_or(::conf::report::ReportValue::Unset),
+                #new_ident.#get_value.map(|v| (&&&&DiffValue(v)).diff_render()).unwrap_or(::conf::report::ReportValue::Unset),
+                (&&DiffEq(#old_ident.#get_value, #new_ident.#get_value)).secret_changed(),
+            );
+        })
+    }
+
+    pub fn gen_push_formatted_values(
+        &self,
+        values_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+        let val_ident = Ident::new("__val__", Span::call_site());
+        let format_val = gen_format_value(&self.value_formatter, &val_ident, &self.field_type);
+
+        Ok(if self.is_optional_type.is_some() {
+            quote! {
+                #values_ident.push_value(#id, self.#field_name.as_ref().map(|#val_ident| #format_val));
+            }
+        } else {
+            quote! {
+                #values_ident.push_value(#id, Some({
+                    let #val_ident = &self.#field_name;
+                    #format_val
+                }));
+            }
+        })
+    }
 }
@@ -61,11 +61,18 @@ pub struct RepeatItem {
     field_type: Type, // This is needed to help with type inference in code gen
     allow_hyphen_values: bool,
     secret: Option<LitBool>,
+    secret_options: SecretOptions,
     long_switch: Option<LitStr>,
     aliases: Option<LitStrArray>,
     env_name: Option<LitStr>,
     env_aliases: Option<LitStrArray>,
+    deprecated_env_aliases: Option<LitStrArray>,
+    deprecated: Option<LitStr>,
+    hide: bool,
+    hide_env_value: bool,
+    env_cmd: Option<LitStr>,
     value_parser: Option<Expr>,
+    value_formatter: Option<Expr>,
     env_delimiter: Option<LitChar>,
     no_env_delimiter: bool,
     serde: Option<RepeatSerdeItem>,
@@ -93,11 +100,18 @@ impl RepeatItem {
             field_type,
             allow_hyphen_values,
             secret: None,
+            secret_options: SecretOptions::default(),
             long_switch: None,
             aliases: None,
             env_name: None,
             env_aliases: None,
+            deprecated_env_aliases: None,
+            deprecated: None,
+            hide: false,
+            hide_env_value: false,
+            env_cmd: None,
             value_parser: None,
+            value_formatter: None,
             env_delimiter: None,
             no_env_delimiter: false,
             serde: None,
@@ -132,10 +146,27 @@ impl RepeatItem {
                                 .or(make_env(&result.field_name, path.span())),
                         )
                     } else if path.is_ident("env_aliases") {
+                        parse_env_aliases(
+                            meta,
+                            &mut result.env_aliases,
+                            &mut result.deprecated_env_aliases,
+                        )
+                    } else if path.is_ident("hide") {
+                        result.hide = true;
+                        Ok(())
+                    } else if path.is_ident("hide_env_value") {
+                        result.hide_env_value = true;
+                        Ok(())
+                    } else if path.is_ident("deprecated") {
+                        set_once(&path, &mut result.deprecated, Some(parse_deprecated(meta)?))
+                    } else if path.is_ident("env_cmd") {
+                        // If no name is given, it is based on the env name, which is resolved
+                        // after all attributes are parsed. Empty string marks that case.
                         set_once(
                             &path,
-                            &mut result.aliases,
-                            Some(parse_required_value::<LitStrArray>(meta)?),
+                            &mut result.env_cmd,
+                            parse_optional_value::<LitStr>(meta)?
+                                .or(Some(LitStr::new("", path.span()))),
                         )
                     } else if path.is_ident("value_parser") {
                         set_once(
@@ -143,6 +174,12 @@ impl RepeatItem {
                             &mut result.value_parser,
                             Some(parse_required_value::<Expr>(meta)?),
                         )
+                    } else if path.is_ident("value_formatter") {
+                        set_once(
+                            &path,
+                            &mut result.value_formatter,
+                            Some(parse_required_value::<Expr>(meta)?),
+                        )
                     } else if path.is_ident("env_delimiter") {
                         set_once(
                             &path,
@@ -156,14 +193,8 @@ impl RepeatItem {
                         result.allow_hyphen_values = true;
                         Ok(())
                     } else if path.is_ident("secret") {
-                        set_once(
-                            &path,
-                            &mut result.secret,
-                            Some(
-                                parse_optional_value::<LitBool>(meta)?
-                                    .unwrap_or(LitBool::new(true, path.span())),
-                            ),
-                        )
+                        let secret = parse_secret(meta, &mut result.secret_options)?;
+                        set_once(&path, &mut result.secret, Some(secret))
                     } else if path.is_ident("serde") {
                         set_once(&path, &mut result.serde, Some(RepeatSerdeItem::new(meta)?))
                     } else {
@@ -209,11 +240,10 @@ impl RepeatItem {
         }
 
         if result.env_name.is_none()
-            && !result
-                .env_aliases
-                .as_ref()
-                .map(LitStrArray::is_empty)
-                .unwrap_or(true)
+            && [&result.env_aliases, &result.deprecated_env_aliases]
+                .into_iter()
+                .flatten()
+                .any(|env_aliases| !env_aliases.is_empty())
         {
             return Err(Error::new(
                 field.span(),
@@ -222,6 +252,27 @@ impl RepeatItem {
             ));
         }
 
+        if let Some(env_cmd) = result.env_cmd.as_mut() {
+            if env_cmd.value().is_empty() {
+                let Some(env_name) = result.env_name.as_ref() else {
+                    return Err(Error::new(
+                        env_cmd.span(),
+                        "Setting env_cmd without a name requires setting an env, \
+                        or specify the env var name with env_cmd = \"...\".",
+                    ));
+                };
+                *env_cmd = LitStr::new(&(env_name.value() + "_CMD"), env_cmd.span());
+            }
+        }
+
+        // Vec<conf::Secret<T>> implies secret, unless secret was explicitly set
+        if result.secret.is_none() && type_is_secret(&inner_type) {
+            if result.long_switch.is_some() {
+                return Err(secret_type_with_switch_error(field));
+            }
+            result.secret = Some(LitBool::new(true, field.ty.span()));
+        }
+
         Ok(result)
     }
 
@@ -240,14 +291,15 @@ impl RepeatItem {
             .unwrap_or_else(|| LitStr::new(&self.field_name.to_string(), self.field_name.span()))
     }
 
-    pub fn get_serde_type(&self) -> Type {
-        let use_value_parser = self
-            .serde
+    pub fn get_serde_use_value_parser(&self) -> bool {
+        self.serde
             .as_ref()
             .map(|serde| serde.use_value_parser)
-            .unwrap_or(false);
+            .unwrap_or(false)
+    }
 
-        if use_value_parser {
+    pub fn get_serde_type(&self) -> Type {
+        if self.get_serde_use_value_parser() {
             parse_quote! { ::std::vec::Vec<::std::string::String> }
         } else {
             self.field_type.clone()
@@ -258,6 +310,20 @@ impl RepeatItem {
         self.serde.as_ref().map(|serde| serde.skip).unwrap_or(false)
     }
 
+    pub fn get_document_value_type(&self) -> TokenStream {
+        let use_value_parser = self
+            .serde
+            .as_ref()
+            .map(|serde| serde.use_value_parser)
+            .unwrap_or(false);
+
+        let inner = match type_is_vec(&self.field_type) {
+            Ok(Some(inner_type)) if !use_value_parser => gen_document_value_type(&inner_type),
+            _ => quote! { ::conf::DocumentValueType::String },
+        };
+        quote! { ::conf::DocumentValueType::Array(::std::boxed::Box::new(#inner)) }
+    }
+
     /// Generate a routine that pushes a ::conf::ProgramOption corresponding to
     /// this field, onto a mut Vec<ProgramOption> that is in scope.
     ///
@@ -272,12 +338,20 @@ impl RepeatItem {
         let long_form = quote_opt_into(&self.long_switch);
         let aliases = self.aliases.as_ref().map(LitStrArray::quote_elements_into);
         let env_form = quote_opt_into(&self.env_name);
-        let env_aliases = self
-            .env_aliases
+        let env_aliases =
+            quote_all_elements_into([&self.env_aliases, &self.deprecated_env_aliases]);
+        let deprecated_env_aliases = self
+            .deprecated_env_aliases
             .as_ref()
             .map(LitStrArray::quote_elements_into);
+        let deprecated = quote_opt_into(&self.deprecated);
+        let hide = self.hide;
+        let hide_env_value = self.hide_env_value;
+        let env_cmd = quote_opt_into(&self.env_cmd);
         let allow_hyphen_values = self.allow_hyphen_values;
         let secret = quote_opt(&self.secret);
+        let secret_allow_cli = self.secret_options.allow_cli;
+        let secret_warn_cli = self.secret_options.warn_cli;
 
         Ok(quote! {
             #program_options_ident.push(::conf::ProgramOption {
@@ -289,10 +363,18 @@ impl RepeatItem {
               aliases: vec![#aliases],
               env_form: #env_form,
               env_aliases: vec![#env_aliases],
+              env_cmd: #env_cmd,
               default_value: None,
               is_required: false,
               allow_hyphen_values: #allow_hyphen_values,
               secret: #secret,
+              secret_allow_cli: #secret_allow_cli,
+              secret_warn_cli: #secret_warn_cli,
+              deprecated: #deprecated,
+              deprecated_env_aliases: vec![#deprecated_env_aliases],
+              help_heading: None,
+              hidden: #hide,
+              hide_env_value: #hide_env_value,
             });
         })
     }
@@ -367,7 +449,15 @@ impl RepeatItem {
             let mut errors = Vec::<InnerError>::new();
             result.reserve(strs.len());
             for val_str in strs {
-              match __value_parser__(val_str) {
+              // If val_str is a reference to be resolved, the error still shows the reference
+              let resolved = match #conf_context_ident.resolve(&value_source, val_str, opt) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                  errors.push(err);
+                  continue;
+                }
+              };
+              match __value_parser__(&resolved) {
                 Ok(val) => result.push(val),
                 Err(err) => errors.push(
                   InnerError::invalid_value(
@@ -410,6 +500,7 @@ impl RepeatItem {
         doc_name: &Ident,
         doc_val: &Ident,
     ) -> Result<(TokenStream, bool), Error> {
+        let id = self.field_name.to_string();
         let use_value_parser = self
             .serde
             .as_ref()
@@ -422,6 +513,11 @@ impl RepeatItem {
             // and #doc_val is a `Vec<String>`.
             let before_value_parser = quote! {
               let (value_source, strs) = if value_source.is_default() {
+                #conf_context_ident.record_document_value(
+                  #id,
+                  #doc_name,
+                  || ::conf::report::ReportValue::List(#doc_val.clone())
+                );
                 (ConfValueSource::Document(#doc_name), #doc_val.iter().map(String::as_str).collect())
               } else {
                 (value_source, strs)
@@ -432,8 +528,14 @@ impl RepeatItem {
         } else {
             // When use_value_parser is not enabled, the behavior is, if conf context produced a
             // default value, we should instead simply return the doc value.
+            // There is no string form of this value to record.
             let before_value_parser = quote! {
               if value_source.is_default() {
+                #conf_context_ident.record_document_value(
+                  #id,
+                  #doc_name,
+                  || ::conf::report::ReportValue::Opaque
+                );
                 return Ok(#doc_val);
               }
             };
@@ -441,4 +543,45 @@ impl RepeatItem {
             self.gen_initializer_helper(conf_context_ident, Some(before_value_parser))
         }
     }
+
+    pub fn gen_push_changes(
+        &self,
+        old_ident: &Ident,
+        new_ident: &Ident,
+        changes_ident: &Ident,
+        program_options_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+
+        Ok(quote! {
+            ::conf::report::ConfChange::push_option(
+                #changes_ident,
+                #program_options_ident,
+                #id,
+                #old_ident.map(|o| ::conf::report::ReportValue::list(o.#field_name.iter().map(|v| (&&&&DiffValue(v)).diff_render()))).unwrap_or(::conf::report::ReportValue::Unset),
+                #new_ident.map(|n| ::conf::report::ReportValue::list(n.#field_name.iter().map(|v| (&&&&DiffValue(v)).diff_render()))).unwrap_or(::conf::report::ReportValue::Unset),
+                (&&DiffEq(#old_ident.map(|o| &o.#field_name), #new_ident.map(|n| &n.#field_name))).secret_changed(),
+            );
+        })
+    }
+
+    pub fn gen_push_formatted_values(
+        &self,
+        values_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+        let delimiter = self.get_delimiter();
+        let val_ident = Ident::new("__val__", Span::call_site());
+        let format_val = gen_format_value(&self.value_formatter, &val_ident, &self.field_type);
+
+        Ok(quote! {
+            #values_ident.push_values(
+                #id,
+                self.#field_name.iter().map(|#val_ident| #format_val).collect(),
+                #delimiter,
+            );
+        })
+    }
 }
@@ -209,7 +209,7 @@ impl SubcommandsItem {
         // * Otherwise, we are attempting to recurse into the subcommand.
         let match_arm = quote! {
           key__ if <#inner_type as SubcommandsSerde>::SERDE_NAMES.iter().any(|(_c, s)| *s == key__) => {
-            let Some((command_name, conf_context_serde)) = #ctxt.for_subcommand() else { continue };
+            let Some((command_name, conf_context_serde)) = #ctxt.for_subcommand(key__) else { continue };
 
             let Some((static_command_name, static_serde_name)) = <#inner_type as SubcommandsSerde>::SERDE_NAMES.iter().find(|(c, s)| *c == command_name && *s == key__) else { continue };
 
@@ -238,4 +238,79 @@ impl SubcommandsItem {
         // proc_macro invocation for the enum.
         Ok((match_arm, vec![]))
     }
+
+    // Pushes a DocumentField describing the sections of each subcommand
+    //
+    // Arguments:
+    // * document_fields_ident: identifier of a mut Vec<DocumentField> to which we can push
+    pub fn gen_push_document_fields(
+        &self,
+        document_fields_ident: &Ident,
+    ) -> Result<TokenStream, Error> {
+        let is_optional = self.is_optional_type.is_some();
+        let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+
+        Ok(quote! {
+            #document_fields_ident.push(::conf::DocumentField::Subcommands {
+                is_optional: #is_optional,
+                sections: <#inner_type as ::conf::SubcommandsSerde>::get_document_sections()?,
+            });
+        })
+    }
+
+    pub fn gen_push_changes(
+        &self,
+        old_ident: &Ident,
+        new_ident: &Ident,
+        changes_ident: &Ident,
+        _program_options_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let id = field_name.to_string();
+        let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+
+        let get_value = if self.is_optional_type.is_some() {
+            quote! { and_then(|s| s.#field_name.as_ref()) }
+        } else {
+            quote! { map(|s| &s.#field_name) }
+        };
+
+        Ok(quote! {
+            {
+                let __old_subcommand__ = #old_ident.#get_value;
+                let __new_subcommand__ = #new_ident.#get_value;
+                ::conf::report::ConfChange::push_subcommand_name(
+                    #changes_ident,
+                    #id,
+                    __old_subcommand__.map(<#inner_type as ::conf::Subcommands>::get_command_name),
+                    __new_subcommand__.map(<#inner_type as ::conf::Subcommands>::get_command_name),
+                );
+                <#inner_type as ::conf::Subcommands>::diff_helper(
+                    __old_subcommand__,
+                    __new_subcommand__,
+                    #changes_ident,
+                );
+            }
+        })
+    }
+
+    pub fn gen_push_formatted_values(
+        &self,
+        values_ident: &Ident,
+    ) -> Result<TokenStream, syn::Error> {
+        let field_name = &self.field_name;
+        let inner_type = self.is_optional_type.as_ref().unwrap_or(&self.field_type);
+
+        Ok(if self.is_optional_type.is_some() {
+            quote! {
+                if let Some(__inner__) = self.#field_name.as_ref() {
+                    <#inner_type as ::conf::SubcommandsToArgs>::format_values(__inner__, #values_ident);
+                }
+            }
+        } else {
+            quote! {
+                <#inner_type as ::conf::SubcommandsToArgs>::format_values(&self.#field_name, #values_ident);
+            }
+        })
+    }
 }
@@ -54,6 +54,7 @@ impl GenConfStruct {
             self.get_program_options_impl()?,
             self.get_subcommands_impl()?,
             self.from_conf_context_impl()?,
+            self.diff_helper_impl()?,
             self.get_name_impl()?,
         ];
 
@@ -161,6 +162,44 @@ impl GenConfStruct {
         })
     }
 
+    /// Generate Conf::diff_helper implementation
+    fn diff_helper_impl(&self) -> Result<TokenStream, Error> {
+        let old_ident = Ident::new("__old__", Span::call_site());
+        let new_ident = Ident::new("__new__", Span::call_site());
+        let changes_ident = Ident::new("__changes__", Span::call_site());
+        let program_options_ident = Ident::new("__program_options__", Span::call_site());
+        let fields_push_changes: Vec<TokenStream> = self
+            .fields
+            .iter()
+            .map(|field| {
+                field.gen_push_changes(
+                    &old_ident,
+                    &new_ident,
+                    &changes_ident,
+                    &program_options_ident,
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // If get_program_options fails, then no instance could have been parsed, but one could
+        // still have been constructed directly. Then changes just won't have switches and envs.
+        Ok(quote! {
+            #[allow(unused_variables, unused_imports)]
+            fn diff_helper(
+                #old_ident: Option<&Self>,
+                #new_ident: Option<&Self>,
+                #changes_ident: &mut Vec<::conf::report::ConfChange>,
+            ) {
+                use ::conf::report::{DiffEq, DiffEqOther as _, DiffEqSecret as _, DiffRenderDebug as _, DiffRenderDisplay as _, DiffRenderOpaque as _, DiffRenderSecret as _, DiffValue};
+
+                let #program_options_ident: &[::conf::ProgramOption] =
+                    <Self as ::conf::Conf>::get_program_options().unwrap_or(&[]);
+
+                #(#fields_push_changes)*
+            }
+        })
+    }
+
     // Generate Conf::from_conf_context implementation
     #[allow(clippy::wrong_self_convention)]
     fn from_conf_context_impl(&self) -> Result<TokenStream, Error> {
@@ -307,6 +346,43 @@ impl GenConfStruct {
         })
     }
 
+    /// Generate an impl ConfToArgs block for this struct (if requested via attributes)
+    ///
+    /// Takes generics associated to this struct.
+    pub fn maybe_gen_conf_to_args_impl(
+        &self,
+        generics: &Generics,
+    ) -> Result<Option<TokenStream>, Error> {
+        if !self.struct_item.to_args {
+            return Ok(None);
+        }
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let ident = self.struct_item.get_ident();
+        let values_ident = Ident::new("__values__", Span::call_site());
+        let fields_push_formatted_values: Vec<TokenStream> = self
+            .fields
+            .iter()
+            .map(|field| field.gen_push_formatted_values(&values_ident))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Some(quote! {
+            #[automatically_derived]
+            #[allow(
+                unused_qualifications,
+            )]
+            impl #impl_generics ::conf::ConfToArgs for #ident #ty_generics #where_clause {
+                #[allow(unused_variables, unused_imports)]
+                fn format_values(&self, #values_ident: &mut ::conf::FormattedValues) {
+                    use ::conf::{FormatDisplay as _, FormatOsStr as _, FormatSecret as _};
+
+                    #(#fields_push_formatted_values)*
+                }
+            }
+        }))
+    }
+
     /// Generate an impl ConfSerde block for this struct (if requested via attributes)
     /// Also, the requisite DeserializeSeed impl's and such.
     ///
@@ -359,6 +435,7 @@ impl GenConfStruct {
 
         let visitor_impl = self.gen_serde_visitor_impl(&seed_ident, generics)?;
         let deserialize_seed_impl = self.gen_serde_deserialize_seed_impl(&seed_ident, generics)?;
+        let get_document_struct_impl = self.gen_get_document_struct_impl()?;
 
         // These generics are used to impl ConfSerde on the user's type.
         let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
@@ -395,11 +472,44 @@ impl GenConfStruct {
 
                 impl #impl_generics ConfSerde for #ident #ty_generics #where_clause {
                     type Seed<#ct> = #seed_ident #seed_generics;
+
+                    #get_document_struct_impl
                 }
             };
         }))
     }
 
+    /// Generate ConfSerde::get_document_struct implementation
+    /// Panics if serde was not requested on this struct
+    fn gen_get_document_struct_impl(&self) -> Result<TokenStream, Error> {
+        let serde_opts = self.struct_item.serde.as_ref().unwrap();
+        let allow_unknown_fields = serde_opts.allow_unknown_fields;
+        let struct_name = self.struct_item.get_ident().to_string();
+
+        let document_fields_ident = Ident::new("__document_fields__", Span::call_site());
+        let fields_push_document_fields: Vec<TokenStream> = self
+            .fields
+            .iter()
+            .map(|field| field.gen_push_document_fields(&document_fields_ident))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(quote! {
+            fn get_document_struct() -> Result<::conf::DocumentStruct, ::conf::Error> {
+                let mut #document_fields_ident = ::std::vec::Vec::<::conf::DocumentField>::new();
+
+                #(#fields_push_document_fields)*
+
+                Ok(::conf::DocumentStruct {
+                    name: #struct_name,
+                    description: <Self as ::conf::Conf>::get_parser_config()?.about,
+                    allow_unknown_fields: #allow_unknown_fields,
+                    program_options: <Self as ::conf::Conf>::get_program_options()?,
+                    fields: #document_fields_ident,
+                })
+            }
+        })
+    }
+
     // Helper which generates the tuple type used as the serde::Visitor::Value,
     // the "output type" of the visitor.
     //
@@ -483,18 +593,31 @@ impl GenConfStruct {
             None
         };
 
+        // The value of an unknown key must still be consumed, so that the map access can continue.
         let handle_unknown_field = if !serde_opts.allow_unknown_fields {
-            Some(quote! {
+            quote! {
+                if let Err(__err__) = #map_access_ident.next_value::<de::IgnoredAny>() {
+                    #errors_ident.push(
+                       InnerError::serde(#conf_serde_context_ident.document_name, #ident_str, __err__)
+                    );
+                }
                 #errors_ident.push(
-                   InnerError::serde(
+                   InnerError::unknown_document_key(
                      #conf_serde_context_ident.document_name,
-                     #ident_str,
-                     #map_access_type::Error::unknown_field(__other__, &[ #(#serde_names),* ])
+                     &#conf_serde_context_ident.document_path_of(__other__),
+                     __other__,
+                     &[ #(#serde_names),* ]
                    )
                 );
-            })
+            }
         } else {
-            None
+            quote! {
+                if let Err(__err__) = #map_access_ident.next_value::<de::IgnoredAny>() {
+                    #errors_ident.push(
+                       InnerError::serde(#conf_serde_context_ident.document_name, #ident_str, __err__)
+                    );
+                }
+            }
         };
 
         Ok(quote! {
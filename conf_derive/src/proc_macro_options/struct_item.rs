@@ -3,7 +3,7 @@ use crate::util::*;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use std::{cmp::Ordering, collections::HashMap};
-use syn::{meta::ParseNestedMeta, token, Attribute, Error, Expr, Ident, LitStr};
+use syn::{meta::ParseNestedMeta, spanned::Spanned, token, Attribute, Error, Expr, Ident, LitStr};
 
 /// #[conf(serde(...))] options listed on a struct which has `#[derive(Conf)]`
 pub struct StructSerdeItem {
@@ -48,8 +48,12 @@ pub struct StructItem {
     pub about: Option<LitStr>,
     pub name: Option<LitStr>,
     pub no_help_flag: bool,
+    pub print_config_flag: Option<LitStr>,
     pub env_prefix: Option<LitStr>,
+    pub deny_unknown_env: bool,
+    pub secret_heuristics: bool,
     pub serde: Option<StructSerdeItem>,
+    pub to_args: bool,
     pub one_of_fields: Vec<(Ordering, List<Ident>)>,
     pub validation_predicates: Vec<Expr>,
     pub doc_string: Option<String>,
@@ -63,13 +67,19 @@ impl StructItem {
             about: None,
             name: None,
             no_help_flag: false,
+            print_config_flag: None,
             env_prefix: None,
+            deny_unknown_env: false,
+            secret_heuristics: false,
             serde: None,
+            to_args: false,
             one_of_fields: Vec::default(),
             validation_predicates: Vec::default(),
             doc_string: None,
         };
 
+        let mut deny_unknown_env_span = None;
+
         for attr in attrs {
             maybe_append_doc_string(&mut result.doc_string, &attr.meta)?;
             if attr.path().is_ident("conf") {
@@ -78,6 +88,13 @@ impl StructItem {
                     if path.is_ident("no_help_flag") {
                         result.no_help_flag = true;
                         Ok(())
+                    } else if path.is_ident("print_config_flag") {
+                        set_once(
+                            &path,
+                            &mut result.print_config_flag,
+                            parse_optional_value::<LitStr>(meta)?
+                                .or(Some(LitStr::new("print-config", path.span()))),
+                        )
                     } else if path.is_ident("about") {
                         set_once(
                             &path,
@@ -96,8 +113,18 @@ impl StructItem {
                             &mut result.env_prefix,
                             Some(parse_required_value::<LitStr>(meta)?),
                         )
+                    } else if path.is_ident("deny_unknown_env") {
+                        result.deny_unknown_env = true;
+                        deny_unknown_env_span = Some(path.span());
+                        Ok(())
+                    } else if path.is_ident("secret_heuristics") {
+                        result.secret_heuristics = true;
+                        Ok(())
                     } else if path.is_ident("serde") {
                         set_once(&path, &mut result.serde, Some(StructSerdeItem::new(meta)?))
+                    } else if path.is_ident("to_args") {
+                        result.to_args = true;
+                        Ok(())
                     } else if path.is_ident("validation_predicate") {
                         result
                             .validation_predicates
@@ -137,6 +164,15 @@ impl StructItem {
             }
         }
 
+        if let Some(span) = deny_unknown_env_span {
+            if result.env_prefix.is_none() {
+                return Err(Error::new(
+                    span,
+                    "deny_unknown_env requires an env_prefix, which determines which env vars are checked",
+                ));
+            }
+        }
+
         Ok(result)
     }
 
@@ -160,22 +196,29 @@ impl StructItem {
             .map(|lit_str| lit_str.value())
             .or(self.doc_string.clone());
         let about = quote_opt(&about_text);
+        let print_config_flag = quote_opt(&self.print_config_flag);
+        let env_prefix = quote_opt(&self.env_prefix);
+        let deny_unknown_env = self.deny_unknown_env;
         Ok(quote! {
             conf::ParserConfig {
                 about: #about,
                 name: #name,
                 no_help_flag: #no_help_flag,
+                print_config_flag: #print_config_flag,
+                env_prefix: #env_prefix,
+                deny_unknown_env: #deny_unknown_env,
             }
         })
     }
 
     /// Generate an (optional) program options post-processing step.
     /// If we have an env_prefix at struct-level, apply it here.
+    /// If secret_heuristics is enabled, apply it here, after the env_prefix.
     pub fn gen_post_process_program_options(
         &self,
         program_options_ident: &Ident,
     ) -> Result<Option<TokenStream>, Error> {
-        if self.env_prefix.is_none() {
+        if self.env_prefix.is_none() && !self.secret_heuristics {
             return Ok(None);
         }
 
@@ -183,11 +226,15 @@ impl StructItem {
             .env_prefix
             .as_ref()
             .map(|env_prefix| quote! { .apply_flatten_prefixes("", "", #env_prefix, "") });
+        let apply_secret_heuristics = self
+            .secret_heuristics
+            .then(|| quote! { .apply_secret_heuristics() });
 
         Ok(Some(quote! {
             #program_options_ident = #program_options_ident.into_iter().map(
               |opt| opt
                 #apply_flatten_prefixes
+                #apply_secret_heuristics
             ).collect();
         }))
     }
@@ -7,6 +7,7 @@ use syn::{Attribute, Error, Ident};
 pub struct EnumItem {
     pub enum_ident: Ident,
     pub serde: bool,
+    pub to_args: bool,
     pub doc_string: Option<String>,
 }
 
@@ -16,6 +17,7 @@ impl EnumItem {
         let mut result = Self {
             enum_ident: enum_ident.clone(),
             serde: false,
+            to_args: false,
             doc_string: None,
         };
 
@@ -27,6 +29,9 @@ impl EnumItem {
                     if path.is_ident("serde") {
                         result.serde = true;
                         Ok(())
+                    } else if path.is_ident("to_args") {
+                        result.to_args = true;
+                        Ok(())
                     } else {
                         Err(meta.error("unrecognized conf option"))
                     }
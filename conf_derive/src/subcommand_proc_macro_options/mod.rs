@@ -49,6 +49,8 @@ impl GenSubcommandsEnum {
         let subcommands_fns = vec![
             self.get_parsers_impl()?,
             self.get_subcommand_names_impl()?,
+            self.get_command_name_impl()?,
+            self.diff_helper_impl()?,
             self.from_conf_context_impl()?,
         ];
 
@@ -99,6 +101,139 @@ impl GenSubcommandsEnum {
         })
     }
 
+    /// Generate Subcommands::get_command_name implementation
+    fn get_command_name_impl(&self) -> Result<TokenStream, syn::Error> {
+        let variant_match_arms: Vec<TokenStream> = self
+            .variants
+            .iter()
+            .map(|var| {
+                let name = var.get_name();
+                let command_name = var.get_command_name();
+                quote! {
+                    Self::#name(_) => #command_name
+                }
+            })
+            .collect();
+
+        Ok(quote! {
+          fn get_command_name(&self) -> &'static str {
+            match self {
+              #(#variant_match_arms,)*
+            }
+          }
+        })
+    }
+
+    /// Generate Subcommands::diff_helper implementation
+    ///
+    /// For each variant, the changes between the two instances of that variant are pushed, where
+    /// an instance which is not that variant counts as absent.
+    fn diff_helper_impl(&self) -> Result<TokenStream, syn::Error> {
+        let variants_push_changes: Vec<TokenStream> = self
+            .variants
+            .iter()
+            .map(|var| {
+                let name = var.get_name();
+                let command_name = var.get_command_name();
+                let ty = var.get_type();
+                quote! {
+                    {
+                        let __old_variant__ = match __old__ {
+                            Some(Self::#name(val)) => Some(val),
+                            _ => None,
+                        };
+                        let __new_variant__ = match __new__ {
+                            Some(Self::#name(val)) => Some(val),
+                            _ => None,
+                        };
+                        if __old_variant__.is_some() || __new_variant__.is_some() {
+                            let mut __inner_changes__ = Vec::new();
+                            <#ty as ::conf::Conf>::diff_helper(
+                                __old_variant__,
+                                __new_variant__,
+                                &mut __inner_changes__,
+                            );
+                            ::conf::report::ConfChange::push_subcommand(
+                                __changes__,
+                                #command_name,
+                                __inner_changes__,
+                            );
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        Ok(quote! {
+          #[allow(unreachable_patterns)]
+          fn diff_helper(
+            __old__: Option<&Self>,
+            __new__: Option<&Self>,
+            __changes__: &mut Vec<::conf::report::ConfChange>,
+          ) {
+            #(#variants_push_changes)*
+          }
+        })
+    }
+
+    /// Generate SubcommandsToArgs::format_values implementation
+    fn format_values_impl(&self) -> Result<TokenStream, syn::Error> {
+        let variant_match_arms: Vec<TokenStream> = self
+            .variants
+            .iter()
+            .map(|var| {
+                let name = var.get_name();
+                let command_name = var.get_command_name();
+                let ty = var.get_type();
+                quote! {
+                    Self::#name(val) => {
+                        let mut __inner_values__ = ::conf::FormattedValues::default();
+                        <#ty as ::conf::ConfToArgs>::format_values(val, &mut __inner_values__);
+                        __values__.set_subcommand(
+                            #command_name,
+                            <#ty as ::conf::Conf>::get_program_options().unwrap_or(&[]),
+                            __inner_values__,
+                        );
+                    }
+                }
+            })
+            .collect();
+
+        Ok(quote! {
+          fn format_values(&self, __values__: &mut ::conf::FormattedValues) {
+            match self {
+              #(#variant_match_arms)*
+            }
+          }
+        })
+    }
+
+    /// Generate a SubcommandsToArgs impl for this enum, if requested
+    pub fn maybe_gen_subcommands_to_args_impl(
+        &self,
+        generics: &Generics,
+    ) -> Result<Option<TokenStream>, syn::Error> {
+        if !self.enum_item.to_args {
+            return Ok(None);
+        }
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let ident = self.enum_item.get_ident();
+
+        let format_values_impl = self.format_values_impl()?;
+
+        Ok(Some(quote! {
+          #[automatically_derived]
+          #[allow(
+            unused_qualifications,
+          )]
+          impl #impl_generics ::conf::SubcommandsToArgs for #ident #ty_generics #where_clause {
+            #format_values_impl
+          }
+        }))
+    }
+
     /// Generate Subcommands::from_conf_context implementation
     #[allow(clippy::wrong_self_convention)]
     fn from_conf_context_impl(&self) -> Result<TokenStream, syn::Error> {
@@ -145,8 +280,11 @@ impl GenSubcommandsEnum {
 
         let ident = self.enum_item.get_ident();
 
-        let subcommands_serde_items =
-            vec![self.gen_serde_names()?, self.gen_from_conf_serde_context()?];
+        let subcommands_serde_items = vec![
+            self.gen_serde_names()?,
+            self.gen_get_document_sections()?,
+            self.gen_from_conf_serde_context()?,
+        ];
 
         Ok(Some(quote! {
           #[automatically_derived]
@@ -177,6 +315,26 @@ impl GenSubcommandsEnum {
         })
     }
 
+    fn gen_get_document_sections(&self) -> Result<TokenStream, syn::Error> {
+        let tuples: Vec<TokenStream> = self
+            .variants
+            .iter()
+            .filter(|var| !var.get_serde_skip())
+            .map(|var| {
+                let serde_name = var.get_serde_name();
+                let ty = var.get_type();
+                quote! {
+                    (#serde_name, <#ty as ::conf::ConfSerde>::get_document_struct()?)
+                }
+            })
+            .collect();
+        Ok(quote! {
+            fn get_document_sections() -> Result<Vec<(&'static str, ::conf::DocumentStruct)>, ::conf::Error> {
+                Ok(vec![ #( #tuples ),* ])
+            }
+        })
+    }
+
     fn gen_from_conf_serde_context(&self) -> Result<TokenStream, syn::Error> {
         let next_value_producer_ident = Ident::new("__next_value_producer__", Span::call_site());
 
@@ -1,11 +1,11 @@
 use heck::{ToKebabCase, ToShoutySnakeCase};
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use std::{borrow::Borrow, fmt::Display};
 use syn::{
     bracketed, meta::ParseNestedMeta, parenthesized, parse::Parse, punctuated::Punctuated,
-    spanned::Spanned, Error, Expr, ExprLit, GenericArgument, GenericParam, Generics, Lifetime,
-    LifetimeParam, Lit, LitChar, LitStr, Meta, Path, PathArguments, Token, Type,
+    spanned::Spanned, token, Error, Expr, ExprLit, GenericArgument, GenericParam, Generics, Ident,
+    Lifetime, LifetimeParam, Lit, LitBool, LitChar, LitStr, Meta, Path, PathArguments, Token, Type,
 };
 
 /// Helper for determining if a type is likely bool
@@ -75,6 +75,37 @@ pub fn type_is_vec(ty: &Type) -> Result<Option<Type>, syn::Error> {
     type_is_given_generic("Vec", ty)
 }
 
+/// Helper for determining if a type is likely conf::Secret<...>
+/// This matches `Secret<T>`, `conf::Secret<T>` and `::conf::Secret<T>`.
+pub fn type_is_secret(ty: &Type) -> bool {
+    match ty {
+        Type::Path(typepath) if typepath.qself.is_none() => {
+            let path = &typepath.path;
+            let is_conf_path = match path.segments.len() {
+                1 => path.leading_colon.is_none(),
+                2 => path.segments.first().unwrap().ident == "conf",
+                _ => false,
+            };
+            let last = path.segments.last().unwrap();
+            is_conf_path
+                && last.ident == "Secret"
+                && matches!(last.arguments, PathArguments::AngleBracketed(_))
+        }
+        _ => false,
+    }
+}
+
+/// Error for a field whose type implies `secret`, which also has a CLI switch.
+/// This would otherwise only be caught at runtime, when the parser is built.
+pub fn secret_type_with_switch_error(field: &syn::Field) -> syn::Error {
+    syn::Error::new(
+        field.span(),
+        "A field of type `Secret<T>` is a secret, and secrets can't have a `short` or `long` \
+        switch by default, because CLI args may be visible to other users of the system. \
+        Use `secret(allow_cli)` to allow this, or remove the switch.",
+    )
+}
+
 /// Helper for determining if a type is a signed number type
 pub fn type_is_signed_number(ty: &Type) -> bool {
     match ty {
@@ -92,6 +123,33 @@ pub fn type_is_signed_number(ty: &Type) -> bool {
     }
 }
 
+/// Helper which classifies a type, for describing the layout of a serde document.
+/// This returns an expression of type `conf::DocumentValueType`.
+///
+/// This only recognizes primitive types by name, anything else is `Any`.
+pub fn gen_document_value_type(ty: &Type) -> TokenStream {
+    let Type::Path(typepath) = ty else {
+        return quote! { ::conf::DocumentValueType::Any };
+    };
+    if typepath.qself.is_some() {
+        return quote! { ::conf::DocumentValueType::Any };
+    }
+    let Some(last) = typepath.path.segments.last() else {
+        return quote! { ::conf::DocumentValueType::Any };
+    };
+    if !last.arguments.is_empty() {
+        return quote! { ::conf::DocumentValueType::Any };
+    }
+    match last.ident.to_string().as_str() {
+        "bool" => quote! { ::conf::DocumentValueType::Bool },
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" => quote! { ::conf::DocumentValueType::Integer },
+        "f32" | "f64" => quote! { ::conf::DocumentValueType::Float },
+        "String" => quote! { ::conf::DocumentValueType::String },
+        _ => quote! { ::conf::DocumentValueType::Any },
+    }
+}
+
 /// Helper for reading a required value, which comes after a key, during `.parse_nested_meta`
 pub fn parse_required_value<T: Parse>(meta: ParseNestedMeta<'_>) -> Result<T, Error> {
     let t: T = meta.value()?.parse()?;
@@ -107,6 +165,80 @@ pub fn parse_optional_value<T: Parse>(meta: ParseNestedMeta<'_>) -> Result<Optio
     }
 }
 
+/// Helper for reading the `secret` attribute, during `.parse_nested_meta`.
+///
+/// This can appear as `secret`, `secret = bool`, or `secret(allow_cli, warn_cli)`, where both of
+/// the nested options are optional. `warn_cli` implies `allow_cli`.
+pub fn parse_secret(
+    meta: ParseNestedMeta<'_>,
+    options: &mut SecretOptions,
+) -> Result<LitBool, Error> {
+    let span = meta.path.span();
+    if meta.input.peek(token::Paren) {
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("allow_cli") {
+                options.allow_cli = true;
+                Ok(())
+            } else if meta.path.is_ident("warn_cli") {
+                options.allow_cli = true;
+                options.warn_cli = true;
+                Ok(())
+            } else {
+                Err(meta.error("unrecognized conf secret option"))
+            }
+        })?;
+        Ok(LitBool::new(true, span))
+    } else {
+        Ok(parse_optional_value::<LitBool>(meta)?.unwrap_or(LitBool::new(true, span)))
+    }
+}
+
+/// Helper for reading the `env_aliases` attribute, during `.parse_nested_meta`.
+///
+/// This can appear as `env_aliases = [...]`, or as `env_aliases(deprecated = [...])` for aliases
+/// which still work but produce a deprecation warning.
+pub fn parse_env_aliases(
+    meta: ParseNestedMeta<'_>,
+    env_aliases: &mut Option<LitStrArray>,
+    deprecated_env_aliases: &mut Option<LitStrArray>,
+) -> Result<(), Error> {
+    if meta.input.peek(token::Paren) {
+        meta.parse_nested_meta(|meta| {
+            let path = meta.path.clone();
+            if path.is_ident("deprecated") {
+                set_once(
+                    &path,
+                    deprecated_env_aliases,
+                    Some(parse_required_value::<LitStrArray>(meta)?),
+                )
+            } else {
+                Err(meta.error("unrecognized conf env_aliases option"))
+            }
+        })
+    } else {
+        set_once(
+            &meta.path.clone(),
+            env_aliases,
+            Some(parse_required_value::<LitStrArray>(meta)?),
+        )
+    }
+}
+
+/// Helper for reading the `deprecated` attribute, during `.parse_nested_meta`.
+///
+/// This can appear as `deprecated` or `deprecated = "note"`. If there is no note, it is empty.
+pub fn parse_deprecated(meta: ParseNestedMeta<'_>) -> Result<LitStr, Error> {
+    let span = meta.path.span();
+    Ok(parse_optional_value::<LitStr>(meta)?.unwrap_or(LitStr::new("", span)))
+}
+
+/// Additional options which can be nested in the `secret` attribute
+#[derive(Default)]
+pub struct SecretOptions {
+    pub allow_cli: bool,
+    pub warn_cli: bool,
+}
+
 /// Helper for making a default short flag for a field
 pub fn make_short(ident: &impl Display, span: Span) -> Option<LitChar> {
     let string = ident.to_string();
@@ -266,6 +398,18 @@ impl<T: Parse + ToTokens> Array<T> {
     }
 }
 
+/// Helper for quoting the elements of several optional arrays as one list of conf::CowStr, e.g.
+/// for `env_aliases` which includes the deprecated env aliases.
+pub fn quote_all_elements_into<'a, T: Parse + ToTokens + 'a>(
+    arrays: impl IntoIterator<Item = &'a Option<Array<T>>>,
+) -> TokenStream {
+    let elements = arrays
+        .into_iter()
+        .flatten()
+        .flat_map(|array| array.elements.iter());
+    quote! { #(#elements .into()),* }
+}
+
 pub type LitStrArray = Array<LitStr>;
 pub type LitCharArray = Array<LitChar>;
 
@@ -312,3 +456,22 @@ pub fn prepend_generic_lifetimes<R: Borrow<Lifetime>>(
 
     generics
 }
+
+/// Generate an expression which formats `val_ident`, a reference to a value of a parameter or
+/// repeat field, as an OsString for `ConfToArgs::to_args`.
+///
+/// This calls the `value_formatter` if one was given. Otherwise it uses `Display` or `AsRef<OsStr>`,
+/// and if the type implements neither, this is a compile error, reported at `field_type`.
+pub fn gen_format_value(
+    value_formatter: &Option<Expr>,
+    val_ident: &Ident,
+    field_type: &Type,
+) -> TokenStream {
+    if let Some(value_formatter) = value_formatter {
+        quote! { ::std::ffi::OsString::from((#value_formatter)(#val_ident)) }
+    } else {
+        quote_spanned! {field_type.span()=>
+            (&&&::conf::FormatValue(#val_ident)).format_value()
+        }
+    }
+}
@@ -1,10 +1,19 @@
-use crate::{parse_env, Conf, ConfContext, Error, InnerError, ParsedArgs, ParsedEnv};
-use std::{ffi::OsString, marker::PhantomData};
+use crate::{
+    parse_env,
+    report::{ConfigReport, Sources, ValueRecords},
+    resolver::Resolvers,
+    warning::WarningHandler,
+    Conf, ConfContext, Error, ErrorFormat, InnerError, ParsedArgs, ParsedEnv, Parser, Resolver,
+    Warning, WarningKind,
+};
+use clap::ArgMatches;
+use std::{ffi::OsString, marker::PhantomData, time::Duration};
 
 /// A builder which collects config value sources for the parse.
 ///
 /// Use any of [`ConfBuilder::args`], [`ConfBuilder::env`], [`ConfBuilder::doc`] to set sources,
-/// and then call one of [`ConfBuilder::parse`] or [`ConfBuilder::try_parse`].
+/// and then call one of [`ConfBuilder::parse`] or [`ConfBuilder::try_parse`], or one of the
+/// `_with_report` variants.
 ///
 /// If `args` is not called, the default source is `std::env::args_os`.
 /// If `env` is not called, the default source is `std::env::vars_os`.
@@ -16,6 +25,12 @@ where
     inited_env: bool,
     collected_args: Vec<OsString>,
     inited_args: bool,
+    env_cmd_timeout: Option<Duration>,
+    resolvers: Resolvers,
+    error_format: ErrorFormat,
+    deny_unknown_env: Option<bool>,
+    deny_deprecated: bool,
+    warning_handler: Option<WarningHandler>,
     _marker: PhantomData<fn() -> S>,
 }
 
@@ -29,6 +44,12 @@ where
             inited_env: false,
             collected_args: Default::default(),
             inited_args: false,
+            env_cmd_timeout: None,
+            resolvers: Default::default(),
+            error_format: Default::default(),
+            deny_unknown_env: None,
+            deny_deprecated: false,
+            warning_handler: None,
             _marker: Default::default(),
         }
     }
@@ -58,11 +79,76 @@ where
         self
     }
 
+    /// Set the timeout for commands which are run to get the value of an `env_cmd` option.
+    ///
+    /// If the command does not finish within the timeout, it is killed, and parsing fails.
+    /// The default is 10 seconds.
+    pub fn env_cmd_timeout(mut self, timeout: Duration) -> Self {
+        self.env_cmd_timeout = Some(timeout);
+        self
+    }
+
+    /// Install a [`Resolver`] for references with the given scheme, e.g. `"vault"`.
+    ///
+    /// Then any value of the form `vault://...` is passed to the resolver, and the result is used
+    /// instead. Resolution failures are reported along with any other errors.
+    ///
+    /// If several resolvers are installed for the same scheme, the first one is used.
+    pub fn resolver(mut self, scheme: &str, resolver: impl Resolver + 'static) -> Self {
+        self.resolvers.push(scheme, Box::new(resolver));
+        self
+    }
+
+    /// Set the format used when errors are printed by [`Error::print`] or [`Error::exit`], e.g.
+    /// when [`ConfBuilder::parse`] fails.
+    ///
+    /// If the env var `CONF_ERROR_FORMAT` is set to `text` or `json`, that takes precedence.
+    pub fn error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
+    }
+
+    /// Set whether env vars which start with the `env_prefix` of the struct (or of a subcommand),
+    /// but are not read by any option, are errors.
+    ///
+    /// This overrides the `deny_unknown_env` attribute of the struct. It has no effect if there is
+    /// no `env_prefix`.
+    pub fn deny_unknown_env(mut self, deny: bool) -> Self {
+        self.deny_unknown_env = Some(deny);
+        self
+    }
+
+    /// Set whether using a deprecated option or env alias is an error.
+    ///
+    /// By default, a warning is printed to stderr instead.
+    pub fn deny_deprecated(mut self, deny: bool) -> Self {
+        self.deny_deprecated = deny;
+        self
+    }
+
+    /// Set a function which is called with each [`Warning`] found while parsing.
+    ///
+    /// Without a handler, warnings are printed to stderr by [`ConfBuilder::parse`],
+    /// [`ConfBuilder::parse_with_report`] and [`ConfBuilder::parse_with_sources`], and the
+    /// `try_parse` functions don't report them at all.
+    ///
+    /// This can be used to send warnings to `log` or `tracing`, for example
+    /// `.on_warning(|warning| log::warn!("{warning}"))`.
+    ///
+    /// The handler is called even if parsing fails, but it is not used by
+    /// [`ConfBuilder::try_parse_with_warnings`], which returns the warnings instead.
+    ///
+    /// The handler must be `Send + Sync`, like a [`Resolver`], so that the builder is too.
+    pub fn on_warning(mut self, handler: impl Fn(&Warning) + Send + Sync + 'static) -> Self {
+        self.warning_handler = Some(Box::new(handler));
+        self
+    }
+
     /// Parse based on supplied sources (or falling back to defaults), and exiting the program
     /// with errors logged to stderr if parsing fails.
     pub fn parse(self) -> S {
-        match self.try_parse() {
-            Ok(result) => result,
+        match self.parse_helper(true) {
+            Ok((result, _report)) => result,
             Err(err) => err.exit(),
         }
     }
@@ -70,26 +156,201 @@ where
     /// Try to parse an instance based on supplied sources (or falling back to defaults),
     /// returning an error if parsing fails.
     pub fn try_parse(self) -> Result<S, Error> {
-        let (parsed_env, args) = self.into_tuple();
+        self.try_parse_with_report().map(|(result, _report)| result)
+    }
 
-        let parser = S::get_parser(&parsed_env)?;
+    /// Parse an instance, together with a report of the effective value of each program option
+    /// and where it came from, exiting the program with errors logged to stderr if parsing fails.
+    pub fn parse_with_report(self) -> (S, ConfigReport) {
+        match self.parse_helper(true) {
+            Ok(result) => result,
+            Err(err) => err.exit(),
+        }
+    }
+
+    /// Parse an instance, together with the source of the value of each program option,
+    /// exiting the program with errors logged to stderr if parsing fails.
+    pub fn parse_with_sources(self) -> (S, Sources) {
+        match self.parse_helper(true) {
+            Ok((result, report)) => (result, Sources::from(&report)),
+            Err(err) => err.exit(),
+        }
+    }
+
+    /// Try to parse an instance, together with the source of the value of each program option,
+    /// returning an error if parsing fails.
+    pub fn try_parse_with_sources(self) -> Result<(S, Sources), Error> {
+        self.try_parse_with_report()
+            .map(|(result, report)| (result, Sources::from(&report)))
+    }
+
+    /// Try to parse an instance, together with a report of the effective value of each program
+    /// option and where it came from, returning an error if parsing fails.
+    pub fn try_parse_with_report(self) -> Result<(S, ConfigReport), Error> {
+        self.parse_helper(false)
+    }
+
+    // Parse, and pass warnings to the handler. If there is no handler, they are printed if
+    // print_warnings is true, and dropped otherwise.
+    fn parse_helper(self, print_warnings: bool) -> Result<(S, ConfigReport), Error> {
+        let (parsed_env, args, settings) = self.into_tuple();
+        let records = ValueRecords::default();
+
+        let result = Self::try_parse_helper(&parsed_env, args, &settings, &records);
+        settings.handle_warnings(records.take_warnings(), print_warnings);
+        result.map_err(|err| err.with_format(settings.error_format))
+    }
+
+    /// Try to parse an instance, together with any [`Warning`]s, returning an error if parsing
+    /// fails.
+    ///
+    /// Warnings are problems which don't cause parsing to fail, such as using a deprecated option.
+    /// Other parse functions pass these to the handler set by [`ConfBuilder::on_warning`], or
+    /// print them to stderr (only the non-`try` ones), but this one only returns them.
+    pub fn try_parse_with_warnings(self) -> Result<(S, Vec<Warning>), Error> {
+        let (parsed_env, args, settings) = self.into_tuple();
+        let records = ValueRecords::default();
+
+        let result = Self::try_parse_helper(&parsed_env, args, &settings, &records);
+        let warnings = records.take_warnings();
+        result
+            .map(|(result, _report)| (result, warnings))
+            .map_err(|err| err.with_format(settings.error_format))
+    }
+
+    fn try_parse_helper(
+        parsed_env: &ParsedEnv,
+        args: Vec<OsString>,
+        settings: &ParseSettings,
+        records: &ValueRecords,
+    ) -> Result<(S, ConfigReport), Error> {
+        let parser = S::get_parser(parsed_env)?;
         let arg_matches = parser.parse(args)?;
         let parsed_args = ParsedArgs::new(&arg_matches, &parser);
-        let conf_context = ConfContext::new(parsed_args, &parsed_env);
-        S::from_conf_context(conf_context)
-            .map_err(|errs| InnerError::vec_to_clap_error(errs, parser.get_command()))
+        let conf_context = ConfContext::new(parsed_args, parsed_env, records, &settings.resolvers);
+        let result = S::from_conf_context(conf_context);
+        let result = settings.check_unknown_env(result, &parser, parsed_env, records);
+        let result = settings
+            .check_deprecated(result, records)
+            .map_err(|errs| InnerError::vec_to_clap_error(errs, parser.get_command()))?;
+        finish_parse(result, &parser, &arg_matches, records)
     }
 
-    /// Convert self into an args, env tuple, after setting defaults from std::env::* and such
-    /// if anything was not inited
-    pub(crate) fn into_tuple(mut self) -> (ParsedEnv, Vec<OsString>) {
+    /// Convert self into an env, args, settings tuple, after setting defaults from std::env::*
+    /// and such if anything was not inited
+    pub(crate) fn into_tuple(mut self) -> (ParsedEnv, Vec<OsString>, ParseSettings) {
         if !self.inited_args {
             self = self.args(std::env::args_os());
         }
         if !self.inited_env {
             self = self.env(std::env::vars_os());
         }
+        if let Some(timeout) = self.env_cmd_timeout {
+            self.collected_env.set_env_cmd_timeout(timeout);
+        }
+
+        let error_format = self
+            .collected_env
+            .get(ErrorFormat::ENV_VAR)
+            .and_then(|val| ErrorFormat::from_env_value(val.to_str()?))
+            .unwrap_or(self.error_format);
+
+        let settings = ParseSettings {
+            resolvers: self.resolvers,
+            error_format,
+            deny_unknown_env: self.deny_unknown_env,
+            deny_deprecated: self.deny_deprecated,
+            warning_handler: self.warning_handler,
+        };
+        (self.collected_env, self.collected_args, settings)
+    }
+}
+
+// Settings from the builder which affect the parse, other than the sources of values
+pub(crate) struct ParseSettings {
+    pub resolvers: Resolvers,
+    pub error_format: ErrorFormat,
+    pub deny_unknown_env: Option<bool>,
+    pub deny_deprecated: bool,
+    pub warning_handler: Option<WarningHandler>,
+}
+
+impl ParseSettings {
+    // Add errors for any unknown env vars, if they are denied, to the result of from_conf_context.
+    // Otherwise, any unknown env vars which look like misspellings are warnings.
+    pub(crate) fn check_unknown_env<S>(
+        &self,
+        result: Result<S, Vec<InnerError>>,
+        parser: &Parser,
+        parsed_env: &ParsedEnv,
+        records: &ValueRecords,
+    ) -> Result<S, Vec<InnerError>> {
+        let unknown_env_errors = parser.unknown_env_errors(parsed_env);
+        if !parser.denies_unknown_env(self.deny_unknown_env) {
+            for err in unknown_env_errors {
+                if matches!(&err, InnerError::UnknownEnv(_, similar) if !similar.is_empty()) {
+                    records.warn(Warning::from_inner_error(WarningKind::UnknownEnv, &err));
+                }
+            }
+            return result;
+        }
+        match result {
+            Ok(result) if unknown_env_errors.is_empty() => Ok(result),
+            Ok(_) => Err(unknown_env_errors),
+            Err(mut errs) => {
+                errs.extend(unknown_env_errors);
+                Err(errs)
+            }
+        }
+    }
+
+    // Report any uses of deprecated options, either as warnings, or as errors added to the result
+    // of from_conf_context if they are denied
+    pub(crate) fn check_deprecated<S>(
+        &self,
+        result: Result<S, Vec<InnerError>>,
+        records: &ValueRecords,
+    ) -> Result<S, Vec<InnerError>> {
+        let deprecated = records.take_deprecated();
+        if !self.deny_deprecated {
+            for err in deprecated {
+                records.warn(Warning::from_inner_error(WarningKind::Deprecated, &err));
+            }
+            return result;
+        }
+        match result {
+            Ok(result) if deprecated.is_empty() => Ok(result),
+            Ok(_) => Err(deprecated),
+            Err(mut errs) => {
+                errs.extend(deprecated);
+                Err(errs)
+            }
+        }
+    }
+
+    // Pass warnings to the warning handler, or print them to stderr if there is none
+    pub(crate) fn handle_warnings(&self, warnings: Vec<Warning>, print_warnings: bool) {
+        for warning in &warnings {
+            match self.warning_handler.as_ref() {
+                Some(handler) => handler(warning),
+                None if print_warnings => warning.print(),
+                None => {}
+            }
+        }
+    }
+}
 
-        (self.collected_env, self.collected_args)
+// Produce the report after a successful parse.
+// If the print config flag was used, the report is instead returned as output to display.
+pub(crate) fn finish_parse<S>(
+    result: S,
+    parser: &Parser,
+    arg_matches: &ArgMatches,
+    records: &ValueRecords,
+) -> Result<(S, ConfigReport), Error> {
+    let report = ConfigReport::new(parser, arg_matches, &records.take_records());
+    if let Some(format) = parser.get_print_config_format(arg_matches) {
+        return Err(Error::output(format.render(&report)));
     }
+    Ok((result, report))
 }
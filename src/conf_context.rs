@@ -1,6 +1,14 @@
-use crate::{str_to_bool, InnerError, ParseType, ParsedArgs, ParsedEnv, ProgramOption};
+use crate::{
+    error::render_help_switch,
+    report::{self, ReportValue, ValueRecord, ValueRecords},
+    resolver::Resolvers,
+    str_to_bool,
+    suggest::similar_env_vars,
+    InnerError, ParseType, ParsedArgs, ParsedEnv, ProgramOption, Warning, WarningKind,
+};
 use clap::parser::ValueSource;
 use core::fmt::Debug;
+use std::{borrow::Cow, collections::HashSet};
 
 // Data about the source of a value returned by ConfContext functions
 // This is mainly used to render help if something fails in the value parser later
@@ -13,15 +21,18 @@ where
 {
     Args,
     Env(S),
+    // The output of a command named by this env var, see `env_cmd`
+    EnvCmd(S),
     Document(S),
     Default,
 }
 
-impl<'a> ConfValueSource<&'a str> {
+impl ConfValueSource<&str> {
     pub fn into_owned(self) -> ConfValueSource<String> {
         match self {
             Self::Args => ConfValueSource::Args,
             Self::Env(s) => ConfValueSource::Env(s.to_owned()),
+            Self::EnvCmd(s) => ConfValueSource::EnvCmd(s.to_owned()),
             Self::Document(s) => ConfValueSource::Document(s.to_owned()),
             Self::Default => ConfValueSource::Default,
         }
@@ -32,7 +43,7 @@ impl<'a> ConfValueSource<&'a str> {
     }
 }
 
-impl<'a> From<ValueSource> for ConfValueSource<&'a str> {
+impl From<ValueSource> for ConfValueSource<&str> {
     fn from(src: ValueSource) -> Self {
         match &src {
             ValueSource::CommandLine => Self::Args,
@@ -62,6 +73,10 @@ pub(crate) struct FlattenedOptionalDebugInfo<'a> {
 // It provides getters which take care of the prefixing, aliases, etc.
 // so that the generated code doesn't have to.
 //
+// The getters also record the value and value source of each option that is read, so that we can
+// report the effective config after the parse. Functions which only check if an option appears
+// don't record anything.
+//
 // Many of the APIs which take an id (or list of ids) will panic if the id is not found.
 // This is okay because this is not a user facing object, and it's okay to panic for internal logic
 // errors like that.
@@ -71,16 +86,72 @@ pub struct ConfContext<'a> {
     env: &'a ParsedEnv,
     id_prefix: String,
     flattened_optional_debug_info: Option<FlattenedOptionalDebugInfo<'a>>,
+    records: &'a ValueRecords,
+    resolvers: &'a Resolvers,
+    command_path: String,
 }
 
 impl<'a> ConfContext<'a> {
-    pub(crate) fn new(args: ParsedArgs<'a>, env: &'a ParsedEnv) -> Self {
+    pub(crate) fn new(
+        args: ParsedArgs<'a>,
+        env: &'a ParsedEnv,
+        records: &'a ValueRecords,
+        resolvers: &'a Resolvers,
+    ) -> Self {
         Self {
             args,
             env,
             id_prefix: String::default(),
             flattened_optional_debug_info: None,
+            records,
+            resolvers,
+            command_path: String::default(),
+        }
+    }
+
+    // The value is only produced if it is going to be reported, i.e. not for secrets
+    fn record(
+        &self,
+        opt: &ProgramOption,
+        source: ConfValueSource<&str>,
+        value: impl FnOnce() -> ReportValue,
+    ) {
+        if opt.secret_warn_cli && source == ConfValueSource::Args {
+            self.records.warn(secret_on_cli_warning(opt));
+        }
+        if let ConfValueSource::Env(name) = &source {
+            for warning in shadowed_env_warnings(opt, name, self.env) {
+                self.records.warn(warning);
+            }
         }
+        if let Some(err) = InnerError::deprecated(&source, opt) {
+            self.records.record_deprecated(err);
+        }
+        self.records
+            .record(ValueRecord::new(&self.command_path, opt, source, value));
+    }
+
+    /// Record that a value from a document was selected for an option, overriding whatever
+    /// value the getters found. If the value has no string form, produce `ReportValue::Opaque`.
+    /// The value is not produced if the option is secret.
+    pub fn record_document_value(
+        &self,
+        id: &str,
+        document_name: &str,
+        value: impl FnOnce() -> ReportValue,
+    ) {
+        let id = self.id_prefix.clone() + id;
+        let opt = self
+            .args
+            .id_to_option()
+            .get(id.as_str())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Option not found by id ({id}), this is an internal_error: {:?}",
+                    self.args.id_to_option()
+                )
+            });
+        self.record(opt, ConfValueSource::Document(document_name), value);
     }
 
     fn get_env(
@@ -101,15 +172,90 @@ impl<'a> ConfContext<'a> {
         Ok(None)
     }
 
+    /// The resolvers which document values are read through
+    #[cfg(feature = "serde")]
+    pub(crate) fn resolvers(&self) -> &'a Resolvers {
+        self.resolvers
+    }
+
+    /// Make the error for a reference in a document which could not be resolved
+    #[cfg(feature = "serde")]
+    pub(crate) fn document_resolve_failed(
+        &self,
+        id: &str,
+        document_name: &str,
+        reference: &str,
+        err: impl std::fmt::Display,
+    ) -> InnerError {
+        let id = self.id_prefix.clone() + id;
+        let opt = self
+            .args
+            .id_to_option()
+            .get(id.as_str())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Option not found by id ({id}), this is an internal_error: {:?}",
+                    self.args.id_to_option()
+                )
+            });
+        InnerError::resolve_failed(
+            ConfValueSource::Document(document_name),
+            reference,
+            opt,
+            err,
+        )
+    }
+
+    /// If a value string is a reference with a scheme that has a registered resolver, resolve it.
+    /// Otherwise return it as is. This is called just before the value parser.
+    pub fn resolve<'b>(
+        &self,
+        value_source: &ConfValueSource<&str>,
+        val_str: &'b str,
+        opt: &ProgramOption,
+    ) -> Result<Cow<'b, str>, InnerError> {
+        match self.resolvers.resolve(val_str) {
+            None => Ok(Cow::Borrowed(val_str)),
+            Some(Ok(resolved)) => Ok(Cow::Owned(resolved)),
+            Some(Err(err)) => Err(InnerError::resolve_failed(
+                value_source.clone(),
+                val_str,
+                opt,
+                err,
+            )),
+        }
+    }
+
+    // If the option has an env_cmd, and that env var is set, run the command and get its output
+    fn get_env_cmd(
+        &self,
+        opt: &'a ProgramOption,
+    ) -> Result<Option<(&'a str, &'a str)>, InnerError> {
+        let Some(env_cmd) = opt.env_cmd.as_deref() else {
+            return Ok(None);
+        };
+        match self.env.get_cmd_output(env_cmd) {
+            None => Ok(None),
+            Some(Ok(val)) => Ok(Some((env_cmd, val))),
+            Some(Err(err)) => Err(InnerError::env_cmd_failed(env_cmd, opt, err)),
+        }
+    }
+
     /// Check if a boolean program option was set to true, using any of its aliases or env value
     pub fn get_boolean_opt(
         &self,
         id: &str,
     ) -> Result<(ConfValueSource<&'a str>, bool), InnerError> {
+        let (src, val, opt) = self.boolean_opt(id)?;
+        self.record(opt, src.clone(), || ReportValue::Bool(val));
+        Ok((src, val))
+    }
+
+    fn boolean_opt(
+        &self,
+        id: &str,
+    ) -> Result<(ConfValueSource<&'a str>, bool, &'a ProgramOption), InnerError> {
         let id = self.id_prefix.clone() + id;
-        if self.args.arg_matches.get_flag(&id) {
-            return Ok((ConfValueSource::<&'a str>::Args, true));
-        }
         let opt = self
             .args
             .id_to_option()
@@ -120,13 +266,29 @@ impl<'a> ConfContext<'a> {
                     self.args.id_to_option()
                 )
             });
+        if self.args.arg_matches.get_flag(&id) {
+            return Ok((ConfValueSource::<&'a str>::Args, true, opt));
+        }
         if let Some(env_form) = opt.env_form.as_deref() {
             if let Some(val) = self.get_env(env_form, opt)? {
-                return Ok((ConfValueSource::<&'a str>::Env(env_form), str_to_bool(val)));
+                return Ok((
+                    ConfValueSource::<&'a str>::Env(env_form),
+                    str_to_bool(val),
+                    opt,
+                ));
+            }
+        }
+        for env_alias in opt.env_aliases.iter() {
+            if let Some(val) = self.get_env(env_alias, opt)? {
+                return Ok((
+                    ConfValueSource::<&'a str>::Env(env_alias),
+                    str_to_bool(val),
+                    opt,
+                ));
             }
         }
 
-        Ok((ConfValueSource::Default, false))
+        Ok((ConfValueSource::Default, false, opt))
     }
 
     /// Get a string program option if it was set, using any of its aliases or env value
@@ -142,6 +304,24 @@ impl<'a> ConfContext<'a> {
             &'a ProgramOption,
         ),
         InnerError,
+    > {
+        let (maybe_val, opt) = self.string_opt(id)?;
+        if let Some((src, val)) = maybe_val.as_ref() {
+            self.record(opt, src.clone(), || ReportValue::String(val.to_string()));
+        }
+        Ok((maybe_val, opt))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn string_opt(
+        &self,
+        id: &str,
+    ) -> Result<
+        (
+            Option<(ConfValueSource<&'a str>, &'a str)>,
+            &'a ProgramOption,
+        ),
+        InnerError,
     > {
         let id = self.id_prefix.clone() + id;
         let opt = self
@@ -187,6 +367,10 @@ impl<'a> ConfContext<'a> {
             }
         }
 
+        if let Some((env_cmd, val)) = self.get_env_cmd(opt)? {
+            return Ok((Some((ConfValueSource::<&str>::EnvCmd(env_cmd), val)), opt));
+        }
+
         if let Some(default_val) = opt.default_value.as_deref() {
             let value_source = ConfValueSource::Default;
             let val_and_source = Some((value_source, default_val));
@@ -204,6 +388,18 @@ impl<'a> ConfContext<'a> {
         &self,
         id: &str,
         env_delimiter: Option<char>,
+    ) -> Result<(ConfValueSource<&'a str>, Vec<&'a str>, &'a ProgramOption), InnerError> {
+        let (src, vals, opt) = self.repeat_opt(id, env_delimiter)?;
+        self.record(opt, src.clone(), || {
+            ReportValue::List(vals.iter().map(|val| val.to_string()).collect())
+        });
+        Ok((src, vals, opt))
+    }
+
+    fn repeat_opt(
+        &self,
+        id: &str,
+        env_delimiter: Option<char>,
     ) -> Result<(ConfValueSource<&'a str>, Vec<&'a str>, &'a ProgramOption), InnerError> {
         let id = self.id_prefix.clone() + id;
         let opt = self
@@ -217,19 +413,21 @@ impl<'a> ConfContext<'a> {
                 )
             });
 
-        if let Some(val) = self.args.arg_matches.get_many::<String>(&id) {
-            let value_source = self
-                .args
-                .arg_matches
-                .value_source(&id)
-                .expect("Id not found, this is an internal error");
-            // Note: We don't support user-defined default value on this one right now, and we don't
-            // give default values to clap so this should be the only possibility
-            assert_eq!(value_source, ValueSource::CommandLine);
+        if opt.short_form.is_some() || opt.long_form.is_some() {
+            if let Some(val) = self.args.arg_matches.get_many::<String>(&id) {
+                let value_source = self
+                    .args
+                    .arg_matches
+                    .value_source(&id)
+                    .expect("Id not found, this is an internal error");
+                // Note: We don't support user-defined default value on this one right now, and we
+                // don't give default values to clap so this should be the only possibility
+                assert_eq!(value_source, ValueSource::CommandLine);
 
-            let results: Vec<&'a str> = val.map(String::as_str).collect();
+                let results: Vec<&'a str> = val.map(String::as_str).collect();
 
-            return Ok((value_source.into(), results, opt));
+                return Ok((value_source.into(), results, opt));
+            }
         }
 
         if let Some(env_form) = opt.env_form.as_deref() {
@@ -256,6 +454,16 @@ impl<'a> ConfContext<'a> {
             }
         }
 
+        if let Some((env_cmd, val)) = self.get_env_cmd(opt)? {
+            let value_source = ConfValueSource::<&str>::EnvCmd(env_cmd);
+
+            return Ok(if let Some(delim) = env_delimiter {
+                (value_source, val.split(delim).collect(), opt)
+            } else {
+                (value_source, vec![val], opt)
+            });
+        }
+
         Ok((ValueSource::DefaultValue.into(), vec![], opt))
     }
 
@@ -289,17 +497,17 @@ impl<'a> ConfContext<'a> {
 
         Ok(match opt.parse_type {
             ParseType::Flag => {
-                let (src, _val) = self.get_boolean_opt(id)?;
+                let (src, _val, _opt) = self.boolean_opt(id)?;
                 Some(src)
             }
             ParseType::Parameter => {
-                let (maybe, _opt) = self.get_string_opt(id)?;
+                let (maybe, _opt) = self.string_opt(id)?;
                 maybe.map(|(src, _val)| src)
             }
             ParseType::Repeat => {
                 // Hack: don't supply delimiter char even if it exists, since it won't matter for
                 // this function
-                let (src, _val, _opt) = self.get_repeat_opt(id, None)?;
+                let (src, _val, _opt) = self.repeat_opt(id, None)?;
                 Some(src)
             }
         })
@@ -316,6 +524,9 @@ impl<'a> ConfContext<'a> {
             env: self.env,
             id_prefix: self.id_prefix.clone() + sub_id_prefix,
             flattened_optional_debug_info: self.flattened_optional_debug_info.clone(),
+            records: self.records,
+            resolvers: self.resolvers,
+            command_path: self.command_path.clone(),
         }
     }
 
@@ -352,6 +563,9 @@ impl<'a> ConfContext<'a> {
             env: self.env,
             id_prefix,
             flattened_optional_debug_info,
+            records: self.records,
+            resolvers: self.resolvers,
+            command_path: self.command_path.clone(),
         }
     }
 
@@ -359,6 +573,7 @@ impl<'a> ConfContext<'a> {
     #[inline]
     pub fn for_subcommand(&self) -> Option<(String, ConfContext<'a>)> {
         self.args.get_subcommand().map(|(name, args)| {
+            let command_path = ValueRecord::subcommand_path(&self.command_path, &name);
             (
                 name,
                 ConfContext {
@@ -366,6 +581,9 @@ impl<'a> ConfContext<'a> {
                     env: self.env,
                     id_prefix: self.id_prefix.clone(),
                     flattened_optional_debug_info: self.flattened_optional_debug_info.clone(),
+                    records: self.records,
+                    resolvers: self.resolvers,
+                    command_path,
                 },
             )
         })
@@ -380,7 +598,16 @@ impl<'a> ConfContext<'a> {
     ///
     /// This error includes context if we are within a flattened optional group
     pub fn missing_required_parameter_error(&self, opt: &ProgramOption) -> InnerError {
-        InnerError::missing_required_parameter(opt, self.flattened_optional_debug_info.clone())
+        let mut env_prefixes = Vec::new();
+        self.args.parser.collect_env_prefixes(&mut env_prefixes);
+        let mut known_env_names = HashSet::new();
+        self.args.parser.collect_env_names(&mut known_env_names);
+        let similar_env_vars = similar_env_vars(opt, self.env, &env_prefixes, &known_env_names);
+        InnerError::missing_required_parameter(
+            opt,
+            self.flattened_optional_debug_info.clone(),
+            similar_env_vars,
+        )
     }
 
     /// Generate a "too_few_arguments" error
@@ -466,3 +693,44 @@ impl<'a> ConfContext<'a> {
         )
     }
 }
+
+// Secrets passed as CLI args may be visible to other users of the system, e.g. in `ps`, and
+// may end up in shell history.
+fn secret_on_cli_warning(opt: &ProgramOption) -> Warning {
+    let switch = render_help_switch(opt);
+    let mut message = format!(
+        "secret value '{}' was passed as a command line argument, where it may be visible to other users of this system",
+        switch.as_deref().unwrap_or(&opt.id)
+    );
+    if let Some(env_form) = opt.env_form.as_deref() {
+        message += &format!("\n  consider using the '{env_form}' env variable instead");
+    }
+    Warning {
+        kind: WarningKind::SecretOnCli,
+        id: Some(opt.id.to_string()),
+        switch,
+        env: opt.env_form.as_deref().map(str::to_owned),
+        source: Some(report::ValueSource::Args),
+        message,
+    }
+}
+
+// If a value was read from one env var of an option, any of its other env vars which are also set
+// are ignored. This is usually a leftover from a rename.
+fn shadowed_env_warnings(opt: &ProgramOption, used: &str, env: &ParsedEnv) -> Vec<Warning> {
+    opt.env_form
+        .iter()
+        .chain(opt.env_aliases.iter())
+        .filter(|name| *name != used && env.get(name).is_some())
+        .map(|name| Warning {
+            kind: WarningKind::ShadowedEnv,
+            id: Some(opt.id.to_string()),
+            switch: render_help_switch(opt),
+            env: Some(name.to_string()),
+            source: Some(report::ValueSource::Env(used.to_owned())),
+            message: format!(
+                "env '{name}' is set, but is ignored because env '{used}' is also set"
+            ),
+        })
+        .collect()
+}
@@ -1,8 +1,11 @@
 use crate::{
-    Conf, ConfBuilder, ConfContext, ConfSerde, ConfSerdeContext, Error, InnerError, ParsedArgs,
+    builder::{finish_parse, ParseSettings},
+    report::{ConfigReport, Sources, ValueRecords},
+    Conf, ConfBuilder, ConfContext, ConfSerde, ConfSerdeContext, Error, ErrorFormat, InnerError,
+    ParsedArgs, ParsedEnv, Resolver, Warning,
 };
 use serde::de::{DeserializeSeed, Deserializer};
-use std::{ffi::OsString, marker::PhantomData};
+use std::{ffi::OsString, marker::PhantomData, time::Duration};
 
 impl<S> ConfBuilder<S>
 where
@@ -65,11 +68,60 @@ where
         self
     }
 
+    /// Install a [`Resolver`] for references with the given scheme.
+    ///
+    /// See [`ConfBuilder::resolver`].
+    pub fn resolver(mut self, scheme: &str, resolver: impl Resolver + 'static) -> Self {
+        self.inner = self.inner.resolver(scheme, resolver);
+        self
+    }
+
+    /// Set the timeout for commands which are run to get the value of an `env_cmd` option.
+    ///
+    /// See [`ConfBuilder::env_cmd_timeout`].
+    pub fn env_cmd_timeout(mut self, timeout: Duration) -> Self {
+        self.inner = self.inner.env_cmd_timeout(timeout);
+        self
+    }
+
+    /// Set the format used when errors are printed.
+    ///
+    /// See [`ConfBuilder::error_format`].
+    pub fn error_format(mut self, format: ErrorFormat) -> Self {
+        self.inner = self.inner.error_format(format);
+        self
+    }
+
+    /// Set whether env vars which start with the env prefix, but are not read by any option, are
+    /// errors.
+    ///
+    /// See [`ConfBuilder::deny_unknown_env`].
+    pub fn deny_unknown_env(mut self, deny: bool) -> Self {
+        self.inner = self.inner.deny_unknown_env(deny);
+        self
+    }
+
+    /// Set whether using a deprecated option or env alias is an error.
+    ///
+    /// See [`ConfBuilder::deny_deprecated`].
+    pub fn deny_deprecated(mut self, deny: bool) -> Self {
+        self.inner = self.inner.deny_deprecated(deny);
+        self
+    }
+
+    /// Set a function which is called with each [`Warning`] found while parsing.
+    ///
+    /// See [`ConfBuilder::on_warning`].
+    pub fn on_warning(mut self, handler: impl Fn(&Warning) + Send + Sync + 'static) -> Self {
+        self.inner = self.inner.on_warning(handler);
+        self
+    }
+
     /// Parse based on supplied sources (or falling back to defaults), and exiting the program
     /// with errors logged to stderr if parsing fails.
     pub fn parse(self) -> S {
-        match self.try_parse() {
-            Ok(result) => result,
+        match self.parse_helper(true) {
+            Ok((result, _report)) => result,
             Err(err) => err.exit(),
         }
     }
@@ -77,18 +129,104 @@ where
     /// Try to parse an instance based on supplied sources (or falling back to defaults),
     /// returning an error if parsing fails.
     pub fn try_parse(self) -> Result<S, Error> {
+        self.try_parse_with_report().map(|(result, _report)| result)
+    }
+
+    /// Parse an instance, together with a report of the effective value of each program option
+    /// and where it came from, exiting the program with errors logged to stderr if parsing fails.
+    pub fn parse_with_report(self) -> (S, ConfigReport) {
+        match self.parse_helper(true) {
+            Ok(result) => result,
+            Err(err) => err.exit(),
+        }
+    }
+
+    /// Parse an instance, together with the source of the value of each program option,
+    /// exiting the program with errors logged to stderr if parsing fails.
+    pub fn parse_with_sources(self) -> (S, Sources) {
+        match self.parse_helper(true) {
+            Ok((result, report)) => (result, Sources::from(&report)),
+            Err(err) => err.exit(),
+        }
+    }
+
+    /// Try to parse an instance, together with the source of the value of each program option,
+    /// returning an error if parsing fails.
+    pub fn try_parse_with_sources(self) -> Result<(S, Sources), Error> {
+        self.try_parse_with_report()
+            .map(|(result, report)| (result, Sources::from(&report)))
+    }
+
+    /// Try to parse an instance, together with a report of the effective value of each program
+    /// option and where it came from, returning an error if parsing fails.
+    pub fn try_parse_with_report(self) -> Result<(S, ConfigReport), Error> {
+        self.parse_helper(false)
+    }
+
+    // Parse, and pass warnings to the handler. If there is no handler, they are printed if
+    // print_warnings is true, and dropped otherwise.
+    fn parse_helper(self, print_warnings: bool) -> Result<(S, ConfigReport), Error> {
+        let Self {
+            inner,
+            document,
+            document_name,
+            _marker,
+        } = self;
+        let (parsed_env, args, settings) = inner.into_tuple();
+        let records = ValueRecords::default();
+
+        let result = Self::try_parse_helper(
+            &parsed_env,
+            args,
+            &settings,
+            &records,
+            document_name,
+            document,
+        );
+        settings.handle_warnings(records.take_warnings(), print_warnings);
+        result.map_err(|err| err.with_format(settings.error_format))
+    }
+
+    /// Try to parse an instance, together with any [`Warning`]s, returning an error if parsing
+    /// fails.
+    ///
+    /// See [`ConfBuilder::try_parse_with_warnings`].
+    pub fn try_parse_with_warnings(self) -> Result<(S, Vec<Warning>), Error> {
         let Self {
             inner,
             document,
             document_name,
             _marker,
         } = self;
-        let (parsed_env, args) = inner.into_tuple();
+        let (parsed_env, args, settings) = inner.into_tuple();
+        let records = ValueRecords::default();
+
+        let result = Self::try_parse_helper(
+            &parsed_env,
+            args,
+            &settings,
+            &records,
+            document_name,
+            document,
+        );
+        let warnings = records.take_warnings();
+        result
+            .map(|(result, _report)| (result, warnings))
+            .map_err(|err| err.with_format(settings.error_format))
+    }
 
-        let parser = <S as Conf>::get_parser(&parsed_env)?;
+    fn try_parse_helper(
+        parsed_env: &ParsedEnv,
+        args: Vec<OsString>,
+        settings: &ParseSettings,
+        records: &ValueRecords,
+        document_name: String,
+        document: D,
+    ) -> Result<(S, ConfigReport), Error> {
+        let parser = <S as Conf>::get_parser(parsed_env)?;
         let arg_matches = parser.parse(args)?;
         let parsed_args = ParsedArgs::new(&arg_matches, &parser);
-        let conf_context = ConfContext::new(parsed_args, &parsed_env);
+        let conf_context = ConfContext::new(parsed_args, parsed_env, records, &settings.resolvers);
         let conf_serde_context = ConfSerdeContext::new(conf_context, document_name.as_str());
         let seed = <S as ConfSerde>::Seed::from(conf_serde_context);
         // Code gen should produce:
@@ -97,8 +235,12 @@ where
         //   ...
         // }
         // So that the result of deserialize call is Result<Result<Self, Vec<InnerError>>, D::Error>
-        DeserializeSeed::<'de>::deserialize(seed, document)
-            .expect("Internal error, Deserializer Error should not be returned here")
-            .map_err(|errs| InnerError::vec_to_clap_error(errs, parser.get_command()))
+        let result = DeserializeSeed::<'de>::deserialize(seed, document)
+            .expect("Internal error, Deserializer Error should not be returned here");
+        let result = settings.check_unknown_env(result, &parser, parsed_env, records);
+        let result = settings
+            .check_deprecated(result, records)
+            .map_err(|errs| InnerError::vec_to_clap_error(errs, parser.get_command()))?;
+        finish_parse(result, &parser, &arg_matches, records)
     }
 }
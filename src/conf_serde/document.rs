@@ -0,0 +1,220 @@
+//! A description of the serde document layout of a `ConfSerde` struct.
+//!
+//! This is generated by the derive macro, and then walked by code in this crate to produce things
+//! like sample config documents, without having to generate more code for each of those things.
+
+use crate::{ParseType, ProgramOption};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// Describes the keys that a `ConfSerde` struct reads from a serde document.
+#[doc(hidden)]
+pub struct DocumentStruct {
+    /// The struct name
+    pub name: &'static str,
+    /// The struct description (typically its doc string)
+    pub description: Option<&'static str>,
+    /// True if unknown keys are not an error for this struct
+    pub allow_unknown_fields: bool,
+    /// The program options of this struct (relative to this struct)
+    pub program_options: &'static [ProgramOption],
+    /// The document fields of this struct, in declaration order, excluding serde(skip) fields
+    pub fields: Vec<DocumentField>,
+}
+
+/// Describes one key (or several, in the case of subcommands) that a struct reads from a document.
+#[doc(hidden)]
+pub enum DocumentField {
+    /// A flag, parameter or repeat field
+    Option {
+        /// The key in the document
+        serde_name: &'static str,
+        /// The id of the corresponding program option (relative to the struct)
+        id: &'static str,
+        /// The type of value we expect to find in the document
+        value_type: DocumentValueType,
+    },
+    /// A flattened struct, which appears as a nested table
+    Flatten {
+        /// The key in the document
+        serde_name: &'static str,
+        /// True if this is flatten-optional
+        is_optional: bool,
+        /// The layout of the nested table
+        inner: DocumentStruct,
+    },
+    /// A subcommands field, each subcommand has its own section
+    Subcommands {
+        /// True if the subcommand is optional
+        is_optional: bool,
+        /// List of (serde_name, layout) for each subcommand that is not serde(skip)
+        sections: Vec<(&'static str, DocumentStruct)>,
+    },
+}
+
+/// A coarse description of the type of a value in a document.
+///
+/// The derive macro can only see tokens, so this is based on recognizing primitive types.
+/// Anything it doesn't recognize is `Any`.
+#[doc(hidden)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DocumentValueType {
+    /// A boolean
+    Bool,
+    /// An integer
+    Integer,
+    /// A floating point number
+    Float,
+    /// A string
+    String,
+    /// An array of values
+    Array(Box<DocumentValueType>),
+    /// We don't know what type this is
+    Any,
+}
+
+impl DocumentStruct {
+    /// Find a program option in this struct by its id
+    pub(crate) fn get_program_option(&self, id: &str) -> Option<&'static ProgramOption> {
+        self.program_options.iter().find(|opt| opt.id == id)
+    }
+
+    /// Produce a sample document from this description
+    pub(crate) fn sample(&self) -> SampleValue {
+        let mut entries = Vec::<(String, SampleValue)>::new();
+
+        for field in self.fields.iter() {
+            match field {
+                DocumentField::Option {
+                    serde_name,
+                    id,
+                    value_type,
+                } => {
+                    let opt = self.get_program_option(id);
+                    let value = value_type.sample(opt);
+                    entries.push((serde_name.to_string(), value));
+                }
+                DocumentField::Flatten {
+                    serde_name, inner, ..
+                } => {
+                    entries.push((serde_name.to_string(), inner.sample()));
+                }
+                DocumentField::Subcommands { sections, .. } => {
+                    // Several subcommands are allowed to share a section, only emit it once.
+                    for (serde_name, inner) in sections.iter() {
+                        if entries.iter().any(|(key, _)| key == serde_name) {
+                            continue;
+                        }
+                        entries.push((serde_name.to_string(), inner.sample()));
+                    }
+                }
+            }
+        }
+
+        SampleValue::Table(entries)
+    }
+}
+
+impl DocumentValueType {
+    /// Produce a sample value of this type, using the default value of the program option if any.
+    fn sample(&self, opt: Option<&ProgramOption>) -> SampleValue {
+        let default_value = opt.and_then(|opt| opt.default_value.as_deref());
+        let is_flag = opt
+            .map(|opt| opt.parse_type == ParseType::Flag)
+            .unwrap_or(false);
+
+        match self {
+            Self::Array(_) => SampleValue::Array(vec![]),
+            Self::Bool if is_flag => SampleValue::Bool(false),
+            _ => match default_value {
+                Some(default_value) => self.sample_from_str(default_value),
+                None => SampleValue::Null,
+            },
+        }
+    }
+
+    /// Convert a string (e.g. a default value) to a sample value of this type, falling back to
+    /// a string if it doesn't parse as expected.
+    pub(crate) fn sample_from_str(&self, src: &str) -> SampleValue {
+        match self {
+            Self::Bool => src
+                .parse::<bool>()
+                .map(SampleValue::Bool)
+                .unwrap_or_else(|_| SampleValue::String(src.to_owned())),
+            Self::Integer => src
+                .parse::<i64>()
+                .map(SampleValue::Integer)
+                .unwrap_or_else(|_| SampleValue::String(src.to_owned())),
+            Self::Float => src
+                .parse::<f64>()
+                .map(SampleValue::Float)
+                .unwrap_or_else(|_| SampleValue::String(src.to_owned())),
+            Self::Array(inner) => SampleValue::Array(vec![inner.sample_from_str(src)]),
+            Self::String | Self::Any => SampleValue::String(src.to_owned()),
+        }
+    }
+}
+
+/// A value in a sample config document, see [`ConfSerde::sample_document`](crate::ConfSerde::sample_document).
+///
+/// This implements `serde::Serialize`, so it can be written out using any serde format,
+/// e.g. `serde_json::to_string_pretty` or `toml::to_string`.
+///
+/// Keys of a table appear in the order that the corresponding fields were declared.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SampleValue {
+    /// No value (no default value was specified).
+    ///
+    /// Formats that don't have null, such as TOML, omit these keys when serializing a table.
+    Null,
+    /// A boolean
+    Bool(bool),
+    /// An integer
+    Integer(i64),
+    /// A floating point number
+    Float(f64),
+    /// A string
+    String(String),
+    /// An array
+    Array(Vec<SampleValue>),
+    /// A table, with keys in declaration order
+    Table(Vec<(String, SampleValue)>),
+}
+
+impl SampleValue {
+    /// Get the value associated to a key, if this is a table
+    pub fn get(&self, key: &str) -> Option<&SampleValue> {
+        match self {
+            Self::Table(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for SampleValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Null => serializer.serialize_none(),
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::Integer(i) => serializer.serialize_i64(*i),
+            Self::Float(f) => serializer.serialize_f64(*f),
+            Self::String(s) => serializer.serialize_str(s),
+            Self::Array(vals) => {
+                let mut seq = serializer.serialize_seq(Some(vals.len()))?;
+                for val in vals {
+                    seq.serialize_element(val)?;
+                }
+                seq.end()
+            }
+            Self::Table(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, val) in entries {
+                    map.serialize_entry(key, val)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
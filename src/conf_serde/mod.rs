@@ -63,6 +63,14 @@
 mod builder;
 pub use builder::ConfSerdeBuilder;
 
+mod document;
+pub use document::{DocumentField, DocumentStruct, DocumentValueType, SampleValue};
+
+#[cfg(feature = "schema")]
+mod schema;
+
+mod resolving;
+
 mod traits;
 pub use traits::{ConfSerde, ConfSerdeContext, NextValueProducer, SubcommandsSerde};
 
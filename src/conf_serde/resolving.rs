@@ -0,0 +1,285 @@
+//! Resolution of references in document values.
+//!
+//! When a field is read from a document, the value is deserialized through a wrapper around the
+//! deserializer, which passes every string it sees to the installed resolvers. This works for
+//! strings at any depth, e.g. in an `Option<T>`, a `Vec<T>`, or a newtype like `Secret<T>`.
+
+use super::NextValueProducer;
+use crate::resolver::{ResolveError, Resolvers};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::{cell::RefCell, fmt, marker::PhantomData};
+
+// The resolvers, and a place to put the reference and error if resolution fails. Failures are
+// reported by the caller as `ResolveFailed` errors, rather than as generic serde errors.
+#[derive(Copy, Clone)]
+struct Resolving<'a> {
+    resolvers: &'a Resolvers,
+    failure: &'a RefCell<Option<(String, ResolveError)>>,
+}
+
+impl<'a> Resolving<'a> {
+    fn resolve<E: de::Error>(&self, val: &str) -> Result<Option<String>, E> {
+        match self.resolvers.resolve(val) {
+            None => Ok(None),
+            Some(Ok(resolved)) => Ok(Some(resolved)),
+            Some(Err(err)) => {
+                *self.failure.borrow_mut() = Some((val.to_owned(), err));
+                Err(E::custom("could not resolve reference"))
+            }
+        }
+    }
+}
+
+// Deserialize the next value from a map, resolving any references in it.
+// On failure, returns the reference and the resolver error if resolution failed, and the serde
+// error otherwise.
+pub(crate) fn next_value_resolved<'de, T, NVP>(
+    next_value_producer: NVP,
+    resolvers: &Resolvers,
+) -> Result<T, Result<(String, ResolveError), NVP::Error>>
+where
+    T: de::Deserialize<'de>,
+    NVP: NextValueProducer<'de>,
+{
+    let failure = RefCell::new(None);
+    let resolving = Resolving {
+        resolvers,
+        failure: &failure,
+    };
+    next_value_producer
+        .next_value_seed(ResolvingSeed {
+            seed: PhantomData::<T>,
+            resolving,
+        })
+        .map_err(|err| failure.into_inner().ok_or(err))
+}
+
+// A seed which deserializes the value of another seed through a ResolvingDeserializer
+struct ResolvingSeed<'a, S> {
+    seed: S,
+    resolving: Resolving<'a>,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for ResolvingSeed<'_, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.seed.deserialize(ResolvingDeserializer {
+            inner: deserializer,
+            resolving: self.resolving,
+        })
+    }
+}
+
+struct ResolvingDeserializer<'a, D> {
+    inner: D,
+    resolving: Resolving<'a>,
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident ( $($arg:ident : $ty:ty),* );)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+                self.inner.$method($($arg,)* ResolvingVisitor {
+                    inner: visitor,
+                    resolving: self.resolving,
+                })
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for ResolvingDeserializer<'_, D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+struct ResolvingVisitor<'a, V> {
+    inner: V,
+    resolving: Resolving<'a>,
+}
+
+macro_rules! forward_visit {
+    ($($method:ident ( $ty:ty );)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for ResolvingVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+        match self.resolving.resolve(v)? {
+            Some(resolved) => self.inner.visit_string(resolved),
+            None => self.inner.visit_str(v),
+        }
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+        match self.resolving.resolve(v)? {
+            Some(resolved) => self.inner.visit_string(resolved),
+            None => self.inner.visit_borrowed_str(v),
+        }
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        match self.resolving.resolve(&v)? {
+            Some(resolved) => self.inner.visit_string(resolved),
+            None => self.inner.visit_string(v),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(ResolvingDeserializer {
+            inner: deserializer,
+            resolving: self.resolving,
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.visit_newtype_struct(ResolvingDeserializer {
+            inner: deserializer,
+            resolving: self.resolving,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(ResolvingAccess {
+            inner: seq,
+            resolving: self.resolving,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(ResolvingAccess {
+            inner: map,
+            resolving: self.resolving,
+        })
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(data)
+    }
+}
+
+// Wraps the elements of a sequence, or the values (but not the keys) of a map
+struct ResolvingAccess<'a, A> {
+    inner: A,
+    resolving: Resolving<'a>,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for ResolvingAccess<'_, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.inner.next_element_seed(ResolvingSeed {
+            seed,
+            resolving: self.resolving,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for ResolvingAccess<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.next_value_seed(ResolvingSeed {
+            seed,
+            resolving: self.resolving,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
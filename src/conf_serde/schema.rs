@@ -0,0 +1,122 @@
+//! JSON Schema generation for the serde document layer.
+//!
+//! This walks the same `DocumentStruct` description that is used for sample documents.
+
+use super::{DocumentField, DocumentStruct, DocumentValueType};
+use crate::{ParseType, ProgramOption};
+use serde_json::{json, Map, Value};
+
+/// The JSON Schema dialect that we produce
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl DocumentStruct {
+    /// Produce a JSON Schema for a document that this struct can read, suitable as a
+    /// top-level schema.
+    pub(crate) fn json_schema(&self) -> Value {
+        let mut schema = self.object_schema();
+        if let Value::Object(map) = &mut schema {
+            map.insert("$schema".into(), JSON_SCHEMA_DIALECT.into());
+        }
+        schema
+    }
+
+    // Produce the schema of the object corresponding to this struct
+    fn object_schema(&self) -> Value {
+        let mut properties = Map::new();
+
+        for field in self.fields.iter() {
+            match field {
+                DocumentField::Option {
+                    serde_name,
+                    id,
+                    value_type,
+                } => {
+                    let schema = value_type.option_schema(self.get_program_option(id));
+                    properties.insert(serde_name.to_string(), schema);
+                }
+                DocumentField::Flatten {
+                    serde_name, inner, ..
+                } => {
+                    properties.insert(serde_name.to_string(), inner.object_schema());
+                }
+                DocumentField::Subcommands { sections, .. } => {
+                    // Several subcommands are allowed to share a section. In that case, the
+                    // section has to conform to (at least) one of the subcommand schemas.
+                    // These are not necessarily mutually exclusive, so this is anyOf, not oneOf.
+                    let mut section_names: Vec<&str> = vec![];
+                    for (serde_name, _) in sections.iter() {
+                        if !section_names.contains(serde_name) {
+                            section_names.push(serde_name);
+                        }
+                    }
+                    for section_name in section_names {
+                        let mut schemas: Vec<Value> = sections
+                            .iter()
+                            .filter(|(serde_name, _)| *serde_name == section_name)
+                            .map(|(_, inner)| inner.object_schema())
+                            .collect();
+                        let schema = if schemas.len() == 1 {
+                            schemas.pop().unwrap()
+                        } else {
+                            json!({ "anyOf": schemas })
+                        };
+                        properties.insert(section_name.to_owned(), schema);
+                    }
+                }
+            }
+        }
+
+        let mut schema = Map::new();
+        schema.insert("title".into(), self.name.into());
+        if let Some(description) = self.description {
+            schema.insert("description".into(), description.into());
+        }
+        schema.insert("type".into(), "object".into());
+        schema.insert("properties".into(), Value::Object(properties));
+        if !self.allow_unknown_fields {
+            schema.insert("additionalProperties".into(), false.into());
+        }
+        Value::Object(schema)
+    }
+}
+
+impl DocumentValueType {
+    // Produce the schema for a single option of this type
+    fn option_schema(&self, opt: Option<&ProgramOption>) -> Value {
+        let mut schema = match self.type_schema() {
+            Value::Object(map) => map,
+            _ => unreachable!("type_schema always returns an object"),
+        };
+
+        if let Some(opt) = opt {
+            if let Some(description) = opt.description.as_deref() {
+                schema.insert("description".into(), description.into());
+            }
+            if let Some(default_value) = opt.default_value.as_deref() {
+                let default_value = serde_json::to_value(self.sample_from_str(default_value))
+                    .expect("sample values are always serializable");
+                schema.insert("default".into(), default_value);
+            } else if opt.parse_type == ParseType::Flag {
+                schema.insert("default".into(), false.into());
+            }
+            if opt.deprecated.is_some() {
+                schema.insert("deprecated".into(), true.into());
+            }
+        }
+
+        Value::Object(schema)
+    }
+
+    // Produce a schema which constrains only the type
+    fn type_schema(&self) -> Value {
+        match self {
+            Self::Bool => json!({ "type": "boolean" }),
+            Self::Integer => json!({ "type": "integer" }),
+            Self::Float => json!({ "type": "number" }),
+            Self::String => json!({ "type": "string" }),
+            Self::Array(inner) => json!({ "type": "array", "items": inner.type_schema() }),
+            // We don't know anything about the type, so anything goes.
+            Self::Any => json!({}),
+        }
+    }
+}
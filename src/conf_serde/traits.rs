@@ -1,4 +1,5 @@
-use crate::{Conf, ConfContext, InnerError, Subcommands};
+use super::{resolving::next_value_resolved, DocumentStruct, SampleValue};
+use crate::{Conf, ConfContext, Error, InnerError, Subcommands};
 use serde::de::{Deserialize, DeserializeSeed};
 
 /// Extension to Conf trait with serde-integration implementation details.
@@ -28,6 +29,48 @@ pub trait ConfSerde: Conf + Sized {
     #[doc(hidden)]
     type Seed<'a>: From<ConfSerdeContext<'a>>
         + for<'de> DeserializeSeed<'de, Value = Result<Self, Vec<InnerError>>>;
+
+    // Describes the layout of the serde document that this struct reads.
+    // This is walked to produce sample documents.
+    #[doc(hidden)]
+    fn get_document_struct() -> Result<DocumentStruct, Error>;
+
+    /// Produce a sample config document for this structure.
+    ///
+    /// The result is a tree of values which can be serialized using any serde format,
+    /// for instance to implement a `--print-sample-config` option.
+    ///
+    /// * Keys are the serialization names, respecting `#[conf(serde(rename))]`, and
+    ///   fields marked `#[conf(serde(skip))]` are omitted.
+    /// * Values are filled in from `default_value` where one was specified. Flags are `false`,
+    ///   repeat parameters are empty arrays, and parameters without a default value are null.
+    /// * Flattened structures appear as nested tables.
+    /// * Each subcommand that is not `serde(skip)` gets its own section.
+    fn sample_document() -> Result<SampleValue, Error> {
+        Ok(Self::get_document_struct()?.sample())
+    }
+
+    /// Produce a JSON Schema describing the config documents that this structure can read.
+    ///
+    /// This can be used by editors to validate and auto-complete config files, including YAML
+    /// and TOML files.
+    ///
+    /// * Each structure is an object, whose keys are the serialization names. Unknown keys are
+    ///   not allowed (`additionalProperties: false`) unless `#[conf(serde(allow_unknown_fields))]`
+    ///   was used.
+    /// * Doc comments become descriptions, and `default_value`'s become defaults.
+    /// * Parameters which use `serde(use_value_parser)` are strings, and repeat parameters are
+    ///   arrays. Primitive types like `bool`, integers and floats are recognized, other types are
+    ///   not constrained.
+    /// * Each subcommand section is an object. If several subcommands share a section, the
+    ///   section must match one of them (`anyOf`). Several sections may appear in a document,
+    ///   since the sections of subcommands which are not selected are ignored.
+    ///
+    /// Requires the `"schema"` feature.
+    #[cfg(feature = "schema")]
+    fn json_schema() -> Result<serde_json::Value, Error> {
+        Ok(Self::get_document_struct()?.json_schema())
+    }
 }
 
 /// Extension to Subcommands trait with serde-integration implementation details.
@@ -50,6 +93,10 @@ pub trait SubcommandsSerde: Subcommands + Sized {
     #[doc(hidden)]
     const SERDE_NAMES: &'static [(&'static str, &'static str)];
 
+    // List of (serde_name, document layout) pairs, for those subcommands that are not serde(skip)
+    #[doc(hidden)]
+    fn get_document_sections() -> Result<Vec<(&'static str, DocumentStruct)>, Error>;
+
     // Similar to Subcommands::from_conf_context but now with serde data as well.
     //
     // Arguments:
@@ -129,6 +176,9 @@ where
 pub struct ConfSerdeContext<'a> {
     pub conf_context: ConfContext<'a>,
     pub document_name: &'a str,
+    /// The dotted path of keys from the root of the document to the map we are visiting, or empty
+    /// at the root. This is used in error messages.
+    pub document_path: String,
     pub depth: usize,
 }
 
@@ -138,21 +188,25 @@ impl<'a> ConfSerdeContext<'a> {
         Self {
             conf_context,
             document_name,
+            document_path: String::default(),
             depth: 0,
         }
     }
 
-    /// Same as [`ConfContext::for_flattened`] but now for a `ConfSerdeContext`
-    pub fn for_flattened(&self, id_prefix: &str) -> Self {
+    /// Same as [`ConfContext::for_flattened`] but now for a `ConfSerdeContext`.
+    /// The document key is the key of the map that the flattened struct is read from.
+    pub fn for_flattened(&self, id_prefix: &str, document_key: &str) -> Self {
         Self {
             conf_context: self.conf_context.for_flattened(id_prefix),
             document_name: self.document_name,
+            document_path: self.document_path_of(document_key),
             depth: self.depth + 1,
         }
     }
 
     /// Same as [`ConfContext::for_subcommand`], but now for a `ConfSerdeContext`
-    pub fn for_subcommand(&self) -> Option<(String, Self)> {
+    /// The document key is the key of the map that the subcommand is read from.
+    pub fn for_subcommand(&self, document_key: &str) -> Option<(String, Self)> {
         self.conf_context
             .for_subcommand()
             .map(|(subcommand_name, conf_context)| {
@@ -161,9 +215,35 @@ impl<'a> ConfSerdeContext<'a> {
                     Self {
                         conf_context,
                         document_name: self.document_name,
+                        document_path: self.document_path_of(document_key),
                         depth: self.depth,
                     },
                 )
             })
     }
+
+    /// Read the value of a flag, parameter or repeat field from the document.
+    /// Any string in it which is a reference with a registered scheme is resolved.
+    pub fn next_document_value<'de, T, NVP>(&self, nvp: NVP, id: &str) -> Result<T, InnerError>
+    where
+        T: Deserialize<'de>,
+        NVP: NextValueProducer<'de>,
+    {
+        next_value_resolved(nvp, self.conf_context.resolvers()).map_err(|err| match err {
+            Ok((reference, err)) => {
+                self.conf_context
+                    .document_resolve_failed(id, self.document_name, &reference, err)
+            }
+            Err(err) => InnerError::serde(self.document_name, id, err),
+        })
+    }
+
+    /// The dotted path of a key of the map we are visiting
+    pub fn document_path_of(&self, key: &str) -> String {
+        if self.document_path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{key}", self.document_path)
+        }
+    }
 }
@@ -0,0 +1,113 @@
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The default timeout for commands run due to `env_cmd`.
+pub(crate) const DEFAULT_ENV_CMD_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Run a command named in an env var, as required by `env_cmd`, and return its stdout, without the
+// trailing newline.
+//
+// The command is split into argv on whitespace, and single or double quotes may be used to group
+// words containing whitespace. No shell is involved.
+//
+// The error string describes the problem, and is shown to the user. It must never contain the
+// output of the command, since that is likely a secret.
+pub(crate) fn run_env_cmd(cmd: &str, timeout: Duration) -> Result<String, String> {
+    let argv = split_command(cmd)?;
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| "the command is empty".to_owned())?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|err| format!("could not run '{program}': {err}"))?;
+
+    // Read stdout on another thread, so that the child can't block on a full pipe while we wait.
+    // The result is sent back on a channel, so that waiting for it can also time out. Stdout may
+    // stay open after the child exits, if it started a process of its own which inherited it.
+    let mut stdout = child.stdout.take().expect("stdout was piped");
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = sender.send(stdout.read_to_end(&mut buf).map(|_| buf));
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {
+                if Instant::now() >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "'{program}' timed out after {:?}, and was killed",
+                        timeout
+                    ));
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(err) => return Err(format!("could not wait for '{program}': {err}")),
+        }
+    };
+
+    if !status.success() {
+        return Err(format!("'{program}' failed ({status})"));
+    }
+
+    let buf = match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(result) => {
+            result.map_err(|err| format!("could not read output of '{program}': {err}"))?
+        }
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            let _ = child.kill();
+            return Err(format!(
+                "'{program}' did not close its output within {:?}",
+                timeout
+            ));
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            return Err(format!("could not read output of '{program}'"));
+        }
+    };
+    let mut output =
+        String::from_utf8(buf).map_err(|_| format!("output of '{program}' was not valid UTF-8"))?;
+
+    let trimmed_len = output.trim_end_matches(['\n', '\r']).len();
+    output.truncate(trimmed_len);
+    Ok(output)
+}
+
+// Split a command into argv, on whitespace, respecting single and double quotes.
+fn split_command(cmd: &str) -> Result<Vec<String>, String> {
+    let mut argv = Vec::new();
+    let mut cur: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for ch in cmd.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => cur.get_or_insert_with(String::new).push(ch),
+            None if ch == '\'' || ch == '"' => {
+                quote = Some(ch);
+                cur.get_or_insert_with(String::new);
+            }
+            None if ch.is_whitespace() => argv.extend(cur.take()),
+            None => cur.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    if quote.is_some() {
+        return Err("the command has an unterminated quote".to_owned());
+    }
+    argv.extend(cur);
+    Ok(argv)
+}
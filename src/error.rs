@@ -1,38 +1,177 @@
-use crate::{ConfValueSource, FlattenedOptionalDebugInfo, ProgramOption};
-use clap::{builder::Styles, error::ErrorKind, Command, Error as ClapError};
-use std::{ffi::OsString, fmt, fmt::Write};
+use crate::{
+    json,
+    report::{ReportValue, ValueSource},
+    suggest::similar_names,
+    ConfValueSource, FlattenedOptionalDebugInfo, ProgramOption,
+};
+use clap::{builder::Styles, error::ErrorKind as ClapErrorKind, Command, Error as ClapError};
+use std::{ffi::OsString, fmt, fmt::Write, io::Write as _};
 
 /// An error which occurs when a `Conf::parse` function is called.
 /// This may conceptually represent many underlying errors of several different types.
+/// These can be inspected using [`Error::items`].
+///
+/// Like a `clap::Error`, this may also represent a request to display some output and exit
+/// successfully, for instance when `--help` is passed. In that case
+/// [`Error::exit_code`] is 0, and [`Error::exit`] prints to stdout.
 //
-// Note: For now this is a thin wrapper around clap::Error just so that we can control our public
-// API independently of clap. We may eventually need to make it contain an enum which is either a
-// clap::Error or a collection of InnerError or something like this, but this approach is working
-// adequately for now.
+// Note: For printing, this is mostly a thin wrapper around clap::Error just so that we can control
+// our public API independently of clap.
 #[derive(Debug)]
-pub struct Error(ClapError);
+pub struct Error {
+    repr: ErrorRepr,
+    items: Vec<ErrorItem>,
+    format: ErrorFormat,
+}
+
+/// The format used when an [`Error`] is printed by [`Error::print`] or [`Error::exit`].
+///
+/// This can be set using [`ConfBuilder::error_format`](crate::ConfBuilder::error_format), or
+/// by setting the env var `CONF_ERROR_FORMAT` to `text` or `json`, which takes precedence.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorFormat {
+    /// Formatted and colored text, as clap prints it
+    #[default]
+    Text,
+    /// One JSON object per line for each error item, as produced by [`Error::to_json`].
+    ///
+    /// Help and other requested output is still printed as text.
+    Json,
+}
+
+impl ErrorFormat {
+    // The name of the env var which can be used to override the error format
+    pub(crate) const ENV_VAR: &'static str = "CONF_ERROR_FORMAT";
+
+    // Parse the value of the env var, ignoring unknown values
+    pub(crate) fn from_env_value(val: &str) -> Option<Self> {
+        match val.trim().to_ascii_lowercase().as_str() {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorRepr {
+    // An error (or help output) from clap
+    Clap(ClapError),
+    // Output that was requested by the user and should be printed to stdout,
+    // e.g. the effective config when the print config flag is used.
+    Output(String),
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        match &self.repr {
+            ErrorRepr::Clap(err) => err.fmt(f),
+            ErrorRepr::Output(text) => f.write_str(text),
+        }
     }
 }
 
 impl Error {
     /// Print formatted and colored error text to stderr or stdout as appropriate (as clap does)
+    ///
+    /// If the [`ErrorFormat`] is `Json`, errors are instead printed to stderr as JSON.
     pub fn print(&self) -> Result<(), std::io::Error> {
-        self.0.print()
+        if self.prints_json() {
+            return std::io::stderr().write_all(self.to_json().as_bytes());
+        }
+        match &self.repr {
+            ErrorRepr::Clap(err) => err.print(),
+            ErrorRepr::Output(text) => std::io::stdout().write_all(text.as_bytes()),
+        }
     }
 
     /// Exit the program, printing an error message to stderr or stdout as appropriate (as clap
     /// does)
     pub fn exit(&self) -> ! {
-        self.0.exit()
+        match &self.repr {
+            ErrorRepr::Clap(err) if !self.prints_json() => err.exit(),
+            _ => {
+                // Swallow broken pipe errors, as clap does
+                let _ = self.print();
+                std::process::exit(self.exit_code())
+            }
+        }
+    }
+
+    /// The individual problems that caused parsing to fail.
+    ///
+    /// If this error is a request to display output (see [`Error::exit_code`]), there are none.
+    pub fn items(&self) -> impl Iterator<Item = &ErrorItem> {
+        self.items.iter()
+    }
+
+    /// Render the error items as JSON, one object per line.
+    ///
+    /// Each object has keys `"kind"` (e.g. `"invalid_value"`), `"id"`, `"switch"`, `"env"`,
+    /// `"source"`, `"value"`, `"constraint_ids"` and `"message"`, corresponding to the fields of
+    /// [`ErrorItem`]. Fields which are not set are `null`. A source is an object such as
+    /// `{"type": "env", "name": "DB_URL"}`, and the value of a secret is `"[REDACTED]"`.
+    ///
+    /// If there are no error items, e.g. because this is a request to display help, this is empty.
+    pub fn to_json(&self) -> String {
+        let mut buf = String::new();
+        for item in &self.items {
+            item.write_json(&mut buf);
+            buf.push('\n');
+        }
+        buf
     }
 
     /// The exit code this error will exit the program with
     pub fn exit_code(&self) -> i32 {
-        self.0.exit_code()
+        match &self.repr {
+            ErrorRepr::Clap(err) => err.exit_code(),
+            ErrorRepr::Output(_) => 0,
+        }
+    }
+
+    /// Make an error for a document which could not be loaded, e.g. because the file could not be
+    /// read, or is not valid TOML.
+    ///
+    /// This is useful with [`ConfReloader`](crate::reload::ConfReloader), which expects loading
+    /// failures to be reported as a [`conf::Error`](Error) of kind [`ErrorKind::Document`].
+    pub fn document_load(document_name: &str, err: impl fmt::Display) -> Self {
+        let message = format!("Loading {document_name}: {err}");
+        Self {
+            repr: ErrorRepr::Clap(ClapError::raw(ClapErrorKind::Io, format!("{message}\n"))),
+            items: vec![ErrorItem {
+                kind: ErrorKind::Document,
+                id: None,
+                switch: None,
+                env: None,
+                source: Some(ValueSource::Document(document_name.to_owned())),
+                value: None,
+                constraint_ids: Vec::new(),
+                message,
+            }],
+            format: ErrorFormat::Text,
+        }
+    }
+
+    // Output which should be displayed, after which the program should exit successfully
+    pub(crate) fn output(text: String) -> Self {
+        Self {
+            repr: ErrorRepr::Output(text),
+            items: Vec::new(),
+            format: ErrorFormat::Text,
+        }
+    }
+
+    // Set the format used by print and exit
+    pub(crate) fn with_format(mut self, format: ErrorFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // Whether print and exit should print JSON. Help and other output is never JSON.
+    fn prints_json(&self) -> bool {
+        self.format == ErrorFormat::Json && !self.items.is_empty()
     }
 
     // An error reported during program options generation
@@ -43,13 +182,162 @@ impl Error {
         field_type_name: &'static str,
     ) -> Self {
         let buf = format!("Internal error (invalid skip short)\n  When flattening {field_type_name} at {field_name}, these short options were not found: {not_found_chars:?}\n  To fix this error, remove them from the skip_short attribute list.");
-        ClapError::raw(ErrorKind::UnknownArgument, buf).into()
+        ClapError::raw(ClapErrorKind::UnknownArgument, buf).into()
     }
 }
 
 impl From<ClapError> for Error {
     fn from(src: ClapError) -> Error {
-        Error(src)
+        // Errors from clap itself are problems with the CLI args (or internal errors). If clap is
+        // displaying help or version, there is no problem.
+        let items = if src.use_stderr() {
+            let rendered = src.render().to_string();
+            let message = rendered
+                .strip_prefix("error: ")
+                .unwrap_or(&rendered)
+                .trim_end()
+                .to_owned();
+            vec![ErrorItem {
+                kind: ErrorKind::Args,
+                id: None,
+                switch: None,
+                env: None,
+                source: None,
+                value: None,
+                constraint_ids: Vec::new(),
+                message,
+            }]
+        } else {
+            Vec::new()
+        };
+        Error {
+            repr: ErrorRepr::Clap(src),
+            items,
+            format: ErrorFormat::Text,
+        }
+    }
+}
+
+/// The kind of an [`ErrorItem`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The CLI args could not be parsed, e.g. an unknown argument was passed
+    Args,
+    /// A required option was not provided
+    MissingRequired,
+    /// A value could not be parsed by the value parser
+    InvalidValue,
+    /// Too few of the options in a constraint like `one_of_fields` were provided
+    TooFewArguments,
+    /// Too many of the options in a constraint like `one_of_fields` were provided
+    TooManyArguments,
+    /// A validation predicate failed
+    ValidationFailed,
+    /// An env var contained invalid UTF-8
+    InvalidUtf8Env,
+    /// A required subcommand was not selected
+    MissingSubcommand,
+    /// A document could not be deserialized
+    Document,
+    /// A command named by an `env_cmd` env var failed
+    EnvCmdFailed,
+    /// A reference could not be resolved by a [`Resolver`](crate::Resolver)
+    ResolveFailed,
+    /// An env var has the env prefix but is not read by any option, and `deny_unknown_env` is used
+    UnknownEnv,
+    /// A document has a key which does not correspond to any field
+    UnknownDocumentKey,
+    /// A deprecated option or env alias was used, and
+    /// [`ConfBuilder::deny_deprecated`](crate::ConfBuilder::deny_deprecated) is set
+    Deprecated,
+}
+
+impl ErrorKind {
+    // The name of this kind in JSON output
+    fn json_name(self) -> &'static str {
+        match self {
+            Self::Args => "args",
+            Self::MissingRequired => "missing_required",
+            Self::InvalidValue => "invalid_value",
+            Self::TooFewArguments => "too_few_arguments",
+            Self::TooManyArguments => "too_many_arguments",
+            Self::ValidationFailed => "validation_failed",
+            Self::InvalidUtf8Env => "invalid_utf8_env",
+            Self::MissingSubcommand => "missing_subcommand",
+            Self::Document => "document",
+            Self::EnvCmdFailed => "env_cmd_failed",
+            Self::ResolveFailed => "resolve_failed",
+            Self::UnknownEnv => "unknown_env",
+            Self::UnknownDocumentKey => "unknown_document_key",
+            Self::Deprecated => "deprecated",
+        }
+    }
+}
+
+/// A single problem found while parsing. See [`Error::items`].
+///
+/// The values of secret options never appear in these.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ErrorItem {
+    /// The kind of problem
+    pub kind: ErrorKind,
+    /// The id path of the option that has the problem, e.g. `"db.url"`, if the problem is about
+    /// a single option.
+    pub id: Option<String>,
+    /// The switch of that option, e.g. `"--db-url"`, if it has one.
+    pub switch: Option<String>,
+    /// The env var that a bad value was read from, or else the env var of the option, if any.
+    pub env: Option<String>,
+    /// Where a bad value came from, if the problem is about a value.
+    pub source: Option<ValueSource>,
+    /// The bad value, if the problem is about a value. This is [`ReportValue::Redacted`] if the
+    /// option is secret, or the value is the output of an `env_cmd`.
+    pub value: Option<ReportValue>,
+    /// For `TooFewArguments` and `TooManyArguments`, the ids of the options and argument groups
+    /// in the constraint.
+    pub constraint_ids: Vec<String>,
+    /// A description of the problem, as it would be displayed (without color), but without the
+    /// title that is shared by all items of the same kind.
+    pub message: String,
+}
+
+impl ErrorItem {
+    fn write_json(&self, buf: &mut String) {
+        let opt_str = |buf: &mut String, val: Option<&str>| match val {
+            Some(val) => json::write_str(buf, val),
+            None => buf.push_str("null"),
+        };
+
+        let mut obj = json::ObjectWriter::new(buf);
+        obj.str("kind", self.kind.json_name());
+        opt_str(obj.key("id"), self.id.as_deref());
+        opt_str(obj.key("switch"), self.switch.as_deref());
+        opt_str(obj.key("env"), self.env.as_deref());
+        let source = obj.key("source");
+        match &self.source {
+            Some(src) => src.write_json(source),
+            None => source.push_str("null"),
+        }
+        let value = obj.key("value");
+        match &self.value {
+            Some(ReportValue::String(s)) => json::write_str(value, s),
+            Some(ReportValue::Redacted) => json::write_str(value, "[REDACTED]"),
+            _ => value.push_str("null"),
+        }
+        json::write_str_array(
+            obj.key("constraint_ids"),
+            self.constraint_ids.iter().map(String::as_str),
+        );
+        obj.str("message", &self.message);
+        obj.end();
+    }
+}
+
+impl fmt::Display for ErrorItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
     }
 }
 
@@ -65,10 +353,12 @@ impl From<fmt::Error> for Error {
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum InnerError {
     /// Missing a required parameter
-    // (missing program option, optional reason it is required)
+    // (missing program option, optional reason it is required, env vars which are set and look
+    // like misspellings of its env form)
     MissingRequiredParameter(
         Box<ProgramOption>,
         Option<Box<OwnedFlattenedOptionalDebugInfo>>,
+        Vec<String>,
     ),
     /// Invalid parameter value
     // (source, value string, program option, error message)
@@ -98,12 +388,32 @@ pub enum InnerError {
     /// Invalid UTF-8 in env
     // (value omitted if it is secret)
     InvalidUtf8Env(String, Box<ProgramOption>, Option<OsString>),
+    /// A command named by an env var (for `env_cmd`) failed
+    // (env var, program option, description of failure, which must not include the output)
+    EnvCmdFailed(String, Box<ProgramOption>, String),
+    /// A reference could not be resolved by a resolver
+    // (source, reference (omitted if secret), program option, error message)
+    ResolveFailed(
+        ConfValueSource<String>,
+        Option<String>,
+        Box<ProgramOption>,
+        String,
+    ),
     /// Missing required subcommand
     // struct name, field name, subcommands
     MissingRequiredSubcommand(String, String, Vec<String>),
     /// Parsing (document)
     // document name, field name, error
     Serde(String, String, String),
+    /// An env var starts with the env prefix but is not read by any option (`deny_unknown_env`)
+    // env var, known env vars which look similar
+    UnknownEnv(String, Vec<String>),
+    /// A document has a key which does not correspond to any field
+    // document name, dotted path of the key, known keys which look similar, all known keys
+    UnknownDocumentKey(String, String, Vec<String>, Vec<String>),
+    /// A deprecated option or env alias was used
+    // (source, program option, deprecation note, which may be empty)
+    Deprecated(ConfValueSource<String>, Box<ProgramOption>, String),
 }
 
 impl InnerError {
@@ -127,10 +437,12 @@ impl InnerError {
     pub(crate) fn missing_required_parameter(
         opt: &ProgramOption,
         flattened_optional_debug_info: Option<FlattenedOptionalDebugInfo<'_>>,
+        similar_env_vars: Vec<String>,
     ) -> Self {
         Self::MissingRequiredParameter(
             Box::new(opt.clone()),
             flattened_optional_debug_info.map(Into::into).map(Box::new),
+            similar_env_vars,
         )
     }
 
@@ -221,6 +533,30 @@ impl InnerError {
         )
     }
 
+    /// Helper which makes EnvCmdFailed
+    pub(crate) fn env_cmd_failed(env_var: &str, program_option: &ProgramOption, err: &str) -> Self {
+        Self::EnvCmdFailed(
+            env_var.to_owned(),
+            Box::new(program_option.clone()),
+            err.to_owned(),
+        )
+    }
+
+    /// Helper which makes ResolveFailed
+    pub(crate) fn resolve_failed(
+        conf_value_source: ConfValueSource<&str>,
+        reference: &str,
+        program_option: &ProgramOption,
+        err: impl fmt::Display,
+    ) -> Self {
+        Self::ResolveFailed(
+            conf_value_source.into_owned(),
+            (!program_option.is_secret()).then(|| reference.to_owned()),
+            Box::new(program_option.clone()),
+            err.to_string(),
+        )
+    }
+
     /// Helper which makes MissingRequiredSubcommand
     pub fn missing_required_subcommand(
         struct_name: &str,
@@ -243,10 +579,54 @@ impl InnerError {
         )
     }
 
+    /// Helper which makes UnknownEnv
+    pub(crate) fn unknown_env(env_var: &str, similar_env_names: Vec<String>) -> Self {
+        Self::UnknownEnv(env_var.to_owned(), similar_env_names)
+    }
+
+    /// Helper which makes UnknownDocumentKey
+    pub fn unknown_document_key(
+        document_name: &str,
+        document_path: &str,
+        key: &str,
+        expected_keys: &[&str],
+    ) -> Self {
+        Self::UnknownDocumentKey(
+            document_name.to_owned(),
+            document_path.to_owned(),
+            similar_names(key, expected_keys),
+            expected_keys.iter().map(|key| (*key).to_owned()).collect(),
+        )
+    }
+
+    /// Helper which makes Deprecated, if a value from this source uses a deprecated option or
+    /// env alias
+    pub(crate) fn deprecated(
+        conf_value_source: &ConfValueSource<&str>,
+        program_option: &ProgramOption,
+    ) -> Option<Self> {
+        let note = match (conf_value_source, program_option.deprecated.as_deref()) {
+            (ConfValueSource::Default, _) => return None,
+            (_, Some(note)) => note.to_owned(),
+            (ConfValueSource::Env(name), None) if program_option.is_deprecated_env_alias(name) => {
+                let env_form = program_option.env_form.as_deref().unwrap_or_default();
+                format!("use '{env_form}' instead")
+            }
+            _ => return None,
+        };
+        Some(Self::Deprecated(
+            conf_value_source.clone().into_owned(),
+            Box::new(program_option.clone()),
+            note,
+        ))
+    }
+
     // A short (one-line) description of the problem
     fn title(&self) -> &'static str {
         match self {
             Self::InvalidUtf8Env(..) => "An env var contained invalid UTF8",
+            Self::EnvCmdFailed(..) => "An env command failed",
+            Self::ResolveFailed(..) => "A reference could not be resolved",
             Self::MissingRequiredParameter(..) => "A required value was not provided",
             Self::TooFewArguments(..) => "Too few arguments",
             Self::TooManyArguments(..) => "Too many arguments",
@@ -254,20 +634,28 @@ impl InnerError {
             Self::InvalidParameterValue(..) => "Invalid value",
             Self::MissingRequiredSubcommand(..) => "Missing required subcommand",
             Self::Serde(..) => "Parsing document",
+            Self::UnknownEnv(..) => "Unknown env var",
+            Self::UnknownDocumentKey(..) => "Unknown document key",
+            Self::Deprecated(..) => "Deprecated option was used",
         }
     }
 
     // convert to clap error kind
-    fn error_kind(&self) -> ErrorKind {
+    fn error_kind(&self) -> ClapErrorKind {
         match self {
-            Self::InvalidUtf8Env(..) => ErrorKind::InvalidValue,
-            Self::MissingRequiredParameter(..) => ErrorKind::MissingRequiredArgument,
-            Self::TooFewArguments(..) => ErrorKind::TooFewValues,
-            Self::TooManyArguments(..) => ErrorKind::TooManyValues,
-            Self::ValidationFailed(..) => ErrorKind::ValueValidation,
-            Self::InvalidParameterValue(..) => ErrorKind::InvalidValue,
-            Self::MissingRequiredSubcommand(..) => ErrorKind::MissingSubcommand,
-            Self::Serde(..) => ErrorKind::InvalidValue,
+            Self::InvalidUtf8Env(..) => ClapErrorKind::InvalidValue,
+            Self::EnvCmdFailed(..) => ClapErrorKind::Io,
+            Self::ResolveFailed(..) => ClapErrorKind::InvalidValue,
+            Self::MissingRequiredParameter(..) => ClapErrorKind::MissingRequiredArgument,
+            Self::TooFewArguments(..) => ClapErrorKind::TooFewValues,
+            Self::TooManyArguments(..) => ClapErrorKind::TooManyValues,
+            Self::ValidationFailed(..) => ClapErrorKind::ValueValidation,
+            Self::InvalidParameterValue(..) => ClapErrorKind::InvalidValue,
+            Self::MissingRequiredSubcommand(..) => ClapErrorKind::MissingSubcommand,
+            Self::Serde(..) => ClapErrorKind::InvalidValue,
+            Self::UnknownEnv(..) => ClapErrorKind::UnknownArgument,
+            Self::UnknownDocumentKey(..) => ClapErrorKind::UnknownArgument,
+            Self::Deprecated(..) => ClapErrorKind::ArgumentConflict,
         }
     }
 
@@ -276,6 +664,8 @@ impl InnerError {
     fn get_program_option(&self) -> Option<&ProgramOption> {
         match self {
             Self::InvalidUtf8Env(_, opt, _) => Some(opt),
+            Self::EnvCmdFailed(_, opt, _) => Some(opt),
+            Self::ResolveFailed(_, _, opt, _) => Some(opt),
             Self::MissingRequiredParameter(opt, ..) => Some(opt),
             Self::InvalidParameterValue(_src, _val_str, opt, _err) => Some(opt),
             Self::TooFewArguments(..) => None,
@@ -283,6 +673,9 @@ impl InnerError {
             Self::ValidationFailed(..) => None,
             Self::MissingRequiredSubcommand(..) => None,
             Self::Serde(..) => None,
+            Self::UnknownEnv(..) => None,
+            Self::UnknownDocumentKey(..) => None,
+            Self::Deprecated(_, opt, _) => Some(opt),
         }
     }
 
@@ -336,13 +729,55 @@ impl InnerError {
                     invalid.render_reset()
                 )?;
             }
-            Self::MissingRequiredParameter(opt, maybe_flatten_optional_debug_info) => {
+            Self::EnvCmdFailed(name, _opt, err) => {
+                let context = format!("  when running command from env '{name}'");
+                let estimated_len = context.len();
+                writeln!(
+                    stream,
+                    "{context}: {err_str}",
+                    err_str = Self::format_err_str(err, estimated_len + 2)
+                )?;
+            }
+            Self::ResolveFailed(value_source, maybe_reference, opt, err) => {
+                let context = format!(
+                    "  when resolving {} value",
+                    render_provided_opt(opt, value_source)
+                );
+                let mut estimated_len = context.len();
+                write!(stream, "{context}")?;
+                if let Some(reference) = maybe_reference {
+                    write!(
+                        stream,
+                        " {}'{reference}'{}",
+                        invalid.render(),
+                        invalid.render_reset()
+                    )?;
+                    estimated_len += 3 + reference.len();
+                }
+                writeln!(
+                    stream,
+                    ": {err_str}",
+                    err_str = Self::format_err_str(err, estimated_len + 2)
+                )?;
+            }
+            Self::MissingRequiredParameter(
+                opt,
+                maybe_flatten_optional_debug_info,
+                similar_env_vars,
+            ) => {
                 print_opt_requirements(stream, opt, "must be provided")?;
                 if let Some(flatten_optional) = maybe_flatten_optional_debug_info.as_ref() {
                     // Indent 4 spaces
                     write!(stream, "    ")?;
                     flatten_optional.print_required_opt_context(stream)?;
                 }
+                for name in similar_env_vars {
+                    let env_form = opt.env_form.as_deref().unwrap_or_default();
+                    writeln!(
+                        stream,
+                        "    note: env '{name}' is set, did you mean '{env_form}'?"
+                    )?;
+                }
             }
             Self::InvalidParameterValue(value_source, value_str, opt, err) => {
                 let context = format!(
@@ -351,7 +786,8 @@ impl InnerError {
                 );
                 let mut estimated_len = context.len();
                 write!(stream, "{context}")?;
-                if !opt.is_secret() {
+                // The output of an env command is never shown, since it is likely a secret
+                if !opt.is_secret() && !matches!(value_source, ConfValueSource::EnvCmd(_)) {
                     write!(
                         stream,
                         " {}'{value_str}'{}",
@@ -445,10 +881,148 @@ impl InnerError {
                     err_str = Self::format_err_str(err, estimated_len + 2)
                 )?;
             }
+            Self::UnknownEnv(name, similar_env_names) => {
+                writeln!(
+                    stream,
+                    "  env '{name}' is set, but is not read by any option"
+                )?;
+                if !similar_env_names.is_empty() {
+                    let alternatives = render_alternatives(similar_env_names);
+                    writeln!(stream, "    note: did you mean {alternatives}?")?;
+                }
+            }
+            Self::UnknownDocumentKey(document_name, path, similar_keys, expected_keys) => {
+                writeln!(
+                    stream,
+                    "  Parsing {document_name}: unknown key {}'{path}'{}",
+                    invalid.render(),
+                    invalid.render_reset()
+                )?;
+                if !similar_keys.is_empty() {
+                    let alternatives = render_alternatives(similar_keys);
+                    writeln!(stream, "    note: did you mean {alternatives}?")?;
+                } else if !expected_keys.is_empty() {
+                    let expected = expected_keys
+                        .iter()
+                        .map(|key| format!("'{key}'"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    writeln!(stream, "    expected one of: {expected}")?;
+                }
+            }
+            Self::Deprecated(value_source, opt, note) => {
+                let provided_opt = match value_source {
+                    ConfValueSource::Document(name) => format!("'{}' in document '{name}'", opt.id),
+                    _ => render_provided_opt(opt, value_source),
+                };
+                if note.is_empty() {
+                    writeln!(stream, "  {provided_opt} is deprecated")?;
+                } else {
+                    writeln!(stream, "  {provided_opt} is deprecated: {note}")?;
+                }
+            }
         }
         Ok(())
     }
 
+    // Describe this error as a public ErrorItem
+    pub(crate) fn to_item(&self) -> ErrorItem {
+        let mut body = String::new();
+        self.print_body(&mut body, &Styles::plain()).unwrap();
+        let message = body
+            .lines()
+            .map(|line| line.strip_prefix("  ").unwrap_or(line).trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let (kind, source) = match self {
+            Self::InvalidUtf8Env(name, ..) => (
+                ErrorKind::InvalidUtf8Env,
+                Some(ValueSource::Env(name.clone())),
+            ),
+            Self::EnvCmdFailed(name, ..) => (
+                ErrorKind::EnvCmdFailed,
+                Some(ValueSource::EnvCmd(name.clone())),
+            ),
+            Self::ResolveFailed(src, ..) => (ErrorKind::ResolveFailed, Some(to_value_source(src))),
+            Self::MissingRequiredParameter(..) => (ErrorKind::MissingRequired, None),
+            Self::TooFewArguments(..) => (ErrorKind::TooFewArguments, None),
+            Self::TooManyArguments(..) => (ErrorKind::TooManyArguments, None),
+            Self::ValidationFailed(..) => (ErrorKind::ValidationFailed, None),
+            Self::InvalidParameterValue(src, ..) => {
+                (ErrorKind::InvalidValue, Some(to_value_source(src)))
+            }
+            Self::MissingRequiredSubcommand(..) => (ErrorKind::MissingSubcommand, None),
+            Self::Serde(document_name, ..) => (
+                ErrorKind::Document,
+                Some(ValueSource::Document(document_name.clone())),
+            ),
+            Self::UnknownEnv(name, _) => {
+                (ErrorKind::UnknownEnv, Some(ValueSource::Env(name.clone())))
+            }
+            Self::UnknownDocumentKey(document_name, ..) => (
+                ErrorKind::UnknownDocumentKey,
+                Some(ValueSource::Document(document_name.clone())),
+            ),
+            Self::Deprecated(src, ..) => (ErrorKind::Deprecated, Some(to_value_source(src))),
+        };
+
+        let constraint_ids = match self {
+            Self::TooFewArguments(_, id_prefix, single_opts, flattened_ids, _) => single_opts
+                .iter()
+                .map(|opt| opt.id.to_string())
+                .chain(flattened_ids.iter().map(|id| format!("{id_prefix}{id}")))
+                .collect(),
+            Self::TooManyArguments(_, id_prefix, single_opts, flattened_opts) => single_opts
+                .iter()
+                .map(|(opt, _)| opt.id.to_string())
+                .chain(
+                    flattened_opts
+                        .iter()
+                        .map(|(field_name, ..)| format!("{id_prefix}{field_name}")),
+                )
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let value = match self {
+            Self::InvalidParameterValue(src, value_str, opt, _) => Some(
+                if opt.is_secret() || matches!(src, ConfValueSource::EnvCmd(_)) {
+                    ReportValue::Redacted
+                } else {
+                    ReportValue::String(value_str.clone())
+                },
+            ),
+            Self::InvalidUtf8Env(_, opt, maybe_val) => Some(match maybe_val {
+                Some(val) if !opt.is_secret() => ReportValue::String(val.to_string_lossy().into()),
+                _ => ReportValue::Redacted,
+            }),
+            Self::ResolveFailed(_, maybe_reference, ..) => Some(match maybe_reference {
+                Some(reference) => ReportValue::String(reference.clone()),
+                None => ReportValue::Redacted,
+            }),
+            _ => None,
+        };
+
+        let opt = self.get_program_option();
+        let env = source
+            .as_ref()
+            .and_then(|src| src.env_name())
+            .map(str::to_owned)
+            .or_else(|| opt.and_then(|opt| opt.env_form.as_deref().map(str::to_owned)));
+
+        ErrorItem {
+            kind,
+            id: opt.map(|opt| opt.id.to_string()),
+            switch: opt.and_then(render_help_switch),
+            env,
+            source,
+            value,
+            constraint_ids,
+            message,
+        }
+    }
+
     // Formats an error string to look nicely indented if it has line breaks and starting with a
     // line break if the error is long.
     fn format_err_str(err_str: &str, estimated_line_length_so_far: usize) -> String {
@@ -486,6 +1060,10 @@ fn print_opt_requirements(
         }
         (Some(switch), None) => writeln!(stream, "  '{switch}' {trailing_text}")?,
         (None, Some(name)) => writeln!(stream, "  env '{name}' {trailing_text}")?,
+        (None, None) if opt.env_cmd.is_some() => {
+            let name = opt.env_cmd.as_deref().unwrap_or_default();
+            writeln!(stream, "  command from env '{name}' {trailing_text}")?
+        }
         (None, None) => {
             debug_assert!(false, "This should be unreachable, we should not be printing opt requirements for an option with no way to specify it");
             writeln!(
@@ -510,13 +1088,26 @@ fn render_provided_opt(opt: &ProgramOption, value_source: &ConfValueSource<Strin
         ConfValueSource::Env(name) => {
             format!("env '{name}'")
         }
+        ConfValueSource::EnvCmd(name) => {
+            format!("command from env '{name}'")
+        }
         ConfValueSource::Document(name) => {
             format!("document '{name}'")
         }
     }
 }
 
-fn render_help_switch(opt: &ProgramOption) -> Option<String> {
+fn to_value_source(value_source: &ConfValueSource<String>) -> ValueSource {
+    match value_source {
+        ConfValueSource::Args => ValueSource::Args,
+        ConfValueSource::Default => ValueSource::Default,
+        ConfValueSource::Env(name) => ValueSource::Env(name.clone()),
+        ConfValueSource::EnvCmd(name) => ValueSource::EnvCmd(name.clone()),
+        ConfValueSource::Document(name) => ValueSource::Document(name.clone()),
+    }
+}
+
+pub(crate) fn render_help_switch(opt: &ProgramOption) -> Option<String> {
     // If we have both a long and a short form, prefer to display the long form in this help message
     opt.long_form
         .as_deref()
@@ -524,6 +1115,20 @@ fn render_help_switch(opt: &ProgramOption) -> Option<String> {
         .or_else(|| opt.short_form.map(|s| format!("-{s}")))
 }
 
+// Render a list of names like `'a', 'b' or 'c'`
+pub(crate) fn render_alternatives(names: &[String]) -> String {
+    let mut quoted = names
+        .iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<_>>();
+    let last = quoted.pop().unwrap_or_default();
+    if quoted.is_empty() {
+        last
+    } else {
+        format!("{} or {last}", quoted.join(", "))
+    }
+}
+
 fn remove_trailing_dot(string: &mut String) {
     if let Some(c) = string.pop() {
         if c != '.' {
@@ -582,9 +1187,11 @@ impl InnerError {
         let mut buf = String::new();
 
         self.print_solo(&mut buf, command.get_styles()).unwrap();
-        ClapError::raw(self.error_kind(), buf)
-            .with_cmd(command)
-            .into()
+        Error {
+            repr: ErrorRepr::Clap(ClapError::raw(self.error_kind(), buf).with_cmd(command)),
+            items: vec![self.to_item()],
+            format: ErrorFormat::Text,
+        }
     }
 
     pub(crate) fn vec_to_clap_error(mut src: Vec<InnerError>, command: &Command) -> Error {
@@ -601,6 +1208,8 @@ impl InnerError {
 
         let mut buf = String::new();
 
+        let items = src.iter().map(InnerError::to_item).collect();
+
         let mut last_title = "";
         for err in src {
             if err.title() != last_title {
@@ -618,8 +1227,10 @@ impl InnerError {
             }
             err.print_body(&mut buf, styles).unwrap();
         }
-        ClapError::raw(last_error_kind, buf)
-            .with_cmd(command)
-            .into()
+        Error {
+            repr: ErrorRepr::Clap(ClapError::raw(last_error_kind, buf).with_cmd(command)),
+            items,
+            format: ErrorFormat::Text,
+        }
     }
 }
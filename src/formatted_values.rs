@@ -0,0 +1,192 @@
+use crate::{ProgramOption, Secret};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+};
+use zeroize::Zeroize;
+
+// The formatted values of the program options of a Conf structure, from which
+// ConfToArgs::to_args and ConfToArgs::to_env_vars are produced. This is filled in by the derive macro.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct FormattedValues {
+    // Values by option id, relative to the structure
+    values: Vec<(String, FormattedValue)>,
+    // The name of the selected subcommand, its program options and its values
+    subcommand: Option<(&'static str, &'static [ProgramOption], Box<FormattedValues>)>,
+}
+
+enum FormattedValue {
+    Flag(bool),
+    Value(OsString),
+    // The values of a repeat option, and its env delimiter
+    Values(Vec<OsString>, Option<char>),
+}
+
+impl FormattedValues {
+    #[doc(hidden)]
+    pub fn push_flag(&mut self, id: &str, val: bool) {
+        self.values.push((id.to_owned(), FormattedValue::Flag(val)));
+    }
+
+    // A value of None means that an optional parameter is not set
+    #[doc(hidden)]
+    pub fn push_value(&mut self, id: &str, val: Option<OsString>) {
+        if let Some(val) = val {
+            self.values
+                .push((id.to_owned(), FormattedValue::Value(val)));
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn push_values(&mut self, id: &str, vals: Vec<OsString>, env_delimiter: Option<char>) {
+        self.values
+            .push((id.to_owned(), FormattedValue::Values(vals, env_delimiter)));
+    }
+
+    // Flattened structures can't have subcommands, so only the values are taken from inner
+    #[doc(hidden)]
+    pub fn push_flattened(&mut self, id_prefix: &str, inner: FormattedValues) {
+        self.values.extend(
+            inner
+                .values
+                .into_iter()
+                .map(|(id, val)| (format!("{id_prefix}{id}"), val)),
+        );
+    }
+
+    #[doc(hidden)]
+    pub fn set_subcommand(
+        &mut self,
+        command_name: &'static str,
+        program_options: &'static [ProgramOption],
+        inner: FormattedValues,
+    ) {
+        self.subcommand = Some((command_name, program_options, Box::new(inner)));
+    }
+
+    // Append CLI args for these values, given the program options of the structure.
+    //
+    // Options without a switch are skipped, and so are secrets which can be read from env, so
+    // that they don't appear in process listings.
+    pub(crate) fn to_args(&self, program_options: &[ProgramOption], args: &mut Vec<OsString>) {
+        for (id, val) in &self.values {
+            let Some(opt) = program_options.iter().find(|opt| opt.id == *id) else {
+                continue;
+            };
+            if opt.is_secret() && opt.env_form.is_some() {
+                continue;
+            }
+            let switch = match (opt.long_form.as_deref(), opt.short_form) {
+                (Some(long), _) => format!("--{long}"),
+                (None, Some(short)) => format!("-{short}"),
+                (None, None) => continue,
+            };
+            let with_value = |val: &OsStr| {
+                let mut arg = OsString::from(format!("{switch}="));
+                arg.push(val);
+                arg
+            };
+            match val {
+                FormattedValue::Flag(true) => args.push(switch.into()),
+                FormattedValue::Flag(false) => {}
+                FormattedValue::Value(val) => args.push(with_value(val)),
+                FormattedValue::Values(vals, _) => {
+                    args.extend(vals.iter().map(|val| with_value(val)))
+                }
+            }
+        }
+        if let Some((command_name, sub_options, sub_values)) = self.subcommand.as_ref() {
+            args.push(command_name.into());
+            sub_values.to_args(sub_options, args);
+        }
+    }
+
+    // Append env vars for these values, given the program options of the structure.
+    //
+    // Options without an env form are skipped, and so are repeat options without an env
+    // delimiter which have more than one value.
+    pub(crate) fn to_env_vars(
+        &self,
+        program_options: &[ProgramOption],
+        env: &mut Vec<(OsString, OsString)>,
+    ) {
+        for (id, val) in &self.values {
+            let Some(env_form) = program_options
+                .iter()
+                .find(|opt| opt.id == *id)
+                .and_then(|opt| opt.env_form.as_deref())
+            else {
+                continue;
+            };
+            let env_val = match val {
+                FormattedValue::Flag(true) => "true".into(),
+                FormattedValue::Flag(false) => continue,
+                FormattedValue::Value(val) => val.clone(),
+                FormattedValue::Values(vals, _) if vals.is_empty() => continue,
+                FormattedValue::Values(vals, Some(delim)) => join(vals, *delim),
+                FormattedValue::Values(vals, None) if vals.len() == 1 => vals[0].clone(),
+                FormattedValue::Values(_, None) => continue,
+            };
+            env.push((env_form.into(), env_val));
+        }
+        if let Some((_, sub_options, sub_values)) = self.subcommand.as_ref() {
+            sub_values.to_env_vars(sub_options, env);
+        }
+    }
+}
+
+// Join values with a delimiter, like `[String]::join`
+fn join(vals: &[OsString], delimiter: char) -> OsString {
+    let mut result = OsString::new();
+    for (idx, val) in vals.iter().enumerate() {
+        if idx > 0 {
+            result.push(delimiter.encode_utf8(&mut [0; 4]));
+        }
+        result.push(val);
+    }
+    result
+}
+
+// Helpers which format the value of a field for ConfToArgs, when no value_formatter is given.
+// The derive macro calls `(&&&FormatValue(&val)).format_value()`, and method resolution picks the
+// first of these traits which is implemented (autoref specialization). If none of them is
+// implemented, this is a compile error.
+//
+// Secret<T> redacts itself in Display, so it is exposed first. Types like PathBuf don't implement
+// Display, but can be formatted via AsRef<OsStr>, without a lossy conversion.
+#[doc(hidden)]
+pub struct FormatValue<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait FormatSecret {
+    fn format_value(&self) -> OsString;
+}
+
+impl<T: Zeroize + fmt::Display> FormatSecret for &&FormatValue<'_, Secret<T>> {
+    fn format_value(&self) -> OsString {
+        self.0.expose().to_string().into()
+    }
+}
+
+#[doc(hidden)]
+pub trait FormatDisplay {
+    fn format_value(&self) -> OsString;
+}
+
+impl<T: fmt::Display> FormatDisplay for &FormatValue<'_, T> {
+    fn format_value(&self) -> OsString {
+        self.0.to_string().into()
+    }
+}
+
+#[doc(hidden)]
+pub trait FormatOsStr {
+    fn format_value(&self) -> OsString;
+}
+
+impl<T: AsRef<OsStr>> FormatOsStr for FormatValue<'_, T> {
+    fn format_value(&self) -> OsString {
+        self.0.as_ref().to_owned()
+    }
+}
@@ -0,0 +1,120 @@
+// The `--help-env` page, which lists options in terms of env vars rather than CLI args.
+//
+// Every env var that is read is listed, including aliases, env command vars, and the env vars of
+// options which also have switches. Each one is listed with its current value in the env that the
+// parser was given (unless it is secret), and is sorted by name.
+
+use crate::{program_option::render_deprecated_tag, Parser, ProgramOption};
+use std::fmt::Write;
+
+// A single env var in the listing
+struct EnvEntry<'b> {
+    name: &'b str,
+    option: &'b ProgramOption,
+    // The subcommand that the option belongs to, if any, e.g. `run`
+    command: String,
+    // How this env var relates to the option, if it is not the env var of the option
+    role: Option<String>,
+}
+
+impl<'a> Parser<'a> {
+    // Render the `--help-env` page for this parser and its subcommands
+    pub(crate) fn render_env_help(&self) -> String {
+        let mut entries = Vec::new();
+        self.collect_env_entries("", &mut entries);
+        entries.sort_by(|a, b| a.name.cmp(b.name));
+
+        let mut buf = "Environment variables:\n".to_owned();
+        if entries.is_empty() {
+            buf += "\n  (none)\n";
+        }
+        for entry in entries {
+            self.write_env_entry(&entry, &mut buf).unwrap();
+        }
+        buf
+    }
+
+    fn collect_env_entries<'b>(&'b self, command_prefix: &str, out: &mut Vec<EnvEntry<'b>>) {
+        for opt in self.get_options().iter().filter(|opt| !opt.hidden) {
+            let primary = opt.env_form.as_deref();
+            let mut push = |name: &'b str, role: Option<String>| {
+                out.push(EnvEntry {
+                    name,
+                    option: opt,
+                    command: command_prefix.trim_end().to_owned(),
+                    role,
+                })
+            };
+            if let Some(name) = primary {
+                push(name, None);
+            }
+            let describe_primary = || match primary {
+                Some(name) => name.to_owned(),
+                None => format!("<{}>", opt.id),
+            };
+            for alias in opt.env_aliases.iter() {
+                let kind = if opt.is_deprecated_env_alias(alias) {
+                    "deprecated alias"
+                } else {
+                    "alias"
+                };
+                push(alias, Some(format!("{kind} of {}", describe_primary())));
+            }
+            if let Some(name) = opt.env_cmd.as_deref() {
+                push(
+                    name,
+                    Some(format!("command whose output is {}", describe_primary())),
+                );
+            }
+        }
+        for subcommand in self.get_subcommands() {
+            let command = format!("{command_prefix}{} ", subcommand.get_command().get_name());
+            subcommand.collect_env_entries(&command, out);
+        }
+    }
+
+    fn write_env_entry(&self, entry: &EnvEntry, buf: &mut String) -> std::fmt::Result {
+        let opt = entry.option;
+        writeln!(buf, "\n  {}", entry.name)?;
+        if let Some(desc) = opt.description.as_deref() {
+            writeln!(buf, "      {}", desc.replace('\n', "\n      "))?;
+        }
+        if let Some(role) = entry.role.as_deref() {
+            writeln!(buf, "      [{role}]")?;
+        }
+        if !entry.command.is_empty() {
+            writeln!(buf, "      [subcommand: {}]", entry.command)?;
+        }
+        let switches = opt
+            .short_form
+            .map(|short| format!("-{short}"))
+            .into_iter()
+            .chain(opt.long_form.as_deref().map(|long| format!("--{long}")))
+            .collect::<Vec<_>>();
+        if !switches.is_empty() {
+            writeln!(buf, "      [switch: {}]", switches.join(", "))?;
+        }
+        match self.get_env().get(entry.name) {
+            None => writeln!(buf, "      [not set]")?,
+            Some(_) if !opt.shows_env_value() => writeln!(buf, "      [current value: <hidden>]")?,
+            Some(_) => writeln!(
+                buf,
+                "      [current value: {}]",
+                self.get_env().get_lossy_or_default(entry.name)
+            )?,
+        }
+        if opt.is_required {
+            writeln!(buf, "      [required]")?;
+        }
+        if let Some(def) = opt.default_value.as_deref() {
+            writeln!(buf, "      [default: {def}]")?;
+        }
+        if opt.is_secret() {
+            writeln!(buf, "      [secret]")?;
+        }
+        if let Some(note) = opt.deprecated.as_deref() {
+            writeln!(buf, "      {}", render_deprecated_tag(note))?;
+        }
+        Ok(())
+    }
+}
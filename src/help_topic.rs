@@ -0,0 +1,194 @@
+// Focused help, i.e. `--help <topic>`, where the topic names a single option, a group of flattened
+// options, or a subcommand.
+//
+// Clap's help flag doesn't take a value. When it is used, clap stops parsing and reports that
+// help should be displayed, so we look for a topic after the help flag at that point.
+
+use crate::{Error, Parser, ProgramOption};
+use clap::{parser::ValueSource as ClapValueSource, ArgMatches};
+use std::ffi::OsString;
+
+// Find a help flag followed by a topic, e.g. `--help db`, `--help=db` or `--help --db-url`.
+// Returns the range of args which the help flag and the topic occupy, and the topic.
+fn find_help_topic(args: &[OsString]) -> Option<(usize, usize, String)> {
+    for (idx, arg) in args.iter().enumerate().skip(1) {
+        let arg = arg.to_str()?;
+        if arg == "--" {
+            return None;
+        }
+        if let Some(topic) = arg.strip_prefix("--help=") {
+            return Some((idx, idx + 1, topic.to_owned()));
+        }
+        if arg == "--help" || arg == "-h" {
+            let topic = args.get(idx + 1)?.to_str()?;
+            if topic == "--" {
+                return None;
+            }
+            return Some((idx, idx + 2, topic.to_owned()));
+        }
+    }
+    None
+}
+
+impl<'a> Parser<'a> {
+    // If the args request help on a topic, render it, and return it as an error which causes
+    // the program to print it and exit. If the topic is unknown, the usual help is rendered, as if
+    // there was no topic.
+    // Returns None if the args don't request help on a topic.
+    pub(crate) fn help_topic(&self, args: &[OsString]) -> Option<Error> {
+        if self.get_parser_config().no_help_flag {
+            return None;
+        }
+        let (start, end, topic) = find_help_topic(args)?;
+
+        // Use the args before the help flag to find which subcommand help was requested for.
+        let matches = self
+            .get_command()
+            .clone()
+            .try_get_matches_from(&args[..start])
+            .ok()?;
+        // Use the rest of the args to find where values currently come from.
+        let mut args_without_help = args.to_vec();
+        args_without_help.drain(start..end);
+        let current_matches = self
+            .get_command()
+            .clone()
+            .try_get_matches_from(args_without_help)
+            .ok();
+
+        let mut parser = self;
+        let mut matches = &matches;
+        let mut current_matches = current_matches.as_ref();
+        let mut bin_name = self.get_command().get_name().to_owned();
+        while let Some((name, sub_matches)) = matches.subcommand() {
+            parser = parser
+                .get_subcommands()
+                .iter()
+                .find(|subcommand| subcommand.get_command().get_name() == name)?;
+            matches = sub_matches;
+            current_matches = current_matches
+                .and_then(ArgMatches::subcommand)
+                .filter(|(current_name, _)| *current_name == name)
+                .map(|(_, sub_matches)| sub_matches);
+            bin_name = format!("{bin_name} {name}");
+        }
+
+        parser
+            .render_help_topic(&topic, &bin_name, current_matches)
+            .or_else(|| {
+                let help_flag = if args[start] == "-h" { "-h" } else { "--help" };
+                let mut help_args = args[..start].to_vec();
+                help_args.push(help_flag.into());
+                self.get_command()
+                    .clone()
+                    .try_get_matches_from(help_args)
+                    .err()
+                    .map(Into::into)
+            })
+    }
+
+    fn render_help_topic(
+        &self,
+        topic: &str,
+        bin_name: &str,
+        current_matches: Option<&ArgMatches>,
+    ) -> Option<Error> {
+        let long_topic = topic.strip_prefix("--").unwrap_or(topic);
+
+        // A single option, by its id, switches, or any of its env vars. Hidden options are not
+        // shown, as in the full help.
+        let short_topic = topic
+            .strip_prefix('-')
+            .filter(|short| short.chars().count() == 1)
+            .and_then(|short| short.chars().next());
+        if let Some(opt) = self
+            .get_options()
+            .iter()
+            .filter(|opt| !opt.hidden)
+            .find(|opt| {
+                opt.id == topic
+                    || opt.long_form.as_deref() == Some(long_topic)
+                    || (short_topic.is_some() && opt.short_form == short_topic)
+                    || opt.env_form.as_deref() == Some(topic)
+                    || opt.env_aliases.iter().any(|alias| alias == topic)
+            })
+        {
+            let mut buf = String::new();
+            opt.print(&mut buf, Some(self.get_env())).unwrap();
+            buf += &format!(
+                "          [current source: {}]\n",
+                self.describe_value_source(opt, current_matches)
+            );
+            return Some(Error::output(buf));
+        }
+
+        // A group of flattened options, by the id of the flattened field, or by help heading
+        let id_prefix = format!("{}.", topic.replace('-', "_"));
+        let heading_prefix = format!("{topic} / ");
+        let group = self
+            .get_options()
+            .iter()
+            .filter(|opt| !opt.hidden)
+            .filter(|opt| {
+                opt.id.starts_with(&id_prefix)
+                    || opt.help_heading.as_deref().is_some_and(|heading| {
+                        // Headings of nested groups are combined, like `Database / Pool`, and
+                        // any part of them matches
+                        heading.eq_ignore_ascii_case(topic)
+                            || heading
+                                .split(" / ")
+                                .any(|part| part.eq_ignore_ascii_case(topic))
+                            || heading
+                                .to_ascii_lowercase()
+                                .starts_with(&heading_prefix.to_ascii_lowercase())
+                    })
+            })
+            .collect::<Vec<_>>();
+        if !group.is_empty() {
+            let mut buf = format!("Options for '{topic}':\n");
+            for opt in group {
+                buf.push('\n');
+                opt.print(&mut buf, Some(self.get_env())).unwrap();
+            }
+            return Some(Error::output(buf));
+        }
+
+        // A subcommand
+        self.render_help_with(bin_name, &[topic], None, true)
+            .map(Error::output)
+    }
+
+    // Describe where the value of an option currently comes from, without revealing the value
+    fn describe_value_source(
+        &self,
+        opt: &ProgramOption,
+        current_matches: Option<&ArgMatches>,
+    ) -> String {
+        let from_args = current_matches.is_some_and(|matches| {
+            matches.try_get_raw(&opt.id).is_ok_and(|raw| raw.is_some())
+                && matches.value_source(&opt.id) == Some(ClapValueSource::CommandLine)
+        });
+        if from_args {
+            return "args".to_owned();
+        }
+        if let Some(name) = opt
+            .env_form
+            .iter()
+            .chain(opt.env_aliases.iter())
+            .find(|name| self.get_env().get(name).is_some())
+        {
+            return format!("env {name}");
+        }
+        if let Some(name) = opt
+            .env_cmd
+            .as_deref()
+            .filter(|name| self.get_env().get(name).is_some())
+        {
+            return format!("env command {name}");
+        }
+        if opt.default_value.is_some() {
+            return "default value".to_owned();
+        }
+        "not set".to_owned()
+    }
+}
@@ -0,0 +1,225 @@
+//! Types describing the program options of a [`Conf`] structure.
+//!
+//! See [`introspect`](crate::introspect()).
+//!
+//! Unlike the internal representation that the derive macros use, these types are part of the
+//! public API, and are considered semver-stable. New fields may be added to them in the future
+//! without a breaking change, so they are `#[non_exhaustive]`.
+
+use crate::{Conf, Error, ParseType, ParsedEnv, Parser, ProgramOption};
+
+/// Get a description of all the program options of a [`Conf`] structure, including those of
+/// flattened structures and subcommands.
+///
+/// This is useful for building tooling around your config, for instance, documentation
+/// generators, or linters that check naming conventions of env vars.
+///
+/// This is independent of the actual args and env of the process.
+pub fn introspect<S: Conf>() -> Result<CommandInfo, Error> {
+    let parsed_env = ParsedEnv::default();
+    let parser = S::get_parser(&parsed_env)?;
+    Ok(CommandInfo::from_parser(&parser))
+}
+
+/// Find all the program options of a [`Conf`] structure (including flattened structures and
+/// subcommands) whose id or env var looks like it names a secret, e.g. `db.password` or
+/// `API_TOKEN`.
+///
+/// This can be used in a test, to check that no such option was accidentally left unmarked.
+/// Options that are marked `secret = false` are reported, but [`SecretLint::is_secret`] is false.
+///
+/// See also the `#[conf(secret_heuristics)]` attribute, which marks such options as secret
+/// automatically.
+pub fn secret_lint<S: Conf>() -> Result<Vec<SecretLint>, Error> {
+    let parsed_env = ParsedEnv::default();
+    let parser = S::get_parser(&parsed_env)?;
+    let mut results = Vec::new();
+    secret_lint_parser(&parser, "", &mut results);
+    Ok(results)
+}
+
+fn secret_lint_parser(parser: &Parser, command_prefix: &str, results: &mut Vec<SecretLint>) {
+    let command = format!("{command_prefix}{}", parser.get_command().get_name());
+    for opt in parser.get_options() {
+        if let Some(matched) = opt.looks_like_secret() {
+            results.push(SecretLint {
+                command: command.clone(),
+                id: opt.id.clone().into_owned(),
+                matched,
+                is_secret: opt.is_secret(),
+            });
+        }
+    }
+    for subcommand in parser.get_subcommands() {
+        secret_lint_parser(subcommand, &format!("{command} "), results);
+    }
+}
+
+/// A program option that looks like it is a secret. See [`secret_lint`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SecretLint {
+    /// The command that the option belongs to, e.g. `"my-app"`, or `"my-app run"` for a
+    /// subcommand.
+    pub command: String,
+    /// The id path of the option.
+    pub id: String,
+    /// The word in the id or env var which looked like a secret, e.g. `"password"`.
+    pub matched: &'static str,
+    /// True if this option is a secret.
+    pub is_secret: bool,
+}
+
+/// Description of a command, i.e. a top-level [`Conf`] structure or a subcommand.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct CommandInfo {
+    /// The name of the command. For the top-level, this is the name of the binary.
+    pub name: String,
+    /// The description of the command, if any.
+    pub description: Option<String>,
+    /// All the program options of the command, including flattened options, in declaration order.
+    pub options: Vec<OptionInfo>,
+    /// Any subcommands of this command.
+    pub subcommands: Vec<CommandInfo>,
+}
+
+impl CommandInfo {
+    pub(crate) fn from_parser(parser: &Parser) -> Self {
+        Self {
+            name: parser.get_command().get_name().to_owned(),
+            description: parser.get_parser_config().about.map(str::to_owned),
+            options: parser
+                .get_options()
+                .iter()
+                .map(|opt| OptionInfo::from(*opt))
+                .collect(),
+            subcommands: parser
+                .get_subcommands()
+                .iter()
+                .map(CommandInfo::from_parser)
+                .collect(),
+        }
+    }
+
+    /// Find a program option of this command by its id, e.g. `"db.url"`.
+    pub fn get_option(&self, id: &str) -> Option<&OptionInfo> {
+        self.options.iter().find(|opt| opt.id == id)
+    }
+
+    /// Find a subcommand of this command by its name.
+    pub fn get_subcommand(&self, name: &str) -> Option<&CommandInfo> {
+        self.subcommands.iter().find(|cmd| cmd.name == name)
+    }
+}
+
+/// The kind of a program option.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum OptionKind {
+    /// A flag, which is either present or absent.
+    Flag,
+    /// A parameter, which takes a single value.
+    Parameter,
+    /// A repeat parameter, which may take several values.
+    Repeat,
+}
+
+impl From<ParseType> for OptionKind {
+    fn from(src: ParseType) -> Self {
+        match src {
+            ParseType::Flag => Self::Flag,
+            ParseType::Parameter => Self::Parameter,
+            ParseType::Repeat => Self::Repeat,
+        }
+    }
+}
+
+/// Description of a single program option.
+///
+/// Switches and env vars already have any flattening prefixes applied.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct OptionInfo {
+    /// The id path of this option, e.g. `"db.url"` for field `url` in a structure flattened
+    /// at field `db`.
+    pub id: String,
+    /// The kind of this option.
+    pub kind: OptionKind,
+    /// The short switch, if any.
+    pub short: Option<char>,
+    /// The long switch, if any (without leading `--`).
+    pub long: Option<String>,
+    /// Any aliases of the long switch.
+    pub aliases: Vec<String>,
+    /// The env var, if any.
+    pub env: Option<String>,
+    /// Any aliases of the env var.
+    pub env_aliases: Vec<String>,
+    /// The env var which may name a command to run to get the value, if any.
+    pub env_cmd: Option<String>,
+    /// The default value, if any.
+    pub default_value: Option<String>,
+    /// True if this option must be supplied.
+    pub is_required: bool,
+    /// True if this option is a secret.
+    pub is_secret: bool,
+    /// The description (doc string) of this option, if any.
+    pub description: Option<String>,
+    /// The deprecation note, if this option is deprecated. This may be empty.
+    pub deprecated: Option<String>,
+    /// Any env aliases which are deprecated. These also appear in `env_aliases`.
+    pub deprecated_env_aliases: Vec<String>,
+    /// The heading that this option is listed under in help, if any.
+    pub help_heading: Option<String>,
+    /// True if this option is hidden from help.
+    pub is_hidden: bool,
+}
+
+impl OptionInfo {
+    /// The id path of the flattened group that this option belongs to, if any.
+    ///
+    /// For example, for `"db.pool.max_size"` this is `Some("db.pool")`.
+    pub fn group(&self) -> Option<&str> {
+        self.id.rsplit_once('.').map(|(group, _)| group)
+    }
+
+    /// The env var followed by any env aliases.
+    pub fn env_names(&self) -> impl Iterator<Item = &str> {
+        self.env
+            .iter()
+            .chain(self.env_aliases.iter())
+            .map(String::as_str)
+    }
+}
+
+impl From<&ProgramOption> for OptionInfo {
+    fn from(src: &ProgramOption) -> Self {
+        Self {
+            id: src.id.clone().into_owned(),
+            kind: src.parse_type.into(),
+            short: src.short_form,
+            long: src.long_form.as_ref().map(|s| s.clone().into_owned()),
+            aliases: src.aliases.iter().map(|s| s.clone().into_owned()).collect(),
+            env: src.env_form.as_ref().map(|s| s.clone().into_owned()),
+            env_aliases: src
+                .env_aliases
+                .iter()
+                .map(|s| s.clone().into_owned())
+                .collect(),
+            env_cmd: src.env_cmd.as_ref().map(|s| s.clone().into_owned()),
+            default_value: src.default_value.as_ref().map(|s| s.clone().into_owned()),
+            is_required: src.is_required,
+            is_secret: src.is_secret(),
+            description: src.description.as_ref().map(|s| s.clone().into_owned()),
+            deprecated: src.deprecated.as_ref().map(|s| s.clone().into_owned()),
+            deprecated_env_aliases: src
+                .deprecated_env_aliases
+                .iter()
+                .map(|s| s.clone().into_owned())
+                .collect(),
+            help_heading: src.help_heading.as_ref().map(|s| s.clone().into_owned()),
+            is_hidden: src.hidden,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! A very small JSON writer, used for machine-readable output.
+//!
+//! We don't want to depend on `serde_json` unconditionally just to print a few objects,
+//! and the things we print are simple enough that building the text directly is easy.
+
+use std::fmt::Write;
+
+/// Write a string as a quoted and escaped JSON string
+pub fn write_str(buf: &mut String, src: &str) {
+    buf.push('"');
+    for ch in src.chars() {
+        match ch {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                write!(buf, "\\u{:04x}", ch as u32).unwrap();
+            }
+            ch => buf.push(ch),
+        }
+    }
+    buf.push('"');
+}
+
+/// Write an array of strings
+pub fn write_str_array<'a>(buf: &mut String, src: impl IntoIterator<Item = &'a str>) {
+    buf.push('[');
+    for (idx, s) in src.into_iter().enumerate() {
+        if idx > 0 {
+            buf.push(',');
+        }
+        write_str(buf, s);
+    }
+    buf.push(']');
+}
+
+/// Helper for writing the members of a JSON object one at a time
+pub struct ObjectWriter<'a> {
+    buf: &'a mut String,
+    first: bool,
+}
+
+impl<'a> ObjectWriter<'a> {
+    /// Start a new object
+    pub fn new(buf: &'a mut String) -> Self {
+        buf.push('{');
+        Self { buf, first: true }
+    }
+
+    /// Write a key, and return the buffer so that the caller can write the value
+    pub fn key(&mut self, key: &str) -> &mut String {
+        if !self.first {
+            self.buf.push(',');
+        }
+        self.first = false;
+        write_str(self.buf, key);
+        self.buf.push(':');
+        self.buf
+    }
+
+    /// Write a key with a string value
+    pub fn str(&mut self, key: &str, val: &str) {
+        write_str(self.key(key), val);
+    }
+
+    /// End the object
+    pub fn end(self) {
+        self.buf.push('}');
+    }
+}
@@ -11,13 +11,27 @@
 
 mod builder;
 mod conf_context;
+mod env_cmd;
 mod error;
 mod find_parameter;
+mod formatted_values;
+mod help_env;
+mod help_topic;
+pub mod introspect;
+mod json;
 mod parse_env;
 mod parser;
 mod program_option;
+pub mod reload;
+pub mod report;
+mod resolver;
+mod secret;
 mod str_to_bool;
+mod suggest;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod traits;
+mod warning;
 
 // These are not needed by users or by generated code
 use conf_context::FlattenedOptionalDebugInfo;
@@ -27,9 +41,13 @@ use str_to_bool::str_to_bool;
 
 // These exports represent the public API.
 pub use builder::ConfBuilder;
-pub use error::Error;
+pub use error::{Error, ErrorFormat, ErrorItem, ErrorKind};
 pub use find_parameter::find_parameter;
-pub use traits::{Conf, Subcommands};
+pub use introspect::introspect;
+pub use resolver::{ResolveError, Resolver};
+pub use secret::Secret;
+pub use traits::{Conf, ConfToArgs, Subcommands, SubcommandsToArgs};
+pub use warning::{Warning, WarningKind};
 // Export conf_derive proc-macros unconditionally. Their docs are on the traits that they
 // produce implementations for.
 #[doc(hidden)]
@@ -41,6 +59,10 @@ pub use conf_context::{ConfContext, ConfValueSource};
 #[doc(hidden)]
 pub use error::InnerError;
 #[doc(hidden)]
+pub use formatted_values::{
+    FormatDisplay, FormatOsStr, FormatSecret, FormatValue, FormattedValues,
+};
+#[doc(hidden)]
 pub use parse_env::ParsedEnv;
 #[doc(hidden)]
 pub use parser::{Parser, ParserConfig};
@@ -53,11 +75,14 @@ mod conf_serde;
 // These are publicly documented. Everything needed to understand how to use the builder
 // should be well-documented.
 #[cfg(feature = "serde")]
-pub use conf_serde::{ConfSerde, ConfSerdeBuilder};
+pub use conf_serde::{ConfSerde, ConfSerdeBuilder, SampleValue};
 // These are internals used by the derive macro.
 #[doc(hidden)]
 #[cfg(feature = "serde")]
-pub use conf_serde::{ConfSerdeContext, IdentString, NextValueProducer, SubcommandsSerde};
+pub use conf_serde::{
+    ConfSerdeContext, DocumentField, DocumentStruct, DocumentValueType, IdentString,
+    NextValueProducer, SubcommandsSerde,
+};
 // Re-export serde crate for the proc macro
 #[doc(hidden)]
 #[cfg(feature = "serde")]
@@ -1,18 +1,37 @@
+use crate::env_cmd::{run_env_cmd, DEFAULT_ENV_CMD_TIMEOUT};
 use std::borrow::Cow;
+use std::cell::OnceCell;
 use std::collections::BTreeMap;
 use std::ffi::OsString;
+use std::time::Duration;
 
 /// Represents env variables loaded into memory
-#[derive(Default)]
 pub struct ParsedEnv {
-    map: BTreeMap<String, OsString>,
+    map: BTreeMap<String, EnvValue>,
+    env_cmd_timeout: Duration,
+}
+
+impl Default for ParsedEnv {
+    fn default() -> Self {
+        Self {
+            map: Default::default(),
+            env_cmd_timeout: DEFAULT_ENV_CMD_TIMEOUT,
+        }
+    }
+}
+
+// The value of an env var, and the result of running it as a command, if `env_cmd` needed that.
+// The command is run at most once per parse.
+struct EnvValue {
+    value: OsString,
+    cmd_output: OnceCell<Result<String, String>>,
 }
 
 impl ParsedEnv {
     /// Get the OsString. This is useful if you want to raise an error with context if it not valid
     /// utf8.
     pub(crate) fn get<'a>(&'a self, name: &str) -> Option<&'a OsString> {
-        self.map.get(name)
+        self.map.get(name).map(|env_value| &env_value.value)
     }
 
     /// Get the OsString as a lossy string, or "" if it's not present.
@@ -20,9 +39,33 @@ impl ParsedEnv {
     pub(crate) fn get_lossy_or_default<'a>(&'a self, name: &str) -> Cow<'a, str> {
         self.map
             .get(name)
-            .map(|os_str| os_str.to_string_lossy())
+            .map(|env_value| env_value.value.to_string_lossy())
             .unwrap_or_default()
     }
+
+    /// Iterate over the names of all env vars that are present
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(String::as_str)
+    }
+
+    /// If the env var is present, run its value as a command (only the first time this is
+    /// called), and get the output, or a description of why the command failed.
+    pub(crate) fn get_cmd_output<'a>(&'a self, name: &str) -> Option<Result<&'a str, &'a str>> {
+        let env_value = self.map.get(name)?;
+        let result = env_value.cmd_output.get_or_init(|| {
+            let cmd = env_value
+                .value
+                .to_str()
+                .ok_or_else(|| "the command is not valid UTF-8".to_owned())?;
+            run_env_cmd(cmd, self.env_cmd_timeout)
+        });
+        Some(result.as_deref().map_err(String::as_str))
+    }
+
+    /// Set the timeout for commands run due to `env_cmd`
+    pub(crate) fn set_env_cmd_timeout(&mut self, timeout: Duration) {
+        self.env_cmd_timeout = timeout;
+    }
 }
 
 /// Parse a generic thing that looks like std::env::vars_os but might be test data,
@@ -42,11 +85,18 @@ where
             .into_iter()
             .filter_map(|(into_key, into_val)| {
                 if let Ok(key) = into_key.into().into_string() {
-                    Some((key, into_val.into()))
+                    Some((
+                        key,
+                        EnvValue {
+                            value: into_val.into(),
+                            cmd_output: OnceCell::new(),
+                        },
+                    ))
                 } else {
                     None
                 }
             })
             .collect(),
+        env_cmd_timeout: DEFAULT_ENV_CMD_TIMEOUT,
     }
 }
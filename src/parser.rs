@@ -1,6 +1,12 @@
-use crate::{Error, ParseType, ParsedEnv, ProgramOption};
+use crate::{
+    program_option::render_deprecated_tag, report::ReportFormat, suggest::similar_known_env_names,
+    Error, InnerError, ParseType, ParsedEnv, ProgramOption,
+};
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use std::{collections::HashMap, ffi::OsString};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+};
 
 /// Result of parsing arguments
 #[derive(Clone)]
@@ -66,20 +72,33 @@ pub struct ParserConfig {
     pub name: &'static str,
     /// True if help flags should not be automatically generated
     pub no_help_flag: bool,
+    /// The long switch of a flag which prints the effective config and exits, if any
+    pub print_config_flag: Option<&'static str>,
+    /// The env prefix of the struct, if any
+    pub env_prefix: Option<&'static str>,
+    /// True if env vars which start with the env prefix, but are not read by any option, are errors
+    pub deny_unknown_env: bool,
 }
 
+// The clap id of the print config flag. Program option ids are rust identifiers joined by '.',
+// so this cannot collide with any of them.
+const PRINT_CONFIG_ID: &str = "@print-config";
+// The clap id of the `--help-env` flag
+const HELP_ENV_ID: &str = "@help-env";
+
 /// A parser which tries to parse args, matching them to a list of ProgramOptions.
 #[derive(Clone)]
 pub struct Parser<'a> {
-    #[allow(unused)]
     parser_config: ParserConfig,
-    #[allow(unused)]
     options: Vec<&'a ProgramOption>,
     id_to_option: HashMap<&'a str, &'a ProgramOption>,
     subcommands: Vec<Parser<'a>>,
-    #[allow(unused)]
     env: &'a ParsedEnv,
     command: Command,
+    // A copy of the command before `Command::build` was called.
+    // This is what gets nested in the parent command when this is a subcommand, because clap can
+    // only propagate global args from the parent into subcommands that have not been built yet.
+    unbuilt_command: Command,
 }
 
 impl<'a> Parser<'a> {
@@ -107,43 +126,98 @@ impl<'a> Parser<'a> {
         }
 
         let mut args = Vec::<Arg>::new();
-        let mut env_only_help_text = Vec::<String>::new();
+        // Help text of env-only options, grouped by help heading, in order of first appearance
+        let mut env_only_help_text = Vec::<(Option<&str>, String, String)>::new();
 
         for opt in options.iter() {
             match Self::make_arg(&parser_config, env, opt)? {
                 MaybeArg::Arg(arg) => {
                     args.push(arg);
                 }
-                MaybeArg::EnvOnly(text) => {
-                    env_only_help_text.push(text);
+                MaybeArg::EnvOnly(heading, short_text, long_text) => {
+                    match env_only_help_text.iter_mut().find(|(h, ..)| *h == heading) {
+                        Some((_, group_short_text, group_long_text)) => {
+                            *group_short_text += &short_text;
+                            *group_long_text += &long_text;
+                        }
+                        None => env_only_help_text.push((heading, short_text, long_text)),
+                    }
                 }
-                MaybeArg::DefaultOnly => {
-                    // We don't bother documenting these since the user can't adjust them
+                MaybeArg::DefaultOnly | MaybeArg::Hidden => {
+                    // We don't bother documenting these since the user can't adjust them, or
+                    // asked for them not to be shown
                 }
             }
         }
 
         let subcommand_vec: Vec<_> = subcommands
             .iter()
-            .map(|p| p.get_command().clone())
+            .map(|p| p.unbuilt_command.clone())
             .collect();
         command = command.args(args).subcommands(subcommand_vec);
 
         if parser_config.no_help_flag {
             command = command.disable_help_flag(true);
+        } else if !options.iter().any(|opt| {
+            opt.long_form.as_deref() == Some("help-env")
+                || opt.aliases.iter().any(|alias| alias == "help-env")
+        }) {
+            command = command.arg(
+                Arg::new(HELP_ENV_ID)
+                    .long("help-env")
+                    .help("Print help for environment variables, and exit")
+                    .action(ArgAction::SetTrue),
+            );
         }
 
-        // Make an environment variables section that goes at the end, in after_help
-        if !env_only_help_text.is_empty() {
-            let mut after_help_text = "Environment variables:\n".to_owned();
+        if let Some(print_config_flag) = parser_config.print_config_flag {
+            command = command.arg(
+                Arg::new(PRINT_CONFIG_ID)
+                    .long(print_config_flag)
+                    .help("Print the effective config, and where each value came from, and exit")
+                    .value_name("FORMAT")
+                    .value_parser(ReportFormat::POSSIBLE_VALUES)
+                    .num_args(0..=1)
+                    .require_equals(true)
+                    .default_missing_value("text")
+                    .global(true),
+            );
+        }
 
-            for var_text in env_only_help_text {
-                after_help_text += &var_text;
+        // Make environment variables sections that go at the end, in after_help. Clap can only list
+        // args under its help headings, so env-only options of each heading get their own section.
+        if !env_only_help_text.is_empty() {
+            // Options without a heading come first
+            env_only_help_text.sort_by_key(|(heading, ..)| heading.is_some());
+            let mut after_help_text = String::new();
+            let mut after_long_help_text = String::new();
+
+            for (heading, short_text, long_text) in env_only_help_text {
+                let title = match heading {
+                    Some(heading) => format!("Environment variables ({heading}):\n"),
+                    None => "Environment variables:\n".to_owned(),
+                };
+                for (text, group_text) in [
+                    (&mut after_help_text, short_text),
+                    (&mut after_long_help_text, long_text),
+                ] {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    *text += &title;
+                    *text += &group_text;
+                }
             }
 
+            // Only set the long version if it differs, because that makes clap distinguish
+            // `-h` from `--help`
+            if after_long_help_text != after_help_text {
+                command = command.after_long_help(after_long_help_text);
+            }
             command = command.after_help(after_help_text);
         }
 
+        let unbuilt_command = command.clone();
         command.build();
 
         Ok(Self {
@@ -153,27 +227,138 @@ impl<'a> Parser<'a> {
             subcommands: subcommands.to_vec(),
             env,
             command,
+            unbuilt_command,
         })
     }
 
     /// Rename a parser. (This is used by subcommands)
     pub fn rename(mut self, name: impl Into<String>) -> Self {
-        self.command = self.command.name(name.into());
+        let name = name.into();
+        self.command = self.command.name(name.clone());
+        self.unbuilt_command = self.unbuilt_command.name(name);
         self
     }
 
+    // Whether env vars which start with an env prefix, but are not read by any option, are errors.
+    // The builder may override this.
+    pub(crate) fn denies_unknown_env(&self, deny_unknown_env: Option<bool>) -> bool {
+        deny_unknown_env.unwrap_or(self.parser_config.deny_unknown_env)
+    }
+
+    // Find all env vars which start with the env prefix of this parser or of any of its
+    // subcommands, but are not read by any option, and make errors for them.
+    pub(crate) fn unknown_env_errors(&self, env: &ParsedEnv) -> Vec<InnerError> {
+        let mut env_prefixes = Vec::new();
+        self.collect_env_prefixes(&mut env_prefixes);
+        let mut known_env_names = HashSet::new();
+        self.collect_env_names(&mut known_env_names);
+
+        env.keys()
+            .filter(|name| {
+                env_prefixes.iter().any(|prefix| name.starts_with(prefix))
+                    && !known_env_names.contains(name)
+            })
+            .map(|name| {
+                InnerError::unknown_env(name, similar_known_env_names(name, &known_env_names))
+            })
+            .collect()
+    }
+
+    // Collect the env prefixes of this parser and its subcommands.
+    pub(crate) fn collect_env_prefixes(&self, out: &mut Vec<&'static str>) {
+        out.extend(self.parser_config.env_prefix);
+        for subcommand in &self.subcommands {
+            subcommand.collect_env_prefixes(out);
+        }
+    }
+
+    // Collect the names of all env vars which are read by options of this parser or its
+    // subcommands.
+    pub(crate) fn collect_env_names(&self, out: &mut HashSet<&'a str>) {
+        for opt in &self.options {
+            out.extend(opt.env_form.as_deref());
+            out.extend(opt.env_aliases.iter().map(|name| name.as_ref()));
+            out.extend(opt.env_cmd.as_deref());
+        }
+        for subcommand in &self.subcommands {
+            subcommand.collect_env_names(out);
+        }
+    }
+
     /// Get command associated to this parser
     pub fn get_command(&self) -> &Command {
         &self.command
     }
 
+    /// Get the env which is shown in help, and used to describe where values come from
+    pub(crate) fn get_env(&self) -> &'a ParsedEnv {
+        self.env
+    }
+
+    /// Get parser config associated to this parser
+    pub(crate) fn get_parser_config(&self) -> &ParserConfig {
+        &self.parser_config
+    }
+
+    /// Get program options associated to this parser
+    pub(crate) fn get_options(&self) -> &[&'a ProgramOption] {
+        &self.options
+    }
+
+    /// Get subcommand parsers associated to this parser
+    pub(crate) fn get_subcommands(&self) -> &[Parser<'a>] {
+        &self.subcommands
+    }
+
+    /// Check if the print config flag appeared in the matches, and which format was requested
+    pub(crate) fn get_print_config_format(&self, arg_matches: &ArgMatches) -> Option<ReportFormat> {
+        self.parser_config.print_config_flag?;
+        arg_matches
+            .get_one::<String>(PRINT_CONFIG_ID)
+            .map(|format| ReportFormat::from_str(format))
+    }
+
     /// Parse from raw os args (or something that looks like std::env::args_os but could be test
     /// data)
     pub(crate) fn parse<T>(&self, args_os: impl IntoIterator<Item = T>) -> Result<ArgMatches, Error>
     where
         T: Into<OsString> + Clone,
     {
-        Ok(self.command.clone().try_get_matches_from(args_os)?)
+        let args_os = args_os
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<OsString>>();
+        let matches = self
+            .command
+            .clone()
+            .try_get_matches_from(&args_os)
+            .map_err(|err| {
+                // `--help <topic>` gets an error from clap, either because it displays help, or
+                // because the help flag doesn't take a value
+                self.help_topic(&args_os).unwrap_or_else(|| err.into())
+            })?;
+        if let Some(parser) = self.find_help_env_request(&matches) {
+            return Err(Error::output(parser.render_env_help()));
+        }
+        Ok(matches)
+    }
+
+    // Find the (sub)command parser whose `--help-env` flag appeared in the matches, if any
+    fn find_help_env_request(&self, matches: &ArgMatches) -> Option<&Self> {
+        let requested = matches
+            .try_get_one::<bool>(HELP_ENV_ID)
+            .ok()
+            .flatten()
+            .copied()
+            .unwrap_or(false);
+        if requested {
+            return Some(self);
+        }
+        let (name, sub_matches) = matches.subcommand()?;
+        self.subcommands
+            .iter()
+            .find(|subcommand| subcommand.command.get_name() == name)?
+            .find_help_env_request(sub_matches)
     }
 
     // Turn a ProgramOption into an arg. Or, if it should not be set via CLI at all, just generate
@@ -210,16 +395,25 @@ impl<'a> Parser<'a> {
         _parser_config: &ParserConfig,
         env: &ParsedEnv,
         option: &'a ProgramOption,
-    ) -> Result<MaybeArg, Error> {
+    ) -> Result<MaybeArg<'a>, Error> {
         if option.short_form.is_none() && option.long_form.is_none() {
             // If there is no short form and no long form, clap is going to make it a positional
             // argument, but we don't want that and there's no way to disable the behavior.
             // Clap also isn't supposed to read a value for this, so the solution is don't create an
             // arg at all, and just add documentation about it ourselves.
-            return if option.env_form.is_some() {
-                let mut buf = String::new();
-                option.print(&mut buf, Some(env))?;
-                Ok(MaybeArg::EnvOnly(buf))
+            return if option.env_form.is_some() || option.env_cmd.is_some() {
+                if option.hidden {
+                    return Ok(MaybeArg::Hidden);
+                }
+                let mut short_help = String::new();
+                option.print_help(&mut short_help, Some(env), false)?;
+                let mut long_help = String::new();
+                option.print_help(&mut long_help, Some(env), true)?;
+                Ok(MaybeArg::EnvOnly(
+                    option.help_heading.as_deref(),
+                    short_help,
+                    long_help,
+                ))
             } else if option.default_value.is_some() {
                 Ok(MaybeArg::DefaultOnly)
             } else {
@@ -227,10 +421,10 @@ impl<'a> Parser<'a> {
             };
         }
 
-        if option.is_secret() {
+        if option.is_secret() && !option.secret_allow_cli {
             let mut buf = String::new();
             option.print(&mut buf, Some(env)).unwrap();
-            panic!("The secret feature is not compatible with arguments that can be read from CLI args. See documentation for more about this.\n\n{buf}")
+            panic!("The secret feature is not compatible with arguments that can be read from CLI args, unless `secret(allow_cli)` is used. See documentation for more about this.\n\n{buf}")
         }
 
         let mut arg = Arg::new(option.id.clone().into_owned());
@@ -261,18 +455,30 @@ impl<'a> Parser<'a> {
         }
 
         // Set the help text if either description or env_form is present, in that order
+        // The current value of the env is omitted if the option is a secret.
+        let render_env = |name: &str| {
+            if !option.shows_env_value() {
+                format!("\n[env {name}]")
+            } else {
+                let cur_val = env.get_lossy_or_default(name);
+                format!("\n[env {name}={cur_val}]")
+            }
+        };
         let mut help_text = option
             .env_form
             .as_deref()
-            .map(|env_form| {
-                let cur_val = env.get_lossy_or_default(env_form);
-                format!("\n[env {env_form}={cur_val}]")
-            })
+            .map(render_env)
             .unwrap_or_default();
         // Append any env aliases to the help text
         for env_alias in option.env_aliases.iter() {
-            let cur_val = env.get_lossy_or_default(env_alias);
-            help_text += &format!("\n[env {env_alias}={cur_val}]");
+            help_text += &render_env(env_alias);
+            if option.is_deprecated_env_alias(env_alias) {
+                help_text += " [deprecated]";
+            }
+        }
+        // Append any env command to the help text. We never run the command just for help.
+        if let Some(env_cmd) = option.env_cmd.as_deref() {
+            help_text += &format!("\n[env command {env_cmd}]");
         }
         // Append any default value to the help text
         if let Some(def) = option.default_value.as_ref() {
@@ -282,10 +488,28 @@ impl<'a> Parser<'a> {
         if option.is_secret() {
             help_text += "\n[secret]";
         }
-        // Prepend the user's description to the help_text if present
-        help_text.insert_str(0, option.description.as_deref().unwrap_or_default());
-        if !help_text.is_empty() {
-            arg = arg.help(help_text);
+        // Append deprecated tag if the option is deprecated
+        if let Some(note) = option.deprecated.as_deref() {
+            help_text += "\n";
+            help_text += &render_deprecated_tag(note);
+        }
+        // Prepend the user's description to the help_text if present. Short help (`-h`) only
+        // shows the first paragraph of it.
+        if option.has_long_description() {
+            let short_description = option.short_description().unwrap_or_default();
+            let long_description = option.description.as_deref().unwrap_or_default();
+            arg = arg
+                .help(format!("{short_description}{help_text}"))
+                .long_help(format!("{long_description}{help_text}"));
+        } else {
+            help_text.insert_str(0, option.description.as_deref().unwrap_or_default());
+            if !help_text.is_empty() {
+                arg = arg.help(help_text);
+            }
+        }
+
+        if option.hidden {
+            arg = arg.hide(true);
         }
 
         // Set the ArgAction of the arg based on its parse type
@@ -308,23 +532,11 @@ impl<'a> Parser<'a> {
             }
         };
 
-        // Set the help heading.
-        // If there is a short-form or long-form set, then it is listed as a "flag" or "option" as
-        // is traditional. If there is only env set, then it is listed as "environment
-        // variable". TODO: Needs more work
-        /*
-        if option.short_form.is_some() || option.long_form.is_some() {
-            match option.parse_type {
-                ParseType::Flag => {
-                    arg = arg.help_heading("Flags");
-                }
-                ParseType::Parameter | ParseType::Repeat => {
-                    arg = arg.help_heading("Options");
-                }
-            }
-        } else {
-            arg = arg.help_heading("Environment Variables");
-        }*/
+        // Set the help heading, if the option was flattened with one. Options without a heading are
+        // listed under "Options", as clap does by default.
+        if let Some(heading) = option.help_heading.as_deref() {
+            arg = arg.help_heading(heading.to_owned());
+        }
 
         Ok(MaybeArg::Arg(arg))
     }
@@ -333,15 +545,57 @@ impl<'a> Parser<'a> {
     // it's here and marked public for testing
     #[doc(hidden)]
     pub fn render_clap_help(&self) -> String {
-        let mut command = self.command.clone();
-        command.set_bin_name("."); // Override the crate name stuff for tests
-        command.render_help().to_string()
+        // Override the crate name stuff and the terminal width for tests
+        self.render_help_with(".", &[], Some(100), false)
+            .expect("no subcommands were requested")
+    }
+
+    // Same as `render_clap_help`, but renders the long help (`--help`) rather than short help (`-h`)
+    #[doc(hidden)]
+    pub fn render_clap_long_help(&self) -> String {
+        self.render_help_with(".", &[], Some(100), true)
+            .expect("no subcommands were requested")
+    }
+
+    // Render the short (`-h`) or long (`--help`) help of this parser, or of one of its (nested)
+    // subcommands, with a fixed bin name and terminal width, so that the output doesn't depend on the binary or the terminal.
+    // Returns None if the subcommand path doesn't exist.
+    pub(crate) fn render_help_with(
+        &self,
+        bin_name: &str,
+        subcommand_path: &[&str],
+        width: Option<usize>,
+        long_help: bool,
+    ) -> Option<String> {
+        let mut parser = self;
+        let mut bin_name = bin_name.to_owned();
+        for name in subcommand_path {
+            parser = parser
+                .subcommands
+                .iter()
+                .find(|subcommand| subcommand.command.get_name() == *name)?;
+            bin_name = format!("{bin_name} {name}");
+        }
+
+        let mut command = parser.command.clone();
+        if let Some(width) = width {
+            command = command.term_width(width);
+        }
+        command.set_bin_name(bin_name);
+        let help = if long_help {
+            command.render_long_help()
+        } else {
+            command.render_help()
+        };
+        Some(help.to_string())
     }
 }
 
 #[allow(clippy::large_enum_variant)]
-enum MaybeArg {
+enum MaybeArg<'a> {
     Arg(Arg),
-    EnvOnly(String),
+    // Help heading, short help and long help of an option which can only be read from env
+    EnvOnly(Option<&'a str>, String, String),
+    Hidden,
     DefaultOnly,
 }
@@ -49,6 +49,8 @@ pub struct ProgramOption {
     pub env_form: Option<CowStr>,
     /// Any env aliases
     pub env_aliases: Vec<CowStr>,
+    /// An env var which may name a command, whose output is used as the value, if any
+    pub env_cmd: Option<CowStr>,
     /// The default-value, if any
     pub default_value: Option<CowStr>,
     /// Whether this option is considered required to appear. Affects help generation & semantics
@@ -59,6 +61,23 @@ pub struct ProgramOption {
     pub allow_hyphen_values: bool,
     /// If set, then the user has specified that this is (or is not) a secret value, explicitly.
     pub secret: Option<bool>,
+    /// If set, this secret is allowed to have a short or long switch, and be read from CLI args.
+    pub secret_allow_cli: bool,
+    /// If set, a warning is printed when this secret is actually read from CLI args.
+    pub secret_warn_cli: bool,
+    /// If set, this option is deprecated, and using it produces a warning with this note.
+    /// The note may be empty.
+    pub deprecated: Option<CowStr>,
+    /// Env aliases which are deprecated, and produce a warning when used.
+    /// These also appear in `env_aliases`.
+    pub deprecated_env_aliases: Vec<CowStr>,
+    /// The heading that this option is listed under in help, if any. This is set when flattening
+    /// with the `help_heading` attribute.
+    pub help_heading: Option<CowStr>,
+    /// If set, this option is not shown in help.
+    pub hidden: bool,
+    /// If set, the current value of the env var is not shown in help.
+    pub hide_env_value: bool,
 }
 
 impl ProgramOption {
@@ -82,10 +101,18 @@ impl ProgramOption {
             mut aliases,
             mut env_form,
             mut env_aliases,
+            mut env_cmd,
             default_value,
             is_required,
             allow_hyphen_values,
             secret,
+            secret_allow_cli,
+            secret_warn_cli,
+            deprecated,
+            mut deprecated_env_aliases,
+            help_heading,
+            hidden,
+            hide_env_value,
         } = self;
 
         id.to_mut().insert_str(0, id_prefix);
@@ -105,11 +132,19 @@ impl ProgramOption {
                 env_form.to_mut().insert_str(0, env_prefix);
             }
         }
-        for env_alias in env_aliases.iter_mut() {
+        for env_alias in env_aliases
+            .iter_mut()
+            .chain(deprecated_env_aliases.iter_mut())
+        {
             if !env_prefix.is_empty() {
                 env_alias.to_mut().insert_str(0, env_prefix);
             }
         }
+        if let Some(env_cmd) = env_cmd.as_mut() {
+            if !env_prefix.is_empty() {
+                env_cmd.to_mut().insert_str(0, env_prefix);
+            }
+        }
 
         if let Some(desc) = description.as_mut() {
             // Description prefix requires a little more subtlety to try to ensure that it is going
@@ -147,13 +182,32 @@ impl ProgramOption {
             aliases,
             env_form,
             env_aliases,
+            env_cmd,
             default_value,
             is_required,
             allow_hyphen_values,
             secret,
+            secret_allow_cli,
+            secret_warn_cli,
+            deprecated,
+            deprecated_env_aliases,
+            help_heading,
+            hidden,
+            hide_env_value,
         }
     }
 
+    /// Apply a help heading to a program option. This is done when flattening with the
+    /// `help_heading` attribute. If the option already has a heading from a nested flatten, the
+    /// headings are combined, e.g. `Database / Replica`.
+    pub fn apply_help_heading(mut self, heading: &str) -> Self {
+        self.help_heading = Some(match self.help_heading.take() {
+            Some(inner) => format!("{heading} / {inner}").into(),
+            None => heading.to_owned().into(),
+        });
+        self
+    }
+
     /// Drop our short form if it belongs to a list of forms to skip.
     /// This is applied when flattening if skip_short attribute is used.
     #[inline]
@@ -167,6 +221,45 @@ impl ProgramOption {
         self
     }
 
+    /// Hide this option from help. This is applied when flattening with the `hide` attribute.
+    #[inline]
+    pub fn make_hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Don't show the current value of the env var of this option in help. This is applied when
+    /// flattening with the `hide_env_value` attribute.
+    #[inline]
+    pub fn make_env_value_hidden(mut self) -> Self {
+        self.hide_env_value = true;
+        self
+    }
+
+    /// The first paragraph of the description, which is all that short help (`-h`) shows.
+    pub fn short_description(&self) -> Option<&str> {
+        let desc = self.description.as_deref()?;
+        Some(
+            desc.split_once("\n\n")
+                .map_or(desc, |(first, _)| first.trim_end()),
+        )
+    }
+
+    /// True if the description has more than one paragraph, so that short help and long help
+    /// differ.
+    pub fn has_long_description(&self) -> bool {
+        self.description
+            .as_deref()
+            .is_some_and(|desc| desc.contains("\n\n"))
+    }
+
+    /// Whether help may show the current value of the env var of this option. This is never the
+    /// case for secrets.
+    #[inline]
+    pub fn shows_env_value(&self) -> bool {
+        !self.is_secret() && !self.hide_env_value
+    }
+
     /// Make this an "optional" option if it was previously required
     #[inline]
     pub fn make_optional(mut self) -> Self {
@@ -181,6 +274,66 @@ impl ProgramOption {
         self.secret.unwrap_or(false)
     }
 
+    /// Check if an env alias of this option is deprecated
+    #[inline]
+    pub fn is_deprecated_env_alias(&self, name: &str) -> bool {
+        self.deprecated_env_aliases
+            .iter()
+            .any(|alias| alias == name)
+    }
+
+    /// Check if the id or env forms of this option look like they name a secret, e.g.
+    /// `db.password` or `API_TOKEN`. Returns the word that matched, if any.
+    ///
+    /// Flags are never considered to look like secrets.
+    pub fn looks_like_secret(&self) -> Option<&'static str> {
+        const SECRET_WORDS: &[&str] = &[
+            "password",
+            "passwd",
+            "passphrase",
+            "secret",
+            "token",
+            "key",
+            "apikey",
+            "credential",
+        ];
+
+        if self.parse_type == ParseType::Flag {
+            return None;
+        }
+
+        let names = core::iter::once(self.id.as_ref())
+            .chain(self.env_form.as_deref())
+            .chain(self.env_aliases.iter().map(AsRef::as_ref));
+        for name in names {
+            for word in name.split(['.', '_', '-']) {
+                let word = word.to_ascii_lowercase();
+                let word = word.strip_suffix('s').unwrap_or(&word);
+                if let Some(found) = SECRET_WORDS.iter().find(|w| **w == word) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    /// Mark this option as a secret if it looks like one, and it was not explicitly marked as
+    /// secret or not secret. This is applied when `secret_heuristics` attribute is used.
+    ///
+    /// Since the user didn't choose to restrict it to env, an option marked this way is still
+    /// allowed to be read from CLI args, but a warning is printed if that happens.
+    #[inline]
+    pub fn apply_secret_heuristics(mut self) -> Self {
+        if self.secret.is_none() && self.looks_like_secret().is_some() {
+            self.secret = Some(true);
+            if self.short_form.is_some() || self.long_form.is_some() {
+                self.secret_allow_cli = true;
+                self.secret_warn_cli = true;
+            }
+        }
+        self
+    }
+
     // Desired output is like:
     //  -x, --xyz <XYZ>
     //          This is the description.
@@ -192,6 +345,17 @@ impl ProgramOption {
         &self,
         stream: &mut impl std::fmt::Write,
         env: Option<&ParsedEnv>,
+    ) -> Result<(), std::fmt::Error> {
+        self.print_help(stream, env, true)
+    }
+
+    /// Like `print`, but if `long_help` is false, only the first paragraph of the description is
+    /// printed, as in short help (`-h`).
+    pub fn print_help(
+        &self,
+        stream: &mut impl std::fmt::Write,
+        env: Option<&ParsedEnv>,
+        long_help: bool,
     ) -> Result<(), std::fmt::Error> {
         // Deal with spacing so that when short is 1 char, all the short options are aligned and
         // indented, and all the long options are too.
@@ -210,11 +374,16 @@ impl ProgramOption {
             write!(stream, "<{}>", self.id)?;
         }
         writeln!(stream)?;
-        if let Some(desc) = self.description.as_ref() {
+        let desc = if long_help {
+            self.description.as_deref()
+        } else {
+            self.short_description()
+        };
+        if let Some(desc) = desc {
             writeln!(stream, "          {}", desc.replace('\n', "\n          "))?;
         }
         if let Some(name) = self.env_form.as_deref() {
-            if let Some(env) = env.filter(|_| !self.is_secret()) {
+            if let Some(env) = env.filter(|_| self.shows_env_value()) {
                 let cur_val = env.get_lossy_or_default(name);
                 writeln!(stream, "          [env: {name}={cur_val}]")?;
             } else {
@@ -223,20 +392,41 @@ impl ProgramOption {
         }
 
         for name in &self.env_aliases {
-            if let Some(env) = env.filter(|_| !self.is_secret()) {
+            let deprecated = if self.is_deprecated_env_alias(name) {
+                " [deprecated]"
+            } else {
+                ""
+            };
+            if let Some(env) = env.filter(|_| self.shows_env_value()) {
                 let cur_val = env.get_lossy_or_default(name);
-                writeln!(stream, "          [env: {name}={cur_val}]")?;
+                writeln!(stream, "          [env: {name}={cur_val}]{deprecated}")?;
             } else {
-                writeln!(stream, "          [env: {name}]")?;
+                writeln!(stream, "          [env: {name}]{deprecated}")?;
             }
         }
 
+        if let Some(name) = self.env_cmd.as_deref() {
+            writeln!(stream, "          [env command: {name}]")?;
+        }
+
         if let Some(def) = self.default_value.as_ref() {
             writeln!(stream, "          [default: {def}]")?;
         }
         if self.is_secret() {
             writeln!(stream, "          [secret]")?;
         }
+        if let Some(note) = self.deprecated.as_deref() {
+            writeln!(stream, "          {}", render_deprecated_tag(note))?;
+        }
         Ok(())
     }
 }
+
+// The tag shown in help for a deprecated option, e.g. `[deprecated: use --foo instead]`
+pub(crate) fn render_deprecated_tag(note: &str) -> String {
+    if note.is_empty() {
+        "[deprecated]".to_owned()
+    } else {
+        format!("[deprecated: {note}]")
+    }
+}
@@ -0,0 +1,154 @@
+//! Reloading a [`Conf`] structure while a program is running.
+//!
+//! A [`ConfReloader`] remembers the args and env that the program started with, and a function
+//! which runs the parse, usually after loading a document from a file. It can then re-run the parse
+//! on demand, or whenever one of the files it watches is modified.
+//!
+//! The live config is only replaced if the new parse succeeds, so a typo in a config file doesn't
+//! take down a running service.
+//!
+//! File watching is polling-based: call [`ConfReloader::poll`] periodically, for instance from a
+//! background thread or a timer task. To reload on a signal such as `SIGHUP`, call
+//! [`ConfReloader::reload`] when your signal handling crate of choice reports it.
+
+use crate::{Conf, ConfBuilder, Error};
+use std::{ffi::OsString, path::PathBuf, sync::Arc, time::SystemTime};
+
+/// Re-runs the parse of a [`Conf`] structure, keeping the last config that parsed successfully.
+///
+/// Example:
+///
+/// ```ignore
+/// let mut reloader = ConfReloader::from_process(|builder: ConfBuilder<Config>| {
+///     let text = std::fs::read_to_string("config.toml")
+///         .map_err(|err| conf::Error::document_load("config.toml", err))?;
+///     let doc: toml::Value = toml::from_str(&text)
+///         .map_err(|err| conf::Error::document_load("config.toml", err))?;
+///     builder.doc("config.toml", doc).try_parse()
+/// })?
+/// .watch("config.toml");
+///
+/// loop {
+///     std::thread::sleep(Duration::from_secs(5));
+///     match reloader.poll() {
+///         Some(Ok(config)) => apply(&config),
+///         Some(Err(err)) => eprintln!("keeping previous config: {err}"),
+///         None => {}
+///     }
+/// }
+/// ```
+pub struct ConfReloader<S: Conf> {
+    args: Vec<OsString>,
+    env: Vec<(OsString, OsString)>,
+    parse_fn: Box<dyn Fn(ConfBuilder<S>) -> Result<S, Error> + Send>,
+    watched: Vec<WatchedFile>,
+    current: Arc<S>,
+}
+
+impl<S: Conf> ConfReloader<S> {
+    /// Create a reloader from args and an env snapshot, and run the initial parse.
+    ///
+    /// `parse_fn` is given a builder which already has these args and env, and should finish the
+    /// parse, e.g. by loading a document and calling `.doc(...).try_parse()`. It is called again
+    /// on every reload, so it should load any files again.
+    ///
+    /// Returns an error if the initial parse fails.
+    pub fn new<K, V>(
+        args: impl IntoIterator<Item: Into<OsString>>,
+        env: impl IntoIterator<Item = (K, V)>,
+        parse_fn: impl Fn(ConfBuilder<S>) -> Result<S, Error> + Send + 'static,
+    ) -> Result<Self, Error>
+    where
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        let args = args.into_iter().map(Into::into).collect::<Vec<_>>();
+        let env = env
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect::<Vec<_>>();
+        let parse_fn = Box::new(parse_fn);
+        let current = Arc::new(Self::run(&args, &env, &*parse_fn)?);
+        Ok(Self {
+            args,
+            env,
+            parse_fn,
+            watched: Vec::new(),
+            current,
+        })
+    }
+
+    /// Create a reloader from a snapshot of `std::env::args_os` and `std::env::vars_os`, and run
+    /// the initial parse. See [`ConfReloader::new`].
+    pub fn from_process(
+        parse_fn: impl Fn(ConfBuilder<S>) -> Result<S, Error> + Send + 'static,
+    ) -> Result<Self, Error> {
+        Self::new(std::env::args_os(), std::env::vars_os(), parse_fn)
+    }
+
+    /// Watch a file, so that [`ConfReloader::poll`] reloads when it is modified.
+    ///
+    /// A file counts as modified when its modification time or size changes, or when it is
+    /// created or removed.
+    pub fn watch(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let fingerprint = fingerprint(&path);
+        self.watched.push(WatchedFile { path, fingerprint });
+        self
+    }
+
+    /// The last config which parsed successfully.
+    pub fn current(&self) -> Arc<S> {
+        self.current.clone()
+    }
+
+    /// Re-run the parse now.
+    ///
+    /// If it succeeds, the new config becomes the current config and is returned. Otherwise the
+    /// current config is unchanged, and the error is returned.
+    pub fn reload(&mut self) -> Result<Arc<S>, Error> {
+        let new = Arc::new(Self::run(&self.args, &self.env, &*self.parse_fn)?);
+        self.current = new.clone();
+        Ok(new)
+    }
+
+    /// Check whether any watched file was modified since the last check, and reload if so.
+    ///
+    /// Returns `None` if nothing was modified, and otherwise the result of [`ConfReloader::reload`].
+    /// If the reload fails, it is not attempted again until a file is modified again.
+    pub fn poll(&mut self) -> Option<Result<Arc<S>, Error>> {
+        let mut modified = false;
+        for file in self.watched.iter_mut() {
+            let fingerprint = fingerprint(&file.path);
+            if fingerprint != file.fingerprint {
+                file.fingerprint = fingerprint;
+                modified = true;
+            }
+        }
+        modified.then(|| self.reload())
+    }
+
+    fn run(
+        args: &[OsString],
+        env: &[(OsString, OsString)],
+        parse_fn: &dyn Fn(ConfBuilder<S>) -> Result<S, Error>,
+    ) -> Result<S, Error> {
+        let builder = S::conf_builder()
+            .args(args.iter().cloned())
+            .env(env.iter().cloned());
+        parse_fn(builder)
+    }
+}
+
+// A file being watched, and what it looked like when it was last checked
+struct WatchedFile {
+    path: PathBuf,
+    fingerprint: Option<(Option<SystemTime>, u64)>,
+}
+
+// The modification time and size of a file, or None if it doesn't exist.
+// Some file systems have coarse modification times, so the size is also checked.
+fn fingerprint(path: &PathBuf) -> Option<(Option<SystemTime>, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
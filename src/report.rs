@@ -0,0 +1,714 @@
+//! Types describing the effective configuration that a [`Conf`](crate::Conf) structure was parsed
+//! from, and where each value came from.
+//!
+//! See [`Conf::parse_with_report`](crate::Conf::parse_with_report),
+//! [`Conf::parse_with_sources`](crate::Conf::parse_with_sources), and the
+//! `#[conf(print_config_flag)]` attribute.
+//!
+//! Like the types in [`introspect`](mod@crate::introspect), these are part of the public API, and new
+//! fields may be added in the future, so they are `#[non_exhaustive]`.
+
+use crate::{
+    error::render_help_switch, json, ConfValueSource, InnerError, Parser, ProgramOption, Secret,
+    Warning,
+};
+use clap::ArgMatches;
+use std::{cell::RefCell, collections::HashMap, fmt};
+use zeroize::Zeroize;
+
+/// Where the value of a program option came from.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ValueSource {
+    /// The value was given in CLI args
+    Args,
+    /// The value was read from this env var (which may be an env alias)
+    Env(String),
+    /// The value is the output of a command, which was read from this env var
+    EnvCmd(String),
+    /// The value was read from the document with this name
+    Document(String),
+    /// The value is the default value
+    Default,
+}
+
+impl ValueSource {
+    /// True if the value was given in CLI args
+    pub fn is_args(&self) -> bool {
+        matches!(self, Self::Args)
+    }
+
+    /// True if the value is the default value
+    pub fn is_default(&self) -> bool {
+        matches!(self, Self::Default)
+    }
+
+    /// The name of the env var that the value was read from, if it was read from env
+    pub fn env_name(&self) -> Option<&str> {
+        match self {
+            Self::Env(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    // Write this as a JSON object such as `{"type": "env", "name": "DB_URL"}`
+    pub(crate) fn write_json(&self, buf: &mut String) {
+        let mut obj = json::ObjectWriter::new(buf);
+        match self {
+            Self::Args => obj.str("type", "args"),
+            Self::Env(name) => {
+                obj.str("type", "env");
+                obj.str("name", name);
+            }
+            Self::EnvCmd(name) => {
+                obj.str("type", "env_cmd");
+                obj.str("name", name);
+            }
+            Self::Document(name) => {
+                obj.str("type", "document");
+                obj.str("name", name);
+            }
+            Self::Default => obj.str("type", "default"),
+        }
+        obj.end();
+    }
+}
+
+impl From<ConfValueSource<&str>> for ValueSource {
+    fn from(src: ConfValueSource<&str>) -> Self {
+        match src {
+            ConfValueSource::Args => Self::Args,
+            ConfValueSource::Env(name) => Self::Env(name.to_owned()),
+            ConfValueSource::EnvCmd(name) => Self::EnvCmd(name.to_owned()),
+            ConfValueSource::Document(name) => Self::Document(name.to_owned()),
+            ConfValueSource::Default => Self::Default,
+        }
+    }
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Args => write!(f, "args"),
+            Self::Env(name) => write!(f, "env {name}"),
+            Self::EnvCmd(name) => write!(f, "env command {name}"),
+            Self::Document(name) => write!(f, "document {name}"),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// The effective value of a program option, as it appeared before the value parser was run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReportValue {
+    /// The option has no value. It is optional and was not given, or it is part of an optional
+    /// flattened group which was not enabled.
+    Unset,
+    /// The value of a flag
+    Bool(bool),
+    /// The value of a parameter
+    String(String),
+    /// The values of a repeat parameter
+    List(Vec<String>),
+    /// The option is a secret, so the value is not reported
+    Redacted,
+    /// The value was deserialized directly from a document, so there is no string form of it
+    Opaque,
+}
+
+impl ReportValue {
+    // Make a list value from rendered values, for Conf::diff. If any value could not be
+    // rendered, the whole list is opaque.
+    #[doc(hidden)]
+    pub fn list(vals: impl Iterator<Item = ReportValue>) -> Self {
+        let mut strs = Vec::new();
+        for val in vals {
+            match val {
+                Self::String(s) => strs.push(s),
+                _ => return Self::Opaque,
+            }
+        }
+        Self::List(strs)
+    }
+}
+
+impl fmt::Display for ReportValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unset => write!(f, "<unset>"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::String(s) => write!(f, "{s:?}"),
+            Self::List(vals) => write!(f, "{vals:?}"),
+            Self::Redacted => write!(f, "[REDACTED]"),
+            Self::Opaque => write!(f, "<opaque>"),
+        }
+    }
+}
+
+/// The effective value of a single program option, and its source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ReportEntry {
+    /// The id path of the program option, e.g. `"db.url"`
+    pub id: String,
+    /// The effective value
+    pub value: ReportValue,
+    /// Where the value came from, if it has a value
+    pub source: Option<ValueSource>,
+}
+
+/// A report of the effective configuration of a command, and of the subcommand that was selected,
+/// if any.
+///
+/// The `Display` impl produces human-readable text, one line per option, and
+/// [`ConfigReport::to_json`] produces JSON. Secret values are always redacted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ConfigReport {
+    /// The name of the command
+    pub command: String,
+    /// An entry for each program option of the command, in declaration order
+    pub entries: Vec<ReportEntry>,
+    /// The report for the subcommand that was selected, if any
+    pub subcommand: Option<Box<ConfigReport>>,
+}
+
+impl ConfigReport {
+    // Build a report from the parser, the clap matches, and the values recorded by ConfContext
+    // during the parse.
+    pub(crate) fn new(parser: &Parser, arg_matches: &ArgMatches, records: &[ValueRecord]) -> Self {
+        Self::new_helper(parser, arg_matches, records, "")
+    }
+
+    fn new_helper(
+        parser: &Parser,
+        arg_matches: &ArgMatches,
+        records: &[ValueRecord],
+        command_path: &str,
+    ) -> Self {
+        let entries = parser
+            .get_options()
+            .iter()
+            .map(|opt| {
+                let record = records
+                    .iter()
+                    .find(|rec| rec.command_path == command_path && rec.id == opt.id);
+                ReportEntry {
+                    id: opt.id.clone().into_owned(),
+                    value: record
+                        .map(|rec| rec.value.clone())
+                        .unwrap_or(ReportValue::Unset),
+                    source: record.map(|rec| rec.source.clone()),
+                }
+            })
+            .collect();
+
+        let subcommand = arg_matches.subcommand().and_then(|(name, sub_matches)| {
+            let sub_parser = parser
+                .get_subcommands()
+                .iter()
+                .find(|sub_parser| sub_parser.get_command().get_name() == name)?;
+            let sub_path = ValueRecord::subcommand_path(command_path, name);
+            Some(Box::new(Self::new_helper(
+                sub_parser,
+                sub_matches,
+                records,
+                &sub_path,
+            )))
+        });
+
+        Self {
+            command: parser.get_command().get_name().to_owned(),
+            entries,
+            subcommand,
+        }
+    }
+
+    /// Find the entry for a program option of this command by its id, e.g. `"db.url"`
+    pub fn get(&self, id: &str) -> Option<&ReportEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Render the report as a JSON object.
+    ///
+    /// The object has keys `"command"`, `"options"` and `"subcommand"`. Each option has an `"id"`,
+    /// a `"value"` and a `"source"`. Values which are unset, or have no string form, are `null`.
+    /// A source is an object such as `{"type": "env", "name": "DB_URL"}`, or `null` if the
+    /// option has no value.
+    pub fn to_json(&self) -> String {
+        let mut buf = String::new();
+        self.write_json(&mut buf);
+        buf
+    }
+
+    fn write_json(&self, buf: &mut String) {
+        let mut obj = json::ObjectWriter::new(buf);
+        obj.str("command", &self.command);
+
+        let options = obj.key("options");
+        options.push('[');
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                options.push(',');
+            }
+            let mut entry_obj = json::ObjectWriter::new(options);
+            entry_obj.str("id", &entry.id);
+            let value = entry_obj.key("value");
+            match &entry.value {
+                ReportValue::Unset | ReportValue::Opaque => value.push_str("null"),
+                ReportValue::Bool(b) => value.push_str(if *b { "true" } else { "false" }),
+                ReportValue::String(s) => json::write_str(value, s),
+                ReportValue::List(vals) => {
+                    json::write_str_array(value, vals.iter().map(String::as_str))
+                }
+                ReportValue::Redacted => json::write_str(value, "[REDACTED]"),
+            }
+            let source = entry_obj.key("source");
+            match &entry.source {
+                None => source.push_str("null"),
+                Some(src) => src.write_json(source),
+            }
+            entry_obj.end();
+        }
+        options.push(']');
+
+        let subcommand = obj.key("subcommand");
+        match &self.subcommand {
+            Some(sub) => sub.write_json(subcommand),
+            None => subcommand.push_str("null"),
+        }
+        obj.end();
+    }
+
+    fn fmt_helper(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        for entry in self.entries.iter() {
+            write!(f, "{:indent$}{} = {}", "", entry.id, entry.value)?;
+            if let Some(source) = entry.source.as_ref() {
+                write!(f, " ({source})")?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(sub) = self.subcommand.as_ref() {
+            writeln!(f, "{:indent$}{}:", "", sub.command)?;
+            sub.fmt_helper(f, indent + 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_helper(f, 0)
+    }
+}
+
+/// The source of the value of each program option of a command, and of the subcommand that was
+/// selected, if any.
+///
+/// This is like a [`ConfigReport`] without the values, and is meant for programs that need to
+/// make decisions based on where a value came from, for instance, refusing to start in production
+/// if a secret key has its default value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Sources {
+    command: String,
+    sources: HashMap<String, ValueSource>,
+    subcommand: Option<Box<Sources>>,
+}
+
+impl Sources {
+    /// The source of the value of a program option, by its id path, e.g. `"db.url"`.
+    ///
+    /// Returns `None` if the option has no value (or there is no such option).
+    pub fn get(&self, id: &str) -> Option<&ValueSource> {
+        self.sources.get(id)
+    }
+
+    /// Iterate over the id paths and sources of all options which have a value, in no
+    /// particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ValueSource)> {
+        self.sources.iter().map(|(id, src)| (id.as_str(), src))
+    }
+
+    /// The name of the command
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// The sources for the subcommand that was selected, if any
+    pub fn subcommand(&self) -> Option<&Sources> {
+        self.subcommand.as_deref()
+    }
+}
+
+impl From<&ConfigReport> for Sources {
+    fn from(report: &ConfigReport) -> Self {
+        Self {
+            command: report.command.clone(),
+            sources: report
+                .entries
+                .iter()
+                .filter_map(|entry| Some((entry.id.clone(), entry.source.clone()?)))
+                .collect(),
+            subcommand: report
+                .subcommand
+                .as_deref()
+                .map(|sub| Box::new(Self::from(sub))),
+        }
+    }
+}
+
+/// A program option whose value differs between two instances of a [`Conf`](crate::Conf)
+/// structure. See [`Conf::diff`](crate::Conf::diff).
+///
+/// When the selected subcommand differs, there is also a change whose `id` is the name of the
+/// `#[conf(subcommands)]` field, and whose values are the names of the subcommands.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ConfChange {
+    /// Names of the subcommands leading to the command which has this option, separated by
+    /// spaces. This is empty for options of the top-level command.
+    pub command: String,
+    /// The id path of the program option, e.g. `"db.url"`
+    pub id: String,
+    /// The switch of the program option, e.g. `"--db-url"`, if it has one
+    pub switch: Option<String>,
+    /// The env var of the program option, if it has one
+    pub env: Option<String>,
+    /// The value in the first instance. This is [`ReportValue::Redacted`] if the option is secret.
+    pub old: ReportValue,
+    /// The value in the second instance. This is [`ReportValue::Redacted`] if the option is secret.
+    pub new: ReportValue,
+}
+
+impl fmt::Display for ConfChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.command.is_empty() {
+            write!(f, "{} ", self.command)?;
+        }
+        write!(f, "{}: {} -> {}", self.id, self.old, self.new)
+    }
+}
+
+impl ConfChange {
+    // Push a change for a program option, if the old and new values differ.
+    // The values are redacted here if the option is secret, so they can be compared first.
+    // If the values can't be compared from their rendered forms (secrets), `changed` says
+    // whether they differ.
+    #[doc(hidden)]
+    pub fn push_option(
+        changes: &mut Vec<ConfChange>,
+        program_options: &[ProgramOption],
+        id: &str,
+        old: ReportValue,
+        new: ReportValue,
+        changed: Option<bool>,
+    ) {
+        if !changed.unwrap_or(old != new) {
+            return;
+        }
+        let redact = |val: ReportValue| match val {
+            ReportValue::Unset => ReportValue::Unset,
+            _ => ReportValue::Redacted,
+        };
+        let mut change = Self::new(program_options, id.to_owned(), old, new);
+        if program_options
+            .iter()
+            .any(|opt| opt.id == id && opt.is_secret())
+        {
+            change.old = redact(change.old);
+            change.new = redact(change.new);
+        }
+        changes.push(change);
+    }
+
+    // Push the changes of a flattened structure, adding the id prefix of the flattened field,
+    // and taking the switch and env from the program options of the outer structure, which have
+    // the prefixes applied.
+    #[doc(hidden)]
+    pub fn push_flattened(
+        changes: &mut Vec<ConfChange>,
+        program_options: &[ProgramOption],
+        id_prefix: &str,
+        inner: Vec<ConfChange>,
+    ) {
+        changes.extend(inner.into_iter().map(|change| {
+            Self::new(
+                program_options,
+                format!("{id_prefix}{}", change.id),
+                change.old,
+                change.new,
+            )
+        }));
+    }
+
+    fn new(
+        program_options: &[ProgramOption],
+        id: String,
+        old: ReportValue,
+        new: ReportValue,
+    ) -> Self {
+        let opt = program_options.iter().find(|opt| opt.id == id);
+        Self {
+            command: String::new(),
+            switch: opt.and_then(render_help_switch),
+            env: opt.and_then(|opt| opt.env_form.as_deref().map(str::to_owned)),
+            id,
+            old,
+            new,
+        }
+    }
+
+    // Push the changes of the selected subcommand, prefixing their command paths with its name.
+    #[doc(hidden)]
+    pub fn push_subcommand(
+        changes: &mut Vec<ConfChange>,
+        command_name: &str,
+        inner: Vec<ConfChange>,
+    ) {
+        changes.extend(inner.into_iter().map(|change| ConfChange {
+            command: if change.command.is_empty() {
+                command_name.to_owned()
+            } else {
+                ValueRecord::subcommand_path(command_name, &change.command)
+            },
+            ..change
+        }));
+    }
+
+    // Push a change for the selected subcommand, if it differs
+    #[doc(hidden)]
+    pub fn push_subcommand_name(
+        changes: &mut Vec<ConfChange>,
+        id: &str,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) {
+        let to_value = |name: Option<&str>| {
+            name.map(|name| ReportValue::String(name.to_owned()))
+                .unwrap_or(ReportValue::Unset)
+        };
+        Self::push_option(changes, &[], id, to_value(old), to_value(new), None);
+    }
+}
+
+// Helpers which render the value of a field for ConfChange, preferring Display to Debug.
+// The derive macro calls `(&&&&DiffValue(&val)).diff_render()`, and method resolution picks the
+// first of these traits which is implemented (autoref specialization).
+// If neither is implemented, the value is opaque and changes to it can't be detected.
+//
+// Secret<T> is never rendered, so that no copy of the secret is made. Instead, secrets are
+// compared in place using `DiffEq`.
+#[doc(hidden)]
+pub struct DiffValue<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait DiffRenderSecret {
+    fn diff_render(&self) -> ReportValue;
+}
+
+impl<T: Zeroize> DiffRenderSecret for &&&DiffValue<'_, Secret<T>> {
+    fn diff_render(&self) -> ReportValue {
+        ReportValue::Redacted
+    }
+}
+
+#[doc(hidden)]
+pub trait DiffRenderDisplay {
+    fn diff_render(&self) -> ReportValue;
+}
+
+impl<T: fmt::Display> DiffRenderDisplay for &&DiffValue<'_, T> {
+    fn diff_render(&self) -> ReportValue {
+        ReportValue::String(self.0.to_string())
+    }
+}
+
+#[doc(hidden)]
+pub trait DiffRenderDebug {
+    fn diff_render(&self) -> ReportValue;
+}
+
+impl<T: fmt::Debug> DiffRenderDebug for &DiffValue<'_, T> {
+    fn diff_render(&self) -> ReportValue {
+        ReportValue::String(format!("{:?}", self.0))
+    }
+}
+
+#[doc(hidden)]
+pub trait DiffRenderOpaque {
+    fn diff_render(&self) -> ReportValue;
+}
+
+impl<T> DiffRenderOpaque for DiffValue<'_, T> {
+    fn diff_render(&self) -> ReportValue {
+        ReportValue::Opaque
+    }
+}
+
+// Helpers which compare the old and new values of a field, if it is a secret (or a list of
+// secrets). The derive macro calls `(&&DiffEq(old, new)).secret_changed()`, which is `None` for
+// other types, whose rendered values are compared instead.
+#[doc(hidden)]
+pub struct DiffEq<'a, T>(pub Option<&'a T>, pub Option<&'a T>);
+
+#[doc(hidden)]
+pub trait DiffEqSecret {
+    fn secret_changed(&self) -> Option<bool>;
+}
+
+impl<T: Zeroize + PartialEq> DiffEqSecret for &DiffEq<'_, Secret<T>> {
+    fn secret_changed(&self) -> Option<bool> {
+        Some(match (self.0, self.1) {
+            (Some(old), Some(new)) => old.expose() != new.expose(),
+            (old, new) => old.is_some() != new.is_some(),
+        })
+    }
+}
+
+impl<T: Zeroize + PartialEq> DiffEqSecret for &DiffEq<'_, Vec<Secret<T>>> {
+    fn secret_changed(&self) -> Option<bool> {
+        Some(match (self.0, self.1) {
+            (Some(old), Some(new)) => {
+                old.len() != new.len()
+                    || old
+                        .iter()
+                        .zip(new)
+                        .any(|(old, new)| old.expose() != new.expose())
+            }
+            (old, new) => old.is_some() != new.is_some(),
+        })
+    }
+}
+
+#[doc(hidden)]
+pub trait DiffEqOther {
+    fn secret_changed(&self) -> Option<bool>;
+}
+
+impl<T> DiffEqOther for DiffEq<'_, T> {
+    fn secret_changed(&self) -> Option<bool> {
+        None
+    }
+}
+
+// The format requested via the print config flag
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl ReportFormat {
+    pub(crate) const POSSIBLE_VALUES: [&'static str; 2] = ["text", "json"];
+
+    pub(crate) fn from_str(src: &str) -> Self {
+        match src {
+            "json" => Self::Json,
+            _ => Self::Text,
+        }
+    }
+
+    pub(crate) fn render(self, report: &ConfigReport) -> String {
+        match self {
+            Self::Text => report.to_string(),
+            Self::Json => report.to_json() + "\n",
+        }
+    }
+}
+
+// The value of a program option and its source, recorded by ConfContext during a parse.
+// Secrets are redacted at the time they are recorded.
+#[derive(Clone, Debug)]
+pub(crate) struct ValueRecord {
+    // Names of the subcommands leading to the command which has this option, separated by spaces
+    pub command_path: String,
+    // The id of the option, including any prefix from flattening
+    pub id: String,
+    pub source: ValueSource,
+    pub value: ReportValue,
+}
+
+impl ValueRecord {
+    pub(crate) fn new(
+        command_path: &str,
+        opt: &ProgramOption,
+        source: ConfValueSource<&str>,
+        value: impl FnOnce() -> ReportValue,
+    ) -> Self {
+        // The output of an env command is never reported, since it is likely a secret.
+        // The value is only produced if it will be reported, so that no copy of a secret is made.
+        let redact = opt.is_secret() || matches!(source, ConfValueSource::EnvCmd(_));
+        Self {
+            command_path: command_path.to_owned(),
+            id: opt.id.clone().into_owned(),
+            source: source.into(),
+            value: if redact {
+                ReportValue::Redacted
+            } else {
+                value()
+            },
+        }
+    }
+
+    pub(crate) fn subcommand_path(command_path: &str, subcommand_name: &str) -> String {
+        if command_path.is_empty() {
+            subcommand_name.to_owned()
+        } else {
+            format!("{command_path} {subcommand_name}")
+        }
+    }
+}
+
+// The collection of records which ConfContext writes to.
+// Recording the same option again replaces the earlier record, which happens when a document value
+// takes precedence over a default value.
+//
+// Uses of deprecated options, and any warnings, are also collected here, so that they can be
+// reported after the parse.
+#[derive(Default)]
+pub(crate) struct ValueRecords {
+    records: RefCell<Vec<ValueRecord>>,
+    deprecated: RefCell<Vec<InnerError>>,
+    warnings: RefCell<Vec<Warning>>,
+}
+
+impl ValueRecords {
+    pub(crate) fn record(&self, record: ValueRecord) {
+        let mut records = self.records.borrow_mut();
+        if let Some(existing) = records
+            .iter_mut()
+            .find(|rec| rec.command_path == record.command_path && rec.id == record.id)
+        {
+            *existing = record;
+        } else {
+            records.push(record);
+        }
+    }
+
+    pub(crate) fn record_deprecated(&self, err: InnerError) {
+        let mut deprecated = self.deprecated.borrow_mut();
+        if !deprecated.contains(&err) {
+            deprecated.push(err);
+        }
+    }
+
+    pub(crate) fn take_deprecated(&self) -> Vec<InnerError> {
+        self.deprecated.take()
+    }
+
+    pub(crate) fn warn(&self, warning: Warning) {
+        let mut warnings = self.warnings.borrow_mut();
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+
+    pub(crate) fn take_warnings(&self) -> Vec<Warning> {
+        self.warnings.take()
+    }
+
+    pub(crate) fn take_records(&self) -> Vec<ValueRecord> {
+        self.records.take()
+    }
+}
@@ -0,0 +1,64 @@
+use std::{collections::HashMap, error::Error as StdError};
+
+/// The error type returned by a [`Resolver`].
+pub type ResolveError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// A resolver turns a reference to a value, such as `vault://kv/app#password`, into the value
+/// itself, e.g. by fetching it from a secret manager.
+///
+/// Resolvers are installed using [`ConfBuilder::resolver`](crate::ConfBuilder::resolver), with a
+/// scheme such as `"vault"`. Then any string value which starts with `vault://` is passed to the
+/// resolver, and the result is given to the value parser instead. This applies whether the string
+/// came from args, env, an `env_cmd` or a default value. Strings in document values are resolved
+/// too, including strings nested in lists, before the value is deserialized.
+///
+/// The resolver is passed the entire reference, including the scheme.
+///
+/// This is implemented for closures, and for a `HashMap<String, String>`, which maps each
+/// reference to its value and may be useful in tests.
+///
+/// Resolvers must be `Send + Sync`, so that a [`ConfBuilder`](crate::ConfBuilder) can be built
+/// on one thread and used on another.
+pub trait Resolver: Send + Sync {
+    /// Resolve a reference to a value.
+    ///
+    /// The error is displayed to the user as part of a parsing error, along with any other errors.
+    fn resolve(&self, reference: &str) -> Result<String, ResolveError>;
+}
+
+impl<F> Resolver for F
+where
+    F: Fn(&str) -> Result<String, ResolveError> + Send + Sync,
+{
+    fn resolve(&self, reference: &str) -> Result<String, ResolveError> {
+        self(reference)
+    }
+}
+
+impl Resolver for HashMap<String, String> {
+    fn resolve(&self, reference: &str) -> Result<String, ResolveError> {
+        self.get(reference)
+            .cloned()
+            .ok_or_else(|| "reference not found".into())
+    }
+}
+
+// The resolvers installed in a ConfBuilder, with the prefixes (scheme://) that they handle.
+#[derive(Default)]
+pub(crate) struct Resolvers {
+    resolvers: Vec<(String, Box<dyn Resolver>)>,
+}
+
+impl Resolvers {
+    pub(crate) fn push(&mut self, scheme: &str, resolver: Box<dyn Resolver>) {
+        self.resolvers.push((format!("{scheme}://"), resolver));
+    }
+
+    // Find a resolver for a string, if it is a reference with a registered scheme, and resolve it.
+    pub(crate) fn resolve(&self, val: &str) -> Option<Result<String, ResolveError>> {
+        self.resolvers
+            .iter()
+            .find(|(prefix, _)| val.starts_with(prefix.as_str()))
+            .map(|(_, resolver)| resolver.resolve(val))
+    }
+}
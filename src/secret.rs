@@ -0,0 +1,105 @@
+use std::{fmt, str::FromStr};
+use zeroize::Zeroize;
+
+/// A wrapper for a secret value, such as a password or an API key.
+///
+/// * `Debug` and `Display` print `[REDACTED]` instead of the value, so a `#[derive(Debug)]` config
+///   structure can be logged without revealing the secret.
+/// * The value is zeroized when it is dropped.
+/// * Accessing the value requires an explicit call to [`Secret::expose`], which is easy to find
+///   in code review.
+///
+/// `Secret<T>` implements `FromStr` (and `serde::Deserialize`, if `T` does), so it can be used as
+/// the type of a `parameter` or `repeat` field, with the default value parser.
+/// When a field has type `Secret<T>` or `Option<Secret<T>>`, `derive(Conf)` marks it as a
+/// `secret` automatically, so that the value is also redacted in errors and help text.
+/// (This can be overridden with `secret = false`.)
+///
+/// ```
+/// # use conf::{Conf, Secret};
+/// #[derive(Conf, Debug)]
+/// pub struct Config {
+///     #[arg(env)]
+///     pub api_key: Secret<String>,
+/// }
+///
+/// let config = Config::conf_builder()
+///     .args(["."])
+///     .env([("API_KEY", "sekrit")])
+///     .parse();
+/// assert_eq!(format!("{config:?}"), "Config { api_key: [REDACTED] }");
+/// assert_eq!(config.api_key.expose(), "sekrit");
+/// ```
+///
+/// Like any secret, such a field can't have a `short` or `long` switch unless `secret(allow_cli)`
+/// is used, and this is reported when the structure is derived:
+///
+/// ```compile_fail
+/// # use conf::{Conf, Secret};
+/// #[derive(Conf)]
+/// pub struct Config {
+///     #[arg(long, env)]
+///     pub api_key: Secret<String>,
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wrap a value
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Get a reference to the secret value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Get a mutable reference to the secret value
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<T: Zeroize + FromStr> FromStr for Secret<T> {
+    type Err = T::Err;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        T::from_str(src).map(Self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Zeroize + serde::Deserialize<'de>> serde::Deserialize<'de> for Secret<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self)
+    }
+}
@@ -0,0 +1,119 @@
+//! "Did you mean" suggestions for misspelled env vars and document keys.
+
+use crate::{ParsedEnv, ProgramOption};
+use std::collections::HashSet;
+
+// Find env vars which are set, but are not read by any option, and which look like misspellings of
+// one of the env names of `opt`. The results are sorted by edit distance and then by name.
+//
+// If there are any env prefixes, only env vars which start with one of them are considered, as in
+// `Parser::unknown_env_errors`, so that we don't suggest variables which are meant for other programs.
+pub(crate) fn similar_env_vars(
+    opt: &ProgramOption,
+    env: &ParsedEnv,
+    env_prefixes: &[&str],
+    known_env_names: &HashSet<&str>,
+) -> Vec<String> {
+    let targets = opt
+        .env_form
+        .iter()
+        .chain(opt.env_aliases.iter())
+        .map(|name| name.as_ref())
+        .collect::<Vec<&str>>();
+    if targets.is_empty() {
+        return Vec::new();
+    }
+
+    let mut found = env
+        .keys()
+        .filter(|name| {
+            env_prefixes.is_empty()
+                || env_prefixes
+                    .iter()
+                    .any(|prefix| starts_with_ignore_ascii_case(name, prefix))
+        })
+        .filter(|name| !known_env_names.contains(name))
+        .filter_map(|name| {
+            targets
+                .iter()
+                .filter_map(|target| similarity(target, name))
+                .min()
+                .map(|distance| (distance, name))
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+    found.into_iter().map(|(_, name)| name.to_owned()).collect()
+}
+
+// Find known env names which look like what an unknown env var was supposed to be.
+// The results are sorted by edit distance and then by name.
+pub(crate) fn similar_known_env_names(name: &str, known_env_names: &HashSet<&str>) -> Vec<String> {
+    let mut found = known_env_names
+        .iter()
+        .filter_map(|known| similarity(known, name).map(|distance| (distance, *known)))
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+        .into_iter()
+        .map(|(_, known)| known.to_owned())
+        .collect()
+}
+
+// Find names which look like what a misspelled name was supposed to be, e.g. for a key in a
+// document. The results are sorted by edit distance and then by name.
+pub(crate) fn similar_names(name: &str, known_names: &[&str]) -> Vec<String> {
+    let mut found = known_names
+        .iter()
+        .filter_map(|known| similarity(known, name).map(|distance| (distance, *known)))
+        .collect::<Vec<_>>();
+    found.sort();
+    found.dedup();
+    found
+        .into_iter()
+        .map(|(_, known)| known.to_owned())
+        .collect()
+}
+
+// If `name` looks like a misspelling of `target`, return the edit distance between them.
+//
+// The distance must also be less than the length of the shorter name, otherwise every short name
+// would look like a misspelling of every other short name, e.g. 'n' of 'a'.
+fn similarity(target: &str, name: &str) -> Option<usize> {
+    let distance = edit_distance(target, name);
+    let shorter_len = target.chars().count().min(name.chars().count());
+    (distance <= max_distance(name) && distance < shorter_len).then_some(distance)
+}
+
+fn starts_with_ignore_ascii_case(name: &str, prefix: &str) -> bool {
+    name.len() >= prefix.len()
+        && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+// How many edits we allow before we don't consider a name to be a misspelling.
+fn max_distance(name: &str) -> usize {
+    (1 + name.len() / 6).min(3)
+}
+
+// Levenshtein distance, ignoring ASCII case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a
+        .chars()
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<Vec<_>>();
+    let b = b
+        .chars()
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<Vec<_>>();
+
+    let mut prev_row = (0..=b.len()).collect::<Vec<usize>>();
+    let mut row = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(a_char != b_char);
+            row[j + 1] = substitution.min(prev_row[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev_row, &mut row);
+    }
+    prev_row[b.len()]
+}
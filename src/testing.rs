@@ -0,0 +1,357 @@
+//! Helpers for testing [`Conf`] structures, available with the `testing` feature.
+//!
+//! * [`Fixture`] collects args and env vars fluently, and parses them.
+//! * [`assert_error_kind`] and [`assert_error_contains`] check the items of an [`Error`].
+//! * [`HelpRenderer`] renders `--help` output with a fixed bin name and width, and
+//!   [`env_template`] renders a `.env`-style template of all the env vars a structure reads.
+//! * [`assert_snapshot`] compares such output against a golden file.
+//!
+//! ```ignore
+//! use conf::{testing::*, ErrorKind};
+//!
+//! #[test]
+//! fn test_config() {
+//!     let config: Config = Fixture::new().env("DB_URL", "postgres://").arg("-v").parse_ok();
+//!     assert!(config.verbose);
+//!
+//!     let err = Fixture::new().parse_err::<Config>();
+//!     assert_error_kind(&err, ErrorKind::MissingRequired, "db_url");
+//!
+//!     assert_help_snapshot::<Config>("tests/snapshots/help.txt");
+//!     assert_env_template_snapshot::<Config>("tests/snapshots/env.txt");
+//! }
+//! ```
+
+use crate::{
+    introspect::{CommandInfo, OptionKind},
+    Conf, ConfBuilder, Error, ErrorItem, ErrorKind, ParsedEnv,
+};
+use std::{ffi::OsString, fmt::Write, path::Path};
+
+/// The env var which makes [`assert_snapshot`] overwrite golden files instead of comparing
+/// against them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "CONF_UPDATE_SNAPSHOTS";
+
+/// A set of CLI args and env vars to parse a [`Conf`] structure from.
+///
+/// The first CLI arg (the binary name) is supplied automatically, so only the args that follow it
+/// need to be added. The env of the test process is never used.
+#[derive(Clone, Debug, Default)]
+pub struct Fixture {
+    args: Vec<OsString>,
+    env: Vec<(OsString, OsString)>,
+}
+
+impl Fixture {
+    /// Create a fixture with no args and an empty env.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a CLI arg
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add several CLI args
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an env var. This replaces any earlier value of the same env var.
+    pub fn env(mut self, key: impl Into<OsString>, val: impl Into<OsString>) -> Self {
+        let key = key.into();
+        self.env.retain(|(k, _)| *k != key);
+        self.env.push((key, val.into()));
+        self
+    }
+
+    /// Set several env vars
+    pub fn envs<K, V>(mut self, env: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        for (key, val) in env {
+            self = self.env(key, val);
+        }
+        self
+    }
+
+    /// Remove an env var which was set earlier
+    pub fn remove_env(mut self, key: impl Into<OsString>) -> Self {
+        let key = key.into();
+        self.env.retain(|(k, _)| *k != key);
+        self
+    }
+
+    /// Get a [`ConfBuilder`] with these args and env, to use other builder options before
+    /// parsing.
+    pub fn builder<S: Conf>(&self) -> ConfBuilder<S> {
+        S::conf_builder()
+            .args(std::iter::once(OsString::from(".")).chain(self.args.iter().cloned()))
+            .env(self.env.iter().cloned())
+    }
+
+    /// Parse these args and env
+    pub fn parse<S: Conf>(&self) -> Result<S, Error> {
+        self.builder().try_parse()
+    }
+
+    /// Parse these args and env, panicking with the error text if that fails.
+    #[track_caller]
+    pub fn parse_ok<S: Conf>(&self) -> S {
+        match self.parse() {
+            Ok(result) => result,
+            Err(err) => panic!("Parsing {self:?} failed:\n{err}"),
+        }
+    }
+
+    /// Parse these args and env, expecting an error, and panicking if parsing succeeds.
+    #[track_caller]
+    pub fn parse_err<S: Conf>(&self) -> Error {
+        match self.parse::<S>() {
+            Ok(_) => panic!("Parsing {self:?} succeeded, but an error was expected"),
+            Err(err) => err,
+        }
+    }
+}
+
+/// Assert that an error has an item of the given kind for the option with the given id path,
+/// e.g. `"db.url"`, and return that item.
+///
+/// On failure, the panic message lists all the items of the error.
+#[track_caller]
+pub fn assert_error_kind<'a>(err: &'a Error, kind: ErrorKind, id: &str) -> &'a ErrorItem {
+    match err
+        .items()
+        .find(|item| item.kind == kind && item.id.as_deref() == Some(id))
+    {
+        Some(item) => item,
+        None => panic!(
+            "Expected an error item of kind {kind:?} for '{id}', but found:\n{}",
+            describe_items(err)
+        ),
+    }
+}
+
+/// Assert that the text of an error (as it would be printed, but without color) contains the
+/// given text.
+#[track_caller]
+pub fn assert_error_contains(err: &Error, text: &str) {
+    let err_text = err.to_string();
+    assert!(
+        err_text.contains(text),
+        "Expected error text to contain {text:?}, but it was:\n{err_text}\nItems:\n{}",
+        describe_items(err)
+    );
+}
+
+fn describe_items(err: &Error) -> String {
+    let mut result = String::new();
+    for item in err.items() {
+        let id = item.id.as_deref().unwrap_or("<none>");
+        writeln!(result, "  {:?} for '{id}': {}", item.kind, item.message).unwrap();
+    }
+    if result.is_empty() {
+        result = "  (no items)\n".to_owned();
+    }
+    result
+}
+
+/// Renders the help output of a [`Conf`] structure or one of its subcommands, in a way that
+/// doesn't depend on the name of the binary, the width of the terminal, or the env of the
+/// test process.
+#[derive(Clone, Debug)]
+pub struct HelpRenderer {
+    bin_name: String,
+    width: usize,
+    subcommand_path: Vec<String>,
+    long_help: bool,
+}
+
+impl Default for HelpRenderer {
+    fn default() -> Self {
+        Self {
+            bin_name: ".".to_owned(),
+            width: 100,
+            subcommand_path: Vec::new(),
+            long_help: false,
+        }
+    }
+}
+
+impl HelpRenderer {
+    /// Create a help renderer with bin name `"."` and width 100.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the bin name that appears in the usage line
+    pub fn bin_name(mut self, bin_name: impl Into<String>) -> Self {
+        self.bin_name = bin_name.into();
+        self
+    }
+
+    /// Set the terminal width that help text is wrapped to
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Render the help of a subcommand instead. This can be called repeatedly to select a
+    /// nested subcommand.
+    pub fn subcommand(mut self, name: impl Into<String>) -> Self {
+        self.subcommand_path.push(name.into());
+        self
+    }
+
+    /// Render the long help (`--help`) instead of the short help (`-h`). These only differ if
+    /// some description has more than one paragraph.
+    pub fn long(mut self) -> Self {
+        self.long_help = true;
+        self
+    }
+
+    /// Render the help text.
+    ///
+    /// Panics if the selected subcommand doesn't exist.
+    #[track_caller]
+    pub fn render<S: Conf>(&self) -> Result<String, Error> {
+        let parsed_env = ParsedEnv::default();
+        let parser = S::get_parser(&parsed_env)?;
+        let path = self
+            .subcommand_path
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        match parser.render_help_with(&self.bin_name, &path, Some(self.width), self.long_help) {
+            Some(help) => Ok(help),
+            None => panic!("Subcommand path {path:?} was not found"),
+        }
+    }
+}
+
+/// Render a `.env`-style template of all the env vars that a [`Conf`] structure (including
+/// flattened structures and subcommands) reads.
+///
+/// Each env var is preceded by its description as a comment. Required env vars are left blank,
+/// env vars with a default value show it, and other env vars are commented out. Secrets never
+/// show a value, and deprecated and hidden options are skipped.
+pub fn env_template<S: Conf>() -> Result<String, Error> {
+    let info = crate::introspect::<S>()?;
+    let mut result = String::new();
+    write_env_template(&info, "", &mut result);
+    Ok(result)
+}
+
+fn write_env_template(info: &CommandInfo, command_prefix: &str, out: &mut String) {
+    for opt in &info.options {
+        let Some(env) = opt.env.as_deref() else {
+            continue;
+        };
+        if opt.deprecated.is_some() || opt.is_hidden {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if let Some(description) = opt.description.as_deref() {
+            for line in description.lines() {
+                writeln!(out, "# {line}").unwrap();
+            }
+        }
+        if opt.is_secret {
+            out.push_str("# (secret)\n");
+        }
+        let default_value = opt.default_value.as_deref().filter(|_| !opt.is_secret);
+        if opt.is_required {
+            writeln!(out, "{env}=").unwrap();
+        } else if let Some(default_value) = default_value {
+            writeln!(out, "{env}={default_value}").unwrap();
+        } else if opt.kind == OptionKind::Flag {
+            writeln!(out, "# {env}=false").unwrap();
+        } else {
+            writeln!(out, "# {env}=").unwrap();
+        }
+    }
+    for subcommand in &info.subcommands {
+        let command = format!("{command_prefix}{}", subcommand.name);
+        let mut section = String::new();
+        write_env_template(subcommand, &format!("{command} "), &mut section);
+        if !section.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            writeln!(out, "## Subcommand: {command}\n").unwrap();
+            out.push_str(&section);
+        }
+    }
+}
+
+/// Compare text against a golden file, panicking if they differ.
+///
+/// If the file doesn't exist yet, or the env var `CONF_UPDATE_SNAPSHOTS` is set to a value other
+/// than `0`, the file is written instead. Trailing whitespace at the ends of lines is ignored.
+#[track_caller]
+pub fn assert_snapshot(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+    let actual = trim_line_ends(actual);
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some_and(|val| val != "0");
+    if update || !path.exists() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .unwrap_or_else(|err| panic!("Creating {}: {err}", dir.display()));
+        }
+        std::fs::write(path, &actual)
+            .unwrap_or_else(|err| panic!("Writing {}: {err}", path.display()));
+        return;
+    }
+
+    let expected = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Reading {}: {err}", path.display()));
+    let expected = trim_line_ends(&expected);
+    if actual != expected {
+        let line = actual
+            .lines()
+            .zip(expected.lines())
+            .position(|(a, e)| a != e)
+            .unwrap_or_else(|| actual.lines().count().min(expected.lines().count()));
+        panic!(
+            "Snapshot {} differs at line {} (set {UPDATE_SNAPSHOTS_ENV}=1 to update it)\n\
+             Expected:\n{expected}\nActual:\n{actual}",
+            path.display(),
+            line + 1
+        );
+    }
+}
+
+/// Compare the help of a [`Conf`] structure, as rendered by a default [`HelpRenderer`], against
+/// a golden file. See [`assert_snapshot`].
+#[track_caller]
+pub fn assert_help_snapshot<S: Conf>(path: impl AsRef<Path>) {
+    let help = HelpRenderer::new()
+        .render::<S>()
+        .unwrap_or_else(|err| panic!("Rendering help failed:\n{err}"));
+    assert_snapshot(path, &help);
+}
+
+/// Compare the [`env_template`] of a [`Conf`] structure against a golden file. See
+/// [`assert_snapshot`].
+#[track_caller]
+pub fn assert_env_template_snapshot<S: Conf>(path: impl AsRef<Path>) {
+    let template =
+        env_template::<S>().unwrap_or_else(|err| panic!("Rendering env template failed:\n{err}"));
+    assert_snapshot(path, &template);
+}
+
+fn trim_line_ends(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for line in text.lines() {
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+    result
+}
@@ -1,6 +1,7 @@
 use crate::{
-    ConfBuilder, ConfContext, ConfValueSource, Error, InnerError, ParsedEnv, Parser, ParserConfig,
-    ProgramOption,
+    report::{ConfChange, ConfigReport, Sources},
+    ConfBuilder, ConfContext, ConfValueSource, Error, FormattedValues, InnerError, ParsedEnv,
+    Parser, ParserConfig, ProgramOption,
 };
 use std::ffi::OsString;
 
@@ -31,6 +32,41 @@ pub trait Conf: Sized {
         Self::conf_builder().try_parse()
     }
 
+    /// Parse self from the process CLI args and environment, together with a report of the
+    /// effective value of each program option and where it came from, and exit the program with
+    /// a help message if we cannot.
+    ///
+    /// The report can be logged on startup. Secret values are redacted.
+    #[inline]
+    fn parse_with_report() -> (Self, ConfigReport) {
+        Self::conf_builder().parse_with_report()
+    }
+
+    /// Try to parse self from the process CLI args and environment, together with a report of
+    /// the effective value of each program option and where it came from, and return an error if
+    /// we cannot.
+    #[inline]
+    fn try_parse_with_report() -> Result<(Self, ConfigReport), Error> {
+        Self::conf_builder().try_parse_with_report()
+    }
+
+    /// Parse self from the process CLI args and environment, together with the source of the
+    /// value of each program option, and exit the program with a help message if we cannot.
+    ///
+    /// This can be used to make decisions based on where a value came from, for instance to warn
+    /// if a sensitive value was passed in CLI args.
+    #[inline]
+    fn parse_with_sources() -> (Self, Sources) {
+        Self::conf_builder().parse_with_sources()
+    }
+
+    /// Try to parse self from the process CLI args and environment, together with the source of
+    /// the value of each program option, and return an error if we cannot.
+    #[inline]
+    fn try_parse_with_sources() -> Result<(Self, Sources), Error> {
+        Self::conf_builder().try_parse_with_sources()
+    }
+
     /// Parse self from given containers which stand in for the process args and environment, and
     /// exit the program with a help message if we cannot. This function's behavior is isolated
     /// from the values of [`std::env::args_os`] and [`std::env::vars_os`].
@@ -63,6 +99,20 @@ pub trait Conf: Sized {
             .try_parse()
     }
 
+    /// Compare self with another instance, and list the program options whose values differ,
+    /// including those of flattened structures and subcommands.
+    ///
+    /// This can be used to log what changed when config is reloaded.
+    ///
+    /// Values are rendered using `Display` if the type of the field implements it, and `Debug`
+    /// otherwise. Fields whose type implements neither can't be compared, and changes to them are
+    /// not reported. Secret values are redacted.
+    fn diff(&self, other: &Self) -> Vec<ConfChange> {
+        let mut changes = Vec::new();
+        Self::diff_helper(Some(self), Some(other), &mut changes);
+        changes
+    }
+
     /// Obtain a ConfBuilder, and use the builder API to parse.
     /// The builder API is needed if you want to use advanced features.
     fn conf_builder() -> ConfBuilder<Self> {
@@ -138,6 +188,12 @@ pub trait Conf: Sized {
         }
         Ok(None)
     }
+    // Push the changes between two instances onto changes, with ids relative to this structure.
+    // None stands for an instance which is absent, e.g. an optional flattened structure which is
+    // None, in which case all of its options are unset.
+    // This is implemented using the derive macros.
+    #[doc(hidden)]
+    fn diff_helper(old: Option<&Self>, new: Option<&Self>, changes: &mut Vec<ConfChange>);
     // Get the name used for this group of options in error messages.
     // Generally this is the struct identifier
     #[doc(hidden)]
@@ -175,6 +231,15 @@ pub trait Subcommands: Sized {
     #[doc(hidden)]
     fn get_subcommand_names() -> &'static [&'static str];
 
+    // Get the subcommand name of the variant that self is
+    #[doc(hidden)]
+    fn get_command_name(&self) -> &'static str;
+
+    // Push the changes between two instances onto changes, with command paths relative to this
+    // enum. None stands for no subcommand being selected.
+    #[doc(hidden)]
+    fn diff_helper(old: Option<&Self>, new: Option<&Self>, changes: &mut Vec<ConfChange>);
+
     // Construct Self from a command name and a conf context for the corresponding subcommand
     #[doc(hidden)]
     fn from_conf_context(
@@ -182,3 +247,104 @@ pub trait Subcommands: Sized {
         conf_context: ConfContext<'_>,
     ) -> Result<Self, Vec<InnerError>>;
 }
+
+/// Extension to Conf trait which turns a parsed value back into CLI args and env vars.
+///
+/// To derive this trait, use `#[derive(Conf)]` together with the `#[conf(to_args)]` annotation
+/// on your struct.
+///
+/// Any flattened substructures must also have `#[conf(to_args)]`, and so must any subcommands
+/// enums. Each parameter and repeat field must have a `value_formatter`, or a type which implements
+/// `Display` or `AsRef<OsStr>`, otherwise the derive fails to compile:
+///
+/// ```compile_fail
+/// # use conf::Conf;
+/// #[derive(Debug)]
+/// pub struct Opaque(String);
+///
+/// impl std::str::FromStr for Opaque {
+///     type Err = std::convert::Infallible;
+///     fn from_str(src: &str) -> Result<Self, Self::Err> {
+///         Ok(Self(src.to_owned()))
+///     }
+/// }
+///
+/// #[derive(Conf)]
+/// #[conf(to_args)]
+/// pub struct Config {
+///     #[arg(long)]
+///     pub opaque: Opaque,
+/// }
+/// ```
+///
+/// **Hand-written implementations of this trait are not supported**.
+///
+/// You should think of this trait as a "non-exhaustive trait" with hidden required items.
+/// `conf` is free to add, modify, or remove these implementation details without considering it
+/// a semver breaking change, so the only stable way to get an impl is via the derive macro.
+pub trait ConfToArgs: Conf {
+    /// The CLI args which represent self, for instance to pass the same config to a child process.
+    ///
+    /// Parsing these together with [`ConfToArgs::to_env_vars`] produces an equal value:
+    /// `S::try_parse_from(config.to_args(), config.to_env_vars())`.
+    ///
+    /// * The first element is the command name, as in [`std::env::args_os`]. Skip it when using
+    ///   `std::process::Command::args`.
+    /// * Options which have no switch are left out, and so are secrets which can be read from env,
+    ///   so that they don't appear in process listings.
+    /// * The selected subcommand, if any, is followed by its own args.
+    ///
+    /// Values are formatted using the `value_formatter` of the field if it has one, and otherwise
+    /// using `Display`, or `AsRef<OsStr>` for types like `PathBuf`, which need not be valid
+    /// UTF-8. `Secret<T>` values are exposed.
+    fn to_args(&self) -> Vec<OsString> {
+        let mut values = FormattedValues::default();
+        self.format_values(&mut values);
+        let mut args = vec![Self::get_parser_config()
+            .map(|parser_config| parser_config.name.into())
+            .unwrap_or_default()];
+        values.to_args(Self::get_program_options().unwrap_or(&[]), &mut args);
+        args
+    }
+
+    /// The env vars which represent self, for instance to pass the same config to a child process.
+    ///
+    /// See [`ConfToArgs::to_args`].
+    ///
+    /// * Options which have no env var are left out.
+    /// * Flags which are false are left out, and flags which are true have the value `true`.
+    /// * The values of repeat options are joined by the env delimiter. If there is no env delimiter
+    ///   and there is more than one value, the option is left out.
+    /// * The env vars of options of the selected subcommand, if any, are included.
+    ///
+    /// Values are formatted as in [`ConfToArgs::to_args`], and secrets are included.
+    fn to_env_vars(&self) -> Vec<(OsString, OsString)> {
+        let mut values = FormattedValues::default();
+        self.format_values(&mut values);
+        let mut env = Vec::new();
+        values.to_env_vars(Self::get_program_options().unwrap_or(&[]), &mut env);
+        env
+    }
+
+    // Push the formatted values of the fields of self onto values, with ids relative to this
+    // structure. This is used to implement to_args and to_env_vars.
+    // This is implemented using the derive macros.
+    #[doc(hidden)]
+    fn format_values(&self, values: &mut FormattedValues);
+}
+
+/// Extension to Subcommands trait which is used by [`ConfToArgs`].
+///
+/// To derive this trait, use `#[derive(Subcommands)]` together with the `#[conf(to_args)]`
+/// annotation on your enum.
+///
+/// **Hand-written implementations of this trait are not supported**.
+///
+/// You should think of this trait as a "non-exhaustive trait" with hidden required items.
+/// `conf` is free to add, modify, or remove these implementation details without considering it
+/// a semver breaking change, so the only stable way to get an impl is via the derive macro.
+pub trait SubcommandsToArgs: Subcommands {
+    // Set the selected subcommand and its formatted values on values
+    #[doc(hidden)]
+    fn format_values(&self, values: &mut FormattedValues);
+}
@@ -0,0 +1,72 @@
+use crate::{report::ValueSource, InnerError};
+use std::fmt;
+
+/// A problem found while parsing, which does not cause parsing to fail.
+///
+/// These are returned by [`ConfBuilder::try_parse_with_warnings`](crate::ConfBuilder::try_parse_with_warnings).
+/// Other parse functions pass them to the handler set with
+/// [`ConfBuilder::on_warning`](crate::ConfBuilder::on_warning). Without a handler, `parse`,
+/// `parse_with_report` and `parse_with_sources` print them to stderr, and the `try_parse`
+/// functions don't report them.
+///
+/// The values of secret options never appear in these.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Warning {
+    /// The kind of problem
+    pub kind: WarningKind,
+    /// The id path of the option that has the problem, e.g. `"db.url"`, if any.
+    pub id: Option<String>,
+    /// The switch of that option, e.g. `"--db-url"`, if it has one.
+    pub switch: Option<String>,
+    /// The env var that the problem is about, or else the env var of the option, if any.
+    pub env: Option<String>,
+    /// Where the value of the option came from, if the problem is about a value.
+    pub source: Option<ValueSource>,
+    /// A description of the problem, as it would be displayed (without the `warning: ` prefix).
+    pub message: String,
+}
+
+/// The kind of a [`Warning`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A deprecated option or env alias was used
+    Deprecated,
+    /// A secret was passed as a CLI argument, and the option uses `secret(warn_cli)`
+    SecretOnCli,
+    /// An env var is set, but is ignored because another env var of the same option is also set
+    ShadowedEnv,
+    /// An env var has the env prefix, and looks like a misspelling of an env var which is read by
+    /// some option, but it is not read by any option
+    UnknownEnv,
+}
+
+// A function which is called with each warning, see `ConfBuilder::on_warning`
+pub(crate) type WarningHandler = Box<dyn Fn(&Warning) + Send + Sync>;
+
+impl Warning {
+    // Make a warning from a problem which would be an error in stricter settings
+    pub(crate) fn from_inner_error(kind: WarningKind, err: &InnerError) -> Self {
+        let item = err.to_item();
+        Self {
+            kind,
+            id: item.id,
+            switch: item.switch,
+            env: item.env,
+            source: item.source,
+            message: item.message,
+        }
+    }
+
+    // The default warning handler
+    pub(crate) fn print(&self) {
+        eprintln!("warning: {self}");
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
@@ -76,6 +76,7 @@ Options:
                                      [env DB_RETRIES=]
       --config <config_file>         Config file path
                                      [env CONFIG=]
+      --help-env                     Print help for environment variables, and exit
   -h, --help                         Print help
 "[1..];
 
@@ -351,15 +352,13 @@ fn test_serde_basic_example_args_env_and_file_with_missing_and_invalid() {
         .output()
         .unwrap();
 
-    let expected = &format!(
-        r#"
+    let expected = &r#"
 error: A required value was not provided
   env 'DB_URL', or '--db-url', must be provided
 error: Invalid value
   when parsing '--auth-url' value 'asdf:/': invalid format
   when parsing env 'AUTH_RETRIES' value 'xxx': invalid digit found in string
-"#
-    )[1..];
+"#[1..];
 
     assert_eq!(
         output.status.code(),
@@ -419,6 +418,7 @@ Usage: serde_basic run-migrations [OPTIONS]
 Options:
       --sql-file <sql_file>  Path to migrations file (instead of embedded migrations)
                              [env SQL_FILE=]
+      --help-env             Print help for environment variables, and exit
   -h, --help                 Print help
 "[1..];
 
@@ -812,13 +812,13 @@ error: A required value was not provided
 error: Invalid value
   when parsing env 'AUTH_RETRIES' value 'yyy': invalid digit found in string
 error: Parsing document
-  Parsing {toml_file} (@ MigrationConfig):
-    unknown field `unexpected_arg`, expected `sql_file`
-
   Parsing {toml_file} (@ retries):
     invalid type: string \"xxx\", expected u32
     in `retries`
 
+error: Unknown document key
+  Parsing {toml_file}: unknown key 'migrations.unexpected_arg'
+    expected one of: 'sql_file'
 "
     )[1..];
 
@@ -0,0 +1,139 @@
+#![allow(unused)]
+
+mod common;
+use common::*;
+
+use conf::{Conf, ErrorKind, Subcommands};
+
+#[derive(Conf, Debug)]
+struct DbConfig {
+    #[arg(long, env)]
+    url: String,
+    #[arg(long, env, env_aliases = ["MAX_CONNS"])]
+    max_connections: Option<u32>,
+}
+
+#[derive(Conf, Debug)]
+#[conf(env_prefix = "MYAPP_", deny_unknown_env)]
+struct Config {
+    #[conf(flatten, prefix)]
+    db: DbConfig,
+    #[arg(env, env_cmd)]
+    password: Option<String>,
+    #[conf(subcommands)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommands, Debug)]
+enum Command {
+    Run(RunConfig),
+}
+
+#[derive(Conf, Debug)]
+#[conf(env_prefix = "RUN_")]
+struct RunConfig {
+    #[arg(env)]
+    threads: Option<u32>,
+}
+
+#[test]
+fn test_deny_unknown_env_ok() {
+    let config = Config::try_parse_from(
+        ["."],
+        [
+            ("MYAPP_DB_URL", "postgres://"),
+            ("MYAPP_DB_MAX_CONNS", "10"),
+            ("MYAPP_PASSWORD_CMD", "echo hunter2"),
+            ("RUN_THREADS", "4"),
+            ("PATH", "/usr/bin"),
+            ("OTHERAPP_FOO", "bar"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(config.db.max_connections, Some(10));
+}
+
+#[test]
+fn test_deny_unknown_env_errors() {
+    let err = Config::try_parse_from(
+        ["."],
+        [
+            ("MYAPP_DB_URL", "postgres://"),
+            ("MYAPP_DB_MAX_CONNECTION", "10"),
+            ("MYAPP_STALE_SETTING", "1"),
+            ("RUN_THREAD", "4"),
+        ],
+    )
+    .unwrap_err();
+
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items.len(), 3);
+    assert!(items.iter().all(|item| item.kind == ErrorKind::UnknownEnv));
+    assert_eq!(items[0].env.as_deref(), Some("MYAPP_DB_MAX_CONNECTION"));
+
+    assert_multiline_eq!(
+        &err.to_string(),
+        "error: Unknown env var
+  env 'MYAPP_DB_MAX_CONNECTION' is set, but is not read by any option
+    note: did you mean 'MYAPP_DB_MAX_CONNECTIONS'?
+  env 'MYAPP_STALE_SETTING' is set, but is not read by any option
+  env 'RUN_THREAD' is set, but is not read by any option
+    note: did you mean 'RUN_THREADS'?
+"
+    );
+}
+
+#[test]
+fn test_deny_unknown_env_aggregated() {
+    assert_error_contains_text!(
+        Config::try_parse_from(["."], [("MYAPP_DB_UTL", "postgres://")]),
+        [
+            "A required value was not provided",
+            "env 'MYAPP_DB_URL', or '--db-url', must be provided",
+            "Unknown env var",
+            "env 'MYAPP_DB_UTL' is set, but is not read by any option",
+            "did you mean 'MYAPP_DB_URL'?"
+        ]
+    );
+}
+
+#[test]
+fn test_deny_unknown_env_builder() {
+    let result = Config::conf_builder()
+        .args(["."])
+        .env([
+            ("MYAPP_DB_URL", "postgres://"),
+            ("MYAPP_STALE_SETTING", "1"),
+        ])
+        .deny_unknown_env(false)
+        .try_parse();
+    assert!(result.is_ok());
+
+    #[derive(Conf, Debug)]
+    #[conf(env_prefix = "MYAPP_")]
+    struct Lenient {
+        #[conf(flatten, prefix)]
+        db: DbConfig,
+    }
+
+    assert!(Lenient::try_parse_from(
+        ["."],
+        [
+            ("MYAPP_DB_URL", "postgres://"),
+            ("MYAPP_STALE_SETTING", "1")
+        ]
+    )
+    .is_ok());
+
+    assert_error_contains_text!(
+        Lenient::conf_builder()
+            .args(["."])
+            .env([
+                ("MYAPP_DB_URL", "postgres://"),
+                ("MYAPP_STALE_SETTING", "1")
+            ])
+            .deny_unknown_env(true)
+            .try_parse(),
+        ["env 'MYAPP_STALE_SETTING' is set, but is not read by any option"]
+    );
+}
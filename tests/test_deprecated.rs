@@ -0,0 +1,181 @@
+#![allow(unused)]
+
+mod common;
+use common::*;
+
+use conf::{introspect, report::ValueSource, Conf, ErrorKind};
+
+#[derive(Conf, Debug)]
+#[conf(env_prefix = "DB_")]
+struct DbConfig {
+    #[arg(long, env, env_aliases = ["URI"], env_aliases(deprecated = ["DATABASE_URL"]))]
+    url: String,
+    #[arg(long, env, deprecated = "the pool is sized automatically")]
+    pool_size: Option<u32>,
+    #[arg(long, deprecated)]
+    legacy_mode: bool,
+}
+
+#[test]
+fn test_deprecated_ok() {
+    // Deprecated names still work, they only produce warnings
+    let config = DbConfig::try_parse_from(
+        [".", "--legacy-mode"],
+        [("DB_DATABASE_URL", "postgres://"), ("DB_POOL_SIZE", "4")],
+    )
+    .unwrap();
+    assert_eq!(config.url, "postgres://");
+    assert_eq!(config.pool_size, Some(4));
+    assert!(config.legacy_mode);
+}
+
+#[test]
+fn test_deprecated_denied() {
+    let err = DbConfig::conf_builder()
+        .args([".", "--legacy-mode", "--pool-size", "4"])
+        .env([("DB_DATABASE_URL", "postgres://")])
+        .deny_deprecated(true)
+        .try_parse()
+        .unwrap_err();
+
+    assert_multiline_eq!(
+        &err.to_string(),
+        "error: Deprecated option was used
+  '--legacy-mode' is deprecated
+  '--pool-size' is deprecated: the pool is sized automatically
+  env 'DB_DATABASE_URL' is deprecated: use 'DB_URL' instead
+"
+    );
+
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items.len(), 3);
+    assert!(items.iter().all(|item| item.kind == ErrorKind::Deprecated));
+    assert_eq!(items[2].id.as_deref(), Some("url"));
+    assert_eq!(items[2].env.as_deref(), Some("DB_DATABASE_URL"));
+    assert_eq!(
+        items[2].source,
+        Some(ValueSource::Env("DB_DATABASE_URL".into()))
+    );
+}
+
+#[test]
+fn test_deprecated_denied_not_used() {
+    // Using the current names is fine
+    let config = DbConfig::conf_builder()
+        .args(["."])
+        .env([("DB_URI", "postgres://")])
+        .deny_deprecated(true)
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.url, "postgres://");
+
+    // Errors are reported together with other errors
+    assert_error_contains_text!(
+        DbConfig::conf_builder()
+            .args([".", "--pool-size", "x"])
+            .env([("DB_DATABASE_URL", "postgres://")])
+            .deny_deprecated(true)
+            .try_parse(),
+        [
+            "Invalid value",
+            "when parsing '--pool-size' value 'x'",
+            "Deprecated option was used",
+            "env 'DB_DATABASE_URL' is deprecated: use 'DB_URL' instead"
+        ]
+    );
+}
+
+#[test]
+fn test_deprecated_help() {
+    let help = DbConfig::try_parse_from([".", "--help"], [("DB_URL", "x")])
+        .unwrap_err()
+        .to_string();
+    assert!(
+        help.contains("[env DB_DATABASE_URL=] [deprecated]"),
+        "{help}"
+    );
+    assert!(
+        help.contains("[deprecated: the pool is sized automatically]"),
+        "{help}"
+    );
+    assert!(!help.contains("[env DB_URI=] [deprecated]"), "{help}");
+}
+
+#[test]
+fn test_deprecated_introspect() {
+    let info = introspect::<DbConfig>().unwrap();
+    let url = &info.options[0];
+    assert_eq!(url.env_aliases, vec!["DB_URI", "DB_DATABASE_URL"]);
+    assert_eq!(url.deprecated_env_aliases, vec!["DB_DATABASE_URL"]);
+    assert_eq!(url.deprecated, None);
+
+    let pool_size = &info.options[1];
+    assert_eq!(
+        pool_size.deprecated.as_deref(),
+        Some("the pool is sized automatically")
+    );
+    let legacy_mode = &info.options[2];
+    assert_eq!(legacy_mode.deprecated.as_deref(), Some(""));
+}
+
+#[cfg(feature = "serde")]
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct DocConfig {
+    #[arg(long, deprecated = "it has no effect")]
+    retries: Option<u32>,
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deprecated_document() {
+    assert_error_contains_text!(
+        DocConfig::conf_builder()
+            .args(["."])
+            .doc("config.json", serde_json::json!({ "retries": 3 }))
+            .deny_deprecated(true)
+            .try_parse(),
+        ["'retries' in document 'config.json' is deprecated: it has no effect"]
+    );
+
+    // Without a value, nothing is deprecated
+    assert!(DocConfig::conf_builder()
+        .args(["."])
+        .doc("config.json", serde_json::json!({}))
+        .deny_deprecated(true)
+        .try_parse()
+        .is_ok());
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_deprecated_schema() {
+    use conf::ConfSerde;
+
+    let schema = DocConfig::json_schema().unwrap();
+    assert_eq!(schema["properties"]["retries"]["deprecated"], true);
+}
+
+#[derive(Conf, Debug)]
+struct FlagConfig {
+    #[arg(long, env, env_aliases(deprecated = ["QUIET"]))]
+    silent: bool,
+}
+
+#[test]
+fn test_deprecated_flag_env_alias() {
+    assert!(
+        FlagConfig::try_parse_from(["."], [("QUIET", "1")])
+            .unwrap()
+            .silent
+    );
+
+    assert_error_contains_text!(
+        FlagConfig::conf_builder()
+            .args(["."])
+            .env([("QUIET", "1")])
+            .deny_deprecated(true)
+            .try_parse(),
+        ["env 'QUIET' is deprecated: use 'SILENT' instead"]
+    );
+}
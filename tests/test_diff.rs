@@ -0,0 +1,233 @@
+#![allow(unused)]
+
+mod common;
+use common::*;
+
+use conf::{
+    report::{ConfChange, ReportValue},
+    Conf, Secret, Subcommands,
+};
+use std::{path::PathBuf, str::FromStr};
+
+#[derive(Conf, Debug)]
+pub struct DbConfig {
+    #[arg(long, env)]
+    pub url: String,
+    #[arg(env, secret)]
+    pub password: Option<String>,
+}
+
+#[derive(Conf, Debug)]
+pub struct RunConfig {
+    #[arg(long, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Conf, Debug)]
+pub struct CheckConfig {
+    #[arg(long)]
+    pub strict: bool,
+}
+
+#[derive(Subcommands, Debug)]
+pub enum Commands {
+    Run(RunConfig),
+    Check(CheckConfig),
+}
+
+// A type which implements neither Display nor Debug
+#[derive(Clone)]
+pub struct Opaque(String);
+
+impl FromStr for Opaque {
+    type Err = std::convert::Infallible;
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Ok(Self(src.to_owned()))
+    }
+}
+
+#[derive(Conf)]
+#[conf(env_prefix = "APP_")]
+pub struct AppConfig {
+    #[arg(short, long)]
+    pub verbose: bool,
+    #[arg(repeat, long, env)]
+    pub peers: Vec<String>,
+    #[arg(long, env)]
+    pub log_dir: Option<PathBuf>,
+    #[arg(long, default_value = "x")]
+    pub opaque: Opaque,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(flatten, prefix)]
+    pub replica: Option<DbConfig>,
+    #[conf(subcommands)]
+    pub command: Option<Commands>,
+}
+
+fn parse(args: &[&str], env: &[(&str, &str)]) -> AppConfig {
+    AppConfig::conf_builder()
+        .args(args.iter().copied())
+        .env(env.iter().copied())
+        .try_parse()
+        .unwrap()
+}
+
+#[test]
+fn test_diff_unchanged() {
+    let a = parse(&["."], &[("APP_DB_URL", "postgres://a")]);
+    let b = parse(&["."], &[("APP_DB_URL", "postgres://a")]);
+    assert_eq!(a.diff(&b), vec![]);
+}
+
+#[test]
+fn test_diff_fields() {
+    let a = parse(
+        &[".", "--peers=a", "--opaque=1"],
+        &[
+            ("APP_DB_URL", "postgres://a"),
+            ("APP_DB_PASSWORD", "hunter2"),
+        ],
+    );
+    let b = parse(
+        &[".", "-v", "--peers=a", "--peers=b", "--opaque=2"],
+        &[
+            ("APP_DB_URL", "postgres://b"),
+            ("APP_DB_PASSWORD", "hunter3"),
+            ("APP_LOG_DIR", "/var/log"),
+        ],
+    );
+    let changes = a.diff(&b);
+    let ids = changes
+        .iter()
+        .map(|change| change.id.as_str())
+        .collect::<Vec<_>>();
+    // Changes to the opaque field can't be detected
+    assert_eq!(
+        ids,
+        vec!["verbose", "peers", "log_dir", "db.url", "db.password"]
+    );
+
+    assert_eq!(changes[0].switch.as_deref(), Some("--verbose"));
+    assert_eq!(changes[0].old, ReportValue::Bool(false));
+    assert_eq!(changes[0].new, ReportValue::Bool(true));
+
+    assert_eq!(changes[1].env.as_deref(), Some("APP_PEERS"));
+    assert_eq!(changes[1].old, ReportValue::List(vec_str(["a"])));
+    assert_eq!(changes[1].new, ReportValue::List(vec_str(["a", "b"])));
+
+    // PathBuf only implements Debug
+    assert_eq!(changes[2].old, ReportValue::Unset);
+    assert_eq!(changes[2].new, ReportValue::String("\"/var/log\"".into()));
+
+    // Flattened options have prefixed switches and env vars
+    assert_eq!(changes[3].switch.as_deref(), Some("--db-url"));
+    assert_eq!(changes[3].env.as_deref(), Some("APP_DB_URL"));
+    assert_eq!(
+        changes[3].to_string(),
+        "db.url: \"postgres://a\" -> \"postgres://b\""
+    );
+
+    // Secrets are redacted
+    assert_eq!(changes[4].old, ReportValue::Redacted);
+    assert_eq!(changes[4].new, ReportValue::Redacted);
+    assert!(!format!("{changes:?}").contains("hunter"));
+}
+
+#[test]
+fn test_diff_flatten_optional() {
+    let a = parse(&["."], &[("APP_DB_URL", "postgres://a")]);
+    let b = parse(
+        &[".", "--replica-url", "postgres://r"],
+        &[("APP_DB_URL", "postgres://a")],
+    );
+    let changes = a.diff(&b);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].id, "replica.url");
+    assert_eq!(changes[0].switch.as_deref(), Some("--replica-url"));
+    assert_eq!(changes[0].old, ReportValue::Unset);
+    assert_eq!(changes[0].new, ReportValue::String("postgres://r".into()));
+
+    // Going the other way, options become unset
+    let changes = b.diff(&a);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].new, ReportValue::Unset);
+}
+
+#[test]
+fn test_diff_subcommands() {
+    let env = [("APP_DB_URL", "postgres://a")];
+    let run1 = parse(&[".", "run"], &env);
+    let run2 = parse(&[".", "run", "--port", "9090"], &env);
+    let check = parse(&[".", "check", "--strict"], &env);
+
+    let changes = run1.diff(&run2);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].command, "run");
+    assert_eq!(changes[0].id, "port");
+    assert_eq!(changes[0].to_string(), "run port: \"8080\" -> \"9090\"");
+
+    // Changing the subcommand is a change, and so are the options of both subcommands
+    let changes = run1.diff(&check);
+    let rendered = changes.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        rendered,
+        vec![
+            "command: \"run\" -> \"check\"",
+            "run port: \"8080\" -> <unset>",
+            "check strict: <unset> -> true",
+        ]
+    );
+}
+
+#[derive(Conf)]
+pub struct SecretConfig {
+    #[arg(env)]
+    pub api_key: Secret<String>,
+    #[arg(env)]
+    pub backup_key: Option<Secret<String>>,
+    #[arg(repeat, env)]
+    pub tokens: Vec<Secret<String>>,
+}
+
+#[test]
+fn test_diff_secret_type() {
+    let a = SecretConfig::try_parse_from(["."], [("API_KEY", "hunter2")]).unwrap();
+    let b = SecretConfig::try_parse_from(["."], [("API_KEY", "hunter3")]).unwrap();
+    let changes = a.diff(&b);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].to_string(), "api_key: [REDACTED] -> [REDACTED]");
+    assert_eq!(a.diff(&a), vec![]);
+
+    let c = SecretConfig::try_parse_from(
+        ["."],
+        [
+            ("API_KEY", "hunter2"),
+            ("BACKUP_KEY", "hunter4"),
+            ("TOKENS", "a,b"),
+        ],
+    )
+    .unwrap();
+    let changes = a.diff(&c);
+    assert_eq!(
+        changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        vec![
+            "backup_key: <unset> -> [REDACTED]",
+            "tokens: [REDACTED] -> [REDACTED]",
+        ]
+    );
+    assert_eq!(c.diff(&c), vec![]);
+
+    let d = SecretConfig::try_parse_from(
+        ["."],
+        [
+            ("API_KEY", "hunter2"),
+            ("BACKUP_KEY", "hunter4"),
+            ("TOKENS", "a,c"),
+        ],
+    )
+    .unwrap();
+    let changes = c.diff(&d);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].id, "tokens");
+}
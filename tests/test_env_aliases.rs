@@ -249,3 +249,51 @@ fn test_env_aliases2_parsing() {
     assert_eq!(result.b.required, "5");
     assert_eq!(result.c.required, "7");
 }
+
+/// Test if env aliases work on repeat options
+#[derive(Conf, Debug)]
+struct TestEnvAliasesRepeat {
+    #[conf(repeat, long, env, env_aliases = ["PEER_LIST"])]
+    peers: Vec<String>,
+}
+
+#[test]
+fn test_env_aliases_repeat() {
+    let opts = TestEnvAliasesRepeat::get_program_options().unwrap();
+    assert_eq!(&opts[0].env_aliases[..], &[Cow::Borrowed("PEER_LIST")]);
+    assert!(opts[0].aliases.is_empty());
+
+    let result = TestEnvAliasesRepeat::try_parse_from::<&str, &str, &str>(
+        vec!["."],
+        vec![("PEER_LIST", "a,b")],
+    )
+    .unwrap();
+    assert_eq!(result.peers, vec!["a", "b"]);
+}
+
+/// Test if env aliases work on flags
+#[derive(Conf, Debug)]
+struct TestEnvAliasesFlag {
+    #[conf(long, env, env_aliases = ["VERBOSE_LOGS"])]
+    verbose: bool,
+}
+
+#[test]
+fn test_env_aliases_flag() {
+    let result = TestEnvAliasesFlag::try_parse_from::<&str, &str, &str>(vec!["."], vec![]).unwrap();
+    assert!(!result.verbose);
+
+    let result = TestEnvAliasesFlag::try_parse_from::<&str, &str, &str>(
+        vec!["."],
+        vec![("VERBOSE_LOGS", "true")],
+    )
+    .unwrap();
+    assert!(result.verbose);
+
+    let result = TestEnvAliasesFlag::try_parse_from::<&str, &str, &str>(
+        vec!["."],
+        vec![("VERBOSE", "true")],
+    )
+    .unwrap();
+    assert!(result.verbose);
+}
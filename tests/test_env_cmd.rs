@@ -0,0 +1,154 @@
+#![cfg(unix)]
+
+mod common;
+use common::*;
+
+use conf::{report::ValueSource, Conf};
+use std::time::Duration;
+
+#[derive(Conf, Debug)]
+struct Config {
+    #[arg(long, env, env_cmd)]
+    db_url: Option<String>,
+    #[arg(env, env_cmd = "API_KEY_COMMAND", secret)]
+    api_key: String,
+    #[arg(env, env_cmd)]
+    port: Option<u16>,
+    #[arg(repeat, env, env_cmd)]
+    peers: Vec<String>,
+}
+
+#[derive(Conf, Debug)]
+#[allow(unused)]
+struct Outer {
+    #[conf(flatten, prefix)]
+    inner: Config,
+}
+
+#[test]
+fn test_env_cmd_program_options() {
+    let opts = Config::get_program_options().unwrap();
+    assert_eq!(opts[0].env_cmd.as_deref(), Some("DB_URL_CMD"));
+    assert_eq!(opts[1].env_cmd.as_deref(), Some("API_KEY_COMMAND"));
+    assert_eq!(opts[3].env_cmd.as_deref(), Some("PEERS_CMD"));
+
+    let opts = Outer::get_program_options().unwrap();
+    assert_eq!(opts[0].env_cmd.as_deref(), Some("INNER_DB_URL_CMD"));
+}
+
+#[test]
+fn test_env_cmd_parse() {
+    let (config, sources) = Config::conf_builder()
+        .args(["."])
+        .env([
+            ("DB_URL_CMD", "echo postgres://localhost"),
+            ("API_KEY_COMMAND", "printf '%s\\n' 'sekrit value'"),
+            ("PEERS_CMD", "echo a,b"),
+        ])
+        .try_parse_with_sources()
+        .unwrap();
+    assert_eq!(config.db_url.as_deref(), Some("postgres://localhost"));
+    assert_eq!(config.api_key, "sekrit value");
+    assert_eq!(config.port, None);
+    assert_eq!(config.peers, vec!["a", "b"]);
+    assert_eq!(
+        sources.get("db_url"),
+        Some(&ValueSource::EnvCmd("DB_URL_CMD".into()))
+    );
+
+    // Args and env take precedence, and then the command is not run
+    let config = Config::try_parse_from(
+        [".", "--db-url=foo"],
+        [
+            ("DB_URL_CMD", "false"),
+            ("API_KEY", "abc"),
+            ("API_KEY_COMMAND", "false"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(config.db_url.as_deref(), Some("foo"));
+    assert_eq!(config.api_key, "abc");
+}
+
+#[test]
+fn test_env_cmd_output_not_reported() {
+    let (_config, report) = Config::conf_builder()
+        .args(["."])
+        .env([
+            ("API_KEY", "abc"),
+            ("DB_URL_CMD", "echo postgres://localhost"),
+        ])
+        .try_parse_with_report()
+        .unwrap();
+    assert_eq!(
+        report.get("db_url").unwrap().value,
+        conf::report::ReportValue::Redacted
+    );
+}
+
+#[test]
+fn test_env_cmd_errors() {
+    assert_error_contains_text!(
+        Config::try_parse_from(
+            ["."],
+            [
+                ("API_KEY", "abc"),
+                ("DB_URL_CMD", "sh -c 'echo sekrit; exit 3'"),
+                ("PORT_CMD", "this-command-does-not-exist"),
+            ]
+        ),
+        [
+            "An env command failed",
+            "when running command from env 'DB_URL_CMD': 'sh' failed (exit status: 3)",
+            "when running command from env 'PORT_CMD': \n    could not run 'this-command-does-not-exist'",
+        ],
+        not["sekrit"]
+    );
+
+    // Value parser errors don't show the output of the command
+    assert_error_contains_text!(
+        Config::try_parse_from(["."], [("API_KEY", "abc"), ("PORT_CMD", "echo 80x80")]),
+        [
+            "Invalid value",
+            "when parsing command from env 'PORT_CMD' value"
+        ],
+        not["80x80"]
+    );
+
+    assert_error_contains_text!(
+        Config::try_parse_from(["."], [("API_KEY", "abc"), ("PORT_CMD", "echo '80")]),
+        ["An env command failed", "unterminated quote"]
+    );
+}
+
+#[test]
+fn test_env_cmd_timeout() {
+    assert_error_contains_text!(
+        Config::conf_builder()
+            .args(["."])
+            .env([("API_KEY", "abc"), ("DB_URL_CMD", "sleep 10")])
+            .env_cmd_timeout(Duration::from_millis(100))
+            .try_parse(),
+        ["'sleep' timed out after 100ms, and was killed"]
+    );
+
+    // The command exits, but a process it started keeps its output open
+    let start = std::time::Instant::now();
+    assert_error_contains_text!(
+        Config::conf_builder()
+            .args(["."])
+            .env([("API_KEY", "abc"), ("DB_URL_CMD", "sh -c 'sleep 10 &'")])
+            .env_cmd_timeout(Duration::from_millis(100))
+            .try_parse(),
+        ["'sh' did not close its output within 100ms"]
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_env_cmd_help() {
+    let err = Config::try_parse_from([".", "--help"], [("DB_URL_CMD", "false")])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("[env command DB_URL_CMD]"), "{err}");
+}
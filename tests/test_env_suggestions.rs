@@ -0,0 +1,105 @@
+#![allow(unused)]
+
+mod common;
+use common::*;
+
+use conf::Conf;
+
+#[derive(Conf, Debug)]
+#[conf(env_prefix = "DB_")]
+struct DbConfig {
+    #[arg(env)]
+    max_connections: u32,
+    #[arg(env)]
+    url: String,
+    #[arg(env)]
+    uri: Option<String>,
+}
+
+#[test]
+fn test_env_suggestion_misspelled() {
+    assert_error_contains_text!(
+        DbConfig::try_parse_from(
+            ["."],
+            [("DB_MAX_CONNECTION", "50"), ("DB_URL", "postgres://")]
+        ),
+        [
+            "env 'DB_MAX_CONNECTIONS' must be provided",
+            "note: env 'DB_MAX_CONNECTION' is set, did you mean 'DB_MAX_CONNECTIONS'?"
+        ]
+    );
+
+    assert_error_contains_text!(
+        DbConfig::try_parse_from(
+            ["."],
+            [("db_max_connections", "50"), ("DB_URL", "postgres://")]
+        ),
+        ["note: env 'db_max_connections' is set, did you mean 'DB_MAX_CONNECTIONS'?"]
+    );
+
+    let err = DbConfig::try_parse_from(
+        ["."],
+        [("DB_MAX_CONECTION", "50"), ("DB_URL", "postgres://")],
+    )
+    .unwrap_err();
+    let item = err.items().next().unwrap();
+    assert_eq!(
+        item.message,
+        "env 'DB_MAX_CONNECTIONS' must be provided\n  note: env 'DB_MAX_CONECTION' is set, did you mean 'DB_MAX_CONNECTIONS'?"
+    );
+}
+
+#[test]
+fn test_env_suggestion_not_unrelated() {
+    // Different prefix
+    assert_error_contains_text!(
+        DbConfig::try_parse_from(
+            ["."],
+            [("XB_MAX_CONNECTIONS", "50"), ("DB_URL", "postgres://")]
+        ),
+        ["env 'DB_MAX_CONNECTIONS' must be provided"],
+        not["note:"]
+    );
+
+    // Too different
+    assert_error_contains_text!(
+        DbConfig::try_parse_from(["."], [("DB_CONNECTIONS", "50"), ("DB_URL", "postgres://")]),
+        ["env 'DB_MAX_CONNECTIONS' must be provided"],
+        not["note:"]
+    );
+
+    // DB_URI is read by another option, so it is not a misspelling of DB_URL
+    assert_error_contains_text!(
+        DbConfig::try_parse_from(
+            ["."],
+            [("DB_MAX_CONNECTIONS", "50"), ("DB_URI", "postgres://")]
+        ),
+        ["env 'DB_URL' must be provided"],
+        not["note:"]
+    );
+}
+
+#[derive(Conf, Debug)]
+struct UnprefixedConfig {
+    #[arg(env = "DB_MAX_CONNECTIONS")]
+    max_connections: u32,
+}
+
+#[test]
+fn test_env_suggestion_misspelled_prefix() {
+    // Without an env prefix, any env var is considered, so a typo in the leading segment is found
+    assert_error_contains_text!(
+        UnprefixedConfig::try_parse_from(["."], [("BD_MAX_CONNECTIONS", "50")]),
+        ["note: env 'BD_MAX_CONNECTIONS' is set, did you mean 'DB_MAX_CONNECTIONS'?"]
+    );
+
+    // With an env prefix, env vars without the prefix are not considered
+    assert_error_contains_text!(
+        DbConfig::try_parse_from(
+            ["."],
+            [("BD_MAX_CONNECTIONS", "50"), ("DB_URL", "postgres://")]
+        ),
+        ["env 'DB_MAX_CONNECTIONS' must be provided"],
+        not["note:"]
+    );
+}
@@ -0,0 +1,140 @@
+#![allow(unused)]
+
+use conf::{
+    report::{ReportValue, ValueSource},
+    Conf, ErrorFormat, ErrorKind,
+};
+
+#[derive(Conf, Debug)]
+struct DbConfig {
+    #[arg(long, env)]
+    url: String,
+    #[arg(long, env)]
+    pool_size: Option<u32>,
+}
+
+#[derive(Conf, Debug)]
+#[conf(one_of_fields(a, b))]
+struct Config {
+    #[conf(flatten, prefix)]
+    db: DbConfig,
+    #[arg(long, env)]
+    port: u16,
+    #[arg(long)]
+    a: bool,
+    #[arg(long)]
+    b: bool,
+    #[arg(env, secret)]
+    password: Option<u32>,
+}
+
+#[test]
+fn test_error_items_single() {
+    let err = Config::try_parse_from(
+        [".", "--a", "--port", "80"],
+        [("DB_URL", "x"), ("DB_POOL_SIZE", "many")],
+    )
+    .unwrap_err();
+
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items.len(), 1);
+    let item = items[0];
+    assert_eq!(item.kind, ErrorKind::InvalidValue);
+    assert_eq!(item.id.as_deref(), Some("db.pool_size"));
+    assert_eq!(item.switch.as_deref(), Some("--db-pool-size"));
+    assert_eq!(item.env.as_deref(), Some("DB_POOL_SIZE"));
+    assert_eq!(item.source, Some(ValueSource::Env("DB_POOL_SIZE".into())));
+    assert_eq!(
+        item.message,
+        "when parsing env 'DB_POOL_SIZE' value 'many': invalid digit found in string"
+    );
+    assert_eq!(item.to_string(), item.message);
+
+    // The printed error is unchanged
+    assert!(err.to_string().contains("Invalid value"));
+    assert_eq!(err.exit_code(), 2);
+}
+
+#[test]
+fn test_error_items_multiple() {
+    let err =
+        Config::try_parse_from([".", "--a", "--port", "x"], [("PASSWORD", "hunter2")]).unwrap_err();
+
+    let mut items = err.items().collect::<Vec<_>>();
+    items.sort_by_key(|item| (format!("{:?}", item.kind), item.id.clone()));
+    assert_eq!(items.len(), 3);
+
+    assert_eq!(items[0].kind, ErrorKind::InvalidValue);
+    assert_eq!(items[0].id.as_deref(), Some("password"));
+    assert_eq!(items[0].source, Some(ValueSource::Env("PASSWORD".into())));
+    assert!(!items[0].message.contains("hunter2"));
+
+    assert_eq!(items[1].kind, ErrorKind::InvalidValue);
+    assert_eq!(items[1].id.as_deref(), Some("port"));
+    assert_eq!(items[1].switch.as_deref(), Some("--port"));
+    assert_eq!(items[1].env.as_deref(), Some("PORT"));
+    assert_eq!(items[1].source, Some(ValueSource::Args));
+
+    assert_eq!(items[2].kind, ErrorKind::MissingRequired);
+    assert_eq!(items[2].id.as_deref(), Some("db.url"));
+    assert_eq!(items[2].env.as_deref(), Some("DB_URL"));
+    assert_eq!(items[2].source, None);
+}
+
+#[test]
+fn test_error_items_constraint() {
+    let err =
+        Config::try_parse_from([".", "--a", "--b", "--port", "80"], [("DB_URL", "x")]).unwrap_err();
+
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].kind, ErrorKind::TooManyArguments);
+    assert_eq!(items[0].id, None);
+    assert_eq!(items[0].source, None);
+    assert_eq!(items[0].constraint_ids, vec!["a", "b"]);
+    assert!(items[0].message.contains("'--a'"), "{}", items[0].message);
+}
+
+#[test]
+fn test_error_items_clap() {
+    let err = Config::try_parse_from([".", "--nope"], [("DB_URL", "x")]).unwrap_err();
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].kind, ErrorKind::Args);
+    assert!(items[0].message.contains("--nope"), "{}", items[0].message);
+
+    let err = Config::try_parse_from([".", "--help"], [("DB_URL", "x")]).unwrap_err();
+    assert_eq!(err.exit_code(), 0);
+    assert_eq!(err.items().count(), 0);
+}
+
+#[test]
+fn test_error_items_json() {
+    let err = Config::try_parse_from(
+        [".", "--a", "--port", "80"],
+        [("DB_URL", "x"), ("PASSWORD", "hunter2")],
+    )
+    .unwrap_err();
+
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items[0].value, Some(ReportValue::Redacted));
+    assert_eq!(
+        err.to_json(),
+        r#"{"kind":"invalid_value","id":"password","switch":null,"env":"PASSWORD","source":{"type":"env","name":"PASSWORD"},"value":"[REDACTED]","constraint_ids":[],"message":"when parsing env 'PASSWORD' value: invalid digit found in string"}
+"#
+    );
+
+    let err =
+        Config::try_parse_from([".", "--a", "--b", "--port", "80"], [("DB_URL", "x")]).unwrap_err();
+    assert!(err
+        .to_json()
+        .starts_with(r#"{"kind":"too_many_arguments","id":null,"switch":null,"env":null,"source":null,"value":null,"constraint_ids":["a","b"],"message":"Too many arguments"#));
+
+    let err = Config::conf_builder()
+        .args([".", "--help"])
+        .env([("DB_URL", "x")])
+        .error_format(ErrorFormat::Json)
+        .try_parse()
+        .unwrap_err();
+    assert_eq!(err.to_json(), "");
+}
@@ -26,7 +26,7 @@ fn test_subsystem_options_help() {
     let opts = SubsystemOptions::get_program_options().unwrap();
 
     let env = Default::default();
-    let parser = Parser::new(parser_config, &opts, &[], &env).unwrap();
+    let parser = Parser::new(parser_config, opts, &[], &env).unwrap();
 
     let clap_help = parser.render_clap_help();
     let expected = &"
@@ -38,6 +38,7 @@ Options:
                              [env MY_PARAM=]
       --my-list <my_list>    Values description
                              [env MY_LIST=]
+      --help-env             Print help for environment variables, and exit
   -h, --help                 Print help
 "[1..];
     assert_multiline_eq!(&clap_help, expected);
@@ -69,7 +70,7 @@ fn test_other_subsystem_options_help() {
     let opts = OtherSubsystemOptions::get_program_options().unwrap();
 
     let env = Default::default();
-    let parser = Parser::new(parser_config, &opts, &[], &env).unwrap();
+    let parser = Parser::new(parser_config, opts, &[], &env).unwrap();
 
     let clap_help = parser.render_clap_help();
     let expected = &"
@@ -78,13 +79,14 @@ Launches other subsystem
 Usage: . [OPTIONS]
 
 Options:
-  -a           Alpha
-  -b, --beta   Beta
-  -g           Gamma
-               [env GAMMA=]
-      --delta  Delta
-               [env DELTA=]
-  -h, --help   Print help
+  -a              Alpha
+  -b, --beta      Beta
+  -g              Gamma
+                  [env GAMMA=]
+      --delta     Delta
+                  [env DELTA=]
+      --help-env  Print help for environment variables, and exit
+  -h, --help      Print help
 "[1..];
     assert_multiline_eq!(&clap_help, expected);
 }
@@ -131,7 +133,7 @@ fn test_system_options_help() {
     let opts = SystemOptions::get_program_options().unwrap();
 
     let env = Default::default();
-    let parser = Parser::new(parser_config, &opts, &[], &env).unwrap();
+    let parser = Parser::new(parser_config, opts, &[], &env).unwrap();
 
     let clap_help = parser.render_clap_help();
     let expected = &"
@@ -160,6 +162,7 @@ Options:
                                                  [env GAMMA=]
       --delta                                    Delta
                                                  [env DELTA=]
+      --help-env                                 Print help for environment variables, and exit
   -h, --help                                     Print help
 
 Environment variables:
@@ -169,3 +172,203 @@ Environment variables:
 "[1..];
     assert_multiline_eq!(&clap_help, expected);
 }
+
+#[derive(Conf)]
+struct PoolOptions {
+    /// Max connections
+    #[conf(long, env)]
+    max_size: u32,
+
+    /// Idle timeout
+    #[conf(env)]
+    idle_timeout: Option<String>,
+}
+
+#[derive(Conf)]
+struct DbOptions {
+    /// Database url
+    #[conf(long, env)]
+    url: String,
+
+    /// Connection pool.
+    #[conf(flatten, prefix, help_heading)]
+    pool: PoolOptions,
+}
+
+#[derive(Conf)]
+struct GroupedSystemOptions {
+    /// Verbose
+    #[conf(short, long)]
+    verbose: bool,
+
+    /// Required value
+    #[conf(env)]
+    required: String,
+
+    #[conf(flatten, prefix, help_heading = "Database")]
+    db: DbOptions,
+
+    #[conf(flatten, prefix = "replica", help_heading = "Replica")]
+    replica: Option<DbOptions>,
+}
+
+#[test]
+fn test_help_headings() {
+    let parser_config = GroupedSystemOptions::get_parser_config().unwrap();
+    let opts = GroupedSystemOptions::get_program_options().unwrap();
+
+    let env = Default::default();
+    let parser = Parser::new(parser_config, opts, &[], &env).unwrap();
+
+    let clap_help = parser.render_clap_help();
+    let expected = &"
+Usage: . [OPTIONS]
+
+Options:
+  -v, --verbose   Verbose
+      --help-env  Print help for environment variables, and exit
+  -h, --help      Print help
+
+Database:
+      --db-url <db.url>  Database url
+                         [env DB_URL=]
+
+Database / Connection pool:
+      --db-pool-max-size <db.pool.max_size>  Max connections
+                                             [env DB_POOL_MAX_SIZE=]
+
+Replica:
+      --replica-url <replica.url>  Database url
+                                   [env REPLICA_URL=]
+
+Replica / Connection pool:
+      --replica-pool-max-size <replica.pool.max_size>  Max connections
+                                                       [env REPLICA_POOL_MAX_SIZE=]
+
+Environment variables:
+      <required>
+          Required value
+          [env: REQUIRED=]
+
+Environment variables (Database / Connection pool):
+      <db.pool.idle_timeout>
+          Idle timeout
+          [env: DB_POOL_IDLE_TIMEOUT=]
+
+Environment variables (Replica / Connection pool):
+      <replica.pool.idle_timeout>
+          Idle timeout
+          [env: REPLICA_POOL_IDLE_TIMEOUT=]
+"[1..];
+    assert_multiline_eq!(&clap_help, expected);
+}
+
+#[derive(Conf)]
+struct TuningOptions {
+    /// Batch size
+    #[conf(long, env)]
+    batch_size: Option<u32>,
+
+    /// Spin count
+    #[conf(env)]
+    spin_count: Option<u32>,
+}
+
+#[derive(Conf)]
+struct HiddenOptions {
+    /// Listen address.
+    ///
+    /// This is the address that the server binds to. Use 0.0.0.0 to listen on all interfaces.
+    #[conf(long, env)]
+    listen_addr: String,
+
+    /// Debug knob
+    #[conf(long, env, hide)]
+    debug_knob: bool,
+
+    /// Api url
+    #[conf(long, env, hide_env_value)]
+    api_url: Option<String>,
+
+    /// Region.
+    ///
+    /// The region that the server runs in.
+    #[conf(env)]
+    region: Option<String>,
+
+    #[conf(flatten, prefix, hide)]
+    tuning: TuningOptions,
+}
+
+#[test]
+fn test_hidden_options_and_long_help() {
+    let parser_config = HiddenOptions::get_parser_config().unwrap();
+    let opts = HiddenOptions::get_program_options().unwrap();
+
+    let env = Default::default();
+    let parser = Parser::new(parser_config, opts, &[], &env).unwrap();
+
+    let clap_help = parser.render_clap_help();
+    let expected = &"
+Usage: . [OPTIONS]
+
+Options:
+      --listen-addr <listen_addr>  Listen address.
+                                   [env LISTEN_ADDR=]
+      --api-url <api_url>          Api url
+                                   [env API_URL]
+      --help-env                   Print help for environment variables, and exit
+  -h, --help                       Print help (see more with '--help')
+
+Environment variables:
+      <region>
+          Region.
+          [env: REGION=]
+"[1..];
+    assert_multiline_eq!(&clap_help, expected);
+
+    let clap_help = parser.render_clap_long_help();
+    let expected = &"
+Usage: . [OPTIONS]
+
+Options:
+      --listen-addr <listen_addr>
+          Listen address.
+
+          This is the address that the server binds to. Use 0.0.0.0 to listen on all interfaces.
+          [env LISTEN_ADDR=]
+
+      --api-url <api_url>
+          Api url
+          [env API_URL]
+
+      --help-env
+          Print help for environment variables, and exit
+
+  -h, --help
+          Print help (see a summary with '-h')
+
+Environment variables:
+      <region>
+          Region.
+
+          The region that the server runs in.
+          [env: REGION=]
+"[1..];
+    assert_multiline_eq!(&clap_help, expected);
+
+    // Hidden options can still be used
+    let config = HiddenOptions::try_parse_from(
+        [
+            ".",
+            "--listen-addr=x",
+            "--debug-knob",
+            "--tuning-batch-size=3",
+        ],
+        [("TUNING_SPIN_COUNT", "5")],
+    )
+    .unwrap();
+    assert!(config.debug_knob);
+    assert_eq!(config.tuning.batch_size, Some(3));
+    assert_eq!(config.tuning.spin_count, Some(5));
+}
@@ -0,0 +1,164 @@
+#![allow(dead_code)]
+
+use conf::{Conf, Subcommands};
+
+mod common;
+use common::*;
+
+#[derive(Conf)]
+struct DbOptions {
+    /// Database url
+    #[conf(long, env, env_aliases = ["ADDR"])]
+    url: String,
+
+    /// Database password
+    #[conf(env, secret)]
+    password: String,
+
+    /// Max connections
+    #[conf(env, default_value = "10")]
+    max_size: u32,
+}
+
+#[derive(Conf)]
+struct RunOptions {
+    /// Port to listen on
+    #[conf(long, env, default_value = "8080")]
+    port: u16,
+}
+
+#[derive(Subcommands)]
+enum Commands {
+    Run(RunOptions),
+}
+
+#[derive(Conf)]
+struct Config {
+    /// Verbose
+    #[conf(short, long, env)]
+    verbose: bool,
+
+    /// Internal knob
+    #[conf(env, hide)]
+    knob: Option<String>,
+
+    #[conf(flatten, prefix)]
+    db: DbOptions,
+
+    #[conf(subcommands)]
+    command: Option<Commands>,
+}
+
+// Get the output that parsing these args and env produces
+fn help_output(args: &[&str], env: &[(&str, &str)]) -> String {
+    match Config::try_parse_from(args.iter().copied(), env.iter().copied()) {
+        Ok(_) => panic!("expected help output"),
+        Err(err) => {
+            assert_eq!(err.exit_code(), 0, "{err}");
+            err.to_string()
+        }
+    }
+}
+
+#[test]
+fn test_help_env() {
+    let expected = &"
+Environment variables:
+
+  DB_ADDR
+      Database url
+      [alias of DB_URL]
+      [switch: --db-url]
+      [current value: postgres://alias]
+      [required]
+
+  DB_MAX_SIZE
+      Max connections
+      [not set]
+      [default: 10]
+
+  DB_PASSWORD
+      Database password
+      [current value: <hidden>]
+      [required]
+      [secret]
+
+  DB_URL
+      Database url
+      [switch: --db-url]
+      [not set]
+      [required]
+
+  PORT
+      Port to listen on
+      [subcommand: run]
+      [switch: --port]
+      [not set]
+      [default: 8080]
+
+  VERBOSE
+      Verbose
+      [switch: -v, --verbose]
+      [current value: 1]
+"[1..];
+    // Required values don't need to be supplied
+    let output = help_output(
+        &[".", "--help-env"],
+        &[
+            ("DB_ADDR", "postgres://alias"),
+            ("DB_PASSWORD", "hunter2"),
+            ("VERBOSE", "1"),
+        ],
+    );
+    assert_multiline_eq!(&output, expected);
+}
+
+#[test]
+fn test_help_env_subcommand() {
+    let expected = &"
+Environment variables:
+
+  PORT
+      Port to listen on
+      [switch: --port]
+      [current value: 9090]
+      [default: 8080]
+"[1..];
+    let output = help_output(&[".", "run", "--help-env"], &[("PORT", "9090")]);
+    assert_multiline_eq!(&output, expected);
+}
+
+#[test]
+fn test_help_env_listed_in_help() {
+    let output = help_output(&[".", "--help"], &[]);
+    assert!(output.contains("--help-env"), "{output}");
+    assert!(
+        output.contains("Print help for environment variables, and exit"),
+        "{output}"
+    );
+}
+
+#[derive(Conf)]
+struct OwnHelpEnv {
+    #[conf(long)]
+    help_env: bool,
+}
+
+#[derive(Conf)]
+struct OwnHelpEnvAlias {
+    #[conf(long, aliases = ["help-env"])]
+    env_help: bool,
+}
+
+#[test]
+fn test_help_env_taken_by_option() {
+    // If an option already uses the switch, as its name or an alias, no --help-env flag is added
+    let config =
+        OwnHelpEnv::try_parse_from::<&str, &str, &str>(vec![".", "--help-env"], vec![]).unwrap();
+    assert!(config.help_env);
+
+    let config =
+        OwnHelpEnvAlias::try_parse_from::<&str, &str, &str>(vec![".", "--help-env"], vec![])
+            .unwrap();
+    assert!(config.env_help);
+}
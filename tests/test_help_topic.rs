@@ -0,0 +1,170 @@
+#![allow(dead_code)]
+
+use conf::{Conf, Subcommands};
+
+mod common;
+use common::*;
+
+#[derive(Conf)]
+struct PoolOptions {
+    /// Max connections
+    #[conf(long, env, default_value = "10")]
+    max_size: u32,
+
+    /// Idle timeout
+    #[conf(env)]
+    idle_timeout: Option<String>,
+}
+
+#[derive(Conf)]
+struct DbOptions {
+    /// Database url
+    #[conf(long, env, env_aliases = ["ADDR"])]
+    url: String,
+
+    /// Connection pool
+    #[conf(flatten, prefix, help_heading)]
+    pool: PoolOptions,
+}
+
+#[derive(Conf)]
+struct RunOptions {
+    /// Port to listen on
+    #[conf(long, env, default_value = "8080")]
+    port: u16,
+}
+
+#[derive(Subcommands)]
+enum Commands {
+    Run(RunOptions),
+}
+
+#[derive(Conf)]
+struct Config {
+    /// Verbose
+    #[conf(short, long)]
+    verbose: bool,
+
+    /// Debug token
+    #[conf(long, env, hide)]
+    debug_token: Option<String>,
+
+    #[conf(flatten, prefix, help_heading = "Database")]
+    db: DbOptions,
+
+    #[conf(subcommands)]
+    command: Option<Commands>,
+}
+
+// Get the output that parsing these args and env produces
+fn help_output(args: &[&str], env: &[(&str, &str)]) -> String {
+    match Config::try_parse_from(args.iter().copied(), env.iter().copied()) {
+        Ok(_) => panic!("expected help output"),
+        Err(err) => {
+            assert_eq!(err.exit_code(), 0, "{err}");
+            err.to_string()
+        }
+    }
+}
+
+#[test]
+fn test_help_topic_option() {
+    let expected = &"
+      --db-url <db.url>
+          Database url
+          [env: DB_URL=]
+          [env: DB_ADDR=postgres://alias]
+          [current source: env DB_ADDR]
+"[1..];
+    // By id, long switch, or env var
+    for topic in ["db.url", "--db-url", "db-url", "DB_URL", "DB_ADDR"] {
+        let output = help_output(&[".", "--help", topic], &[("DB_ADDR", "postgres://alias")]);
+        assert_multiline_eq!(&output, expected);
+    }
+
+    let output = help_output(
+        &[".", "--db-pool-max-size=5", "--help=db.pool.max_size"],
+        &[],
+    );
+    assert!(output.contains("[default: 10]"), "{output}");
+    assert!(output.contains("[current source: args]"), "{output}");
+
+    let output = help_output(&[".", "-h", "db.pool.max_size"], &[]);
+    assert!(
+        output.contains("[current source: default value]"),
+        "{output}"
+    );
+
+    let output = help_output(&[".", "-h", "-v"], &[]);
+    assert!(output.starts_with("  -v, --verbose"), "{output}");
+
+    let output = help_output(&[".", "-h", "db.pool.idle_timeout"], &[]);
+    assert!(output.contains("[current source: not set]"), "{output}");
+}
+
+#[test]
+fn test_help_topic_group() {
+    let expected = &"
+Options for 'db.pool':
+
+      --db-pool-max-size <db.pool.max_size>
+          Max connections
+          [env: DB_POOL_MAX_SIZE=]
+          [default: 10]
+
+      <db.pool.idle_timeout>
+          Idle timeout
+          [env: DB_POOL_IDLE_TIMEOUT=]
+"[1..];
+    let output = help_output(&[".", "--help", "db.pool"], &[]);
+    assert_multiline_eq!(&output, expected);
+
+    // By help heading
+    let output = help_output(&[".", "--help", "connection pool"], &[]);
+    assert!(output.contains("<db.pool.idle_timeout>"), "{output}");
+    assert!(!output.contains("<db.url>"), "{output}");
+
+    // Nested groups are included
+    let output = help_output(&[".", "--help", "db"], &[]);
+    assert!(output.contains("<db.url>"), "{output}");
+    assert!(output.contains("<db.pool.idle_timeout>"), "{output}");
+    let output = help_output(&[".", "--help", "Database"], &[]);
+    assert!(output.contains("<db.url>"), "{output}");
+    assert!(output.contains("<db.pool.idle_timeout>"), "{output}");
+}
+
+#[test]
+fn test_help_topic_subcommand() {
+    // The help of a subcommand
+    let output = help_output(&[".", "--help", "run"], &[]);
+    assert!(output.contains("Usage: conf run [OPTIONS]"), "{output}");
+
+    // Topics of a subcommand
+    let output = help_output(&[".", "run", "--help", "port"], &[("PORT", "9090")]);
+    assert!(output.contains("[env: PORT=9090]"), "{output}");
+    assert!(output.contains("[current source: env PORT]"), "{output}");
+}
+
+#[test]
+fn test_help_topic_unknown() {
+    // An unknown topic shows the usual help, as if there was no topic
+    let full_help = help_output(&[".", "--help"], &[]);
+    assert!(full_help.starts_with("Usage:"), "{full_help}");
+    assert_eq!(help_output(&[".", "--help", "db.ulr"], &[]), full_help);
+    assert_eq!(help_output(&[".", "--help=db.ulr"], &[]), full_help);
+    assert_eq!(help_output(&[".", "--help", "--quiet"], &[]), full_help);
+    assert_eq!(
+        help_output(&[".", "-h", "foo"], &[]),
+        help_output(&[".", "-h"], &[])
+    );
+}
+
+#[test]
+fn test_help_topic_hidden_option() {
+    // A hidden option is not a topic, just as it isn't in the usual help
+    let full_help = help_output(&[".", "--help"], &[]);
+    assert!(!full_help.contains("Debug token"), "{full_help}");
+    for topic in ["debug_token", "--debug-token", "debug-token", "DEBUG_TOKEN"] {
+        assert_eq!(help_output(&[".", "--help", topic], &[]), full_help);
+    }
+}
@@ -0,0 +1,127 @@
+use conf::{
+    introspect,
+    introspect::{OptionInfo, OptionKind},
+    Conf, Subcommands,
+};
+
+#[derive(Conf)]
+pub struct PoolConfig {
+    /// Max pool size
+    #[arg(long, env, default_value = "10")]
+    pub max_size: u32,
+}
+
+#[derive(Conf)]
+pub struct DbConfig {
+    /// Database url
+    #[arg(long, env, env_aliases = ["DATABASE_URL"])]
+    pub url: String,
+    #[arg(env, secret)]
+    pub password: String,
+    #[conf(flatten, prefix)]
+    pub pool: PoolConfig,
+}
+
+#[derive(Conf)]
+pub struct RunConfig {
+    #[arg(short, long, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Subcommands)]
+pub enum Commands {
+    Run(RunConfig),
+}
+
+/// My app
+#[derive(Conf)]
+#[conf(name = "my-app")]
+pub struct AppConfig {
+    /// Verbose output
+    #[arg(short, long, aliases = ["loud"])]
+    pub verbose: bool,
+    #[arg(repeat, long, env)]
+    pub peers: Vec<String>,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(flatten, prefix, help_prefix = "Replica:")]
+    pub replica: Option<DbConfig>,
+    #[conf(subcommands)]
+    pub command: Commands,
+}
+
+#[test]
+fn test_introspect() {
+    let info = introspect::<AppConfig>().unwrap();
+
+    assert_eq!(info.name, "my-app");
+    assert_eq!(info.description.as_deref(), Some("My app"));
+
+    let ids: Vec<&str> = info.options.iter().map(|opt| opt.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "verbose",
+            "peers",
+            "db.url",
+            "db.password",
+            "db.pool.max_size",
+            "replica.url",
+            "replica.password",
+            "replica.pool.max_size",
+        ]
+    );
+
+    let verbose: &OptionInfo = info.get_option("verbose").unwrap();
+    assert_eq!(verbose.kind, OptionKind::Flag);
+    assert_eq!(verbose.short, Some('v'));
+    assert_eq!(verbose.long.as_deref(), Some("verbose"));
+    assert_eq!(verbose.aliases, vec!["loud"]);
+    assert_eq!(verbose.env, None);
+    assert!(!verbose.is_required);
+    assert_eq!(verbose.description.as_deref(), Some("Verbose output"));
+    assert_eq!(verbose.group(), None);
+
+    let peers = info.get_option("peers").unwrap();
+    assert_eq!(peers.kind, OptionKind::Repeat);
+    assert_eq!(peers.env.as_deref(), Some("PEERS"));
+
+    let url = info.get_option("db.url").unwrap();
+    assert_eq!(url.kind, OptionKind::Parameter);
+    assert_eq!(url.long.as_deref(), Some("db-url"));
+    assert_eq!(url.env.as_deref(), Some("DB_URL"));
+    assert_eq!(url.env_aliases, vec!["DB_DATABASE_URL"]);
+    assert_eq!(
+        url.env_names().collect::<Vec<_>>(),
+        vec!["DB_URL", "DB_DATABASE_URL"]
+    );
+    assert!(url.is_required);
+    assert!(!url.is_secret);
+    assert_eq!(url.group(), Some("db"));
+
+    let password = info.get_option("db.password").unwrap();
+    assert!(password.is_secret);
+    assert_eq!(password.long, None);
+    assert_eq!(password.env.as_deref(), Some("DB_PASSWORD"));
+
+    let max_size = info.get_option("db.pool.max_size").unwrap();
+    assert_eq!(max_size.default_value.as_deref(), Some("10"));
+    assert_eq!(max_size.env.as_deref(), Some("DB_POOL_MAX_SIZE"));
+    assert!(!max_size.is_required);
+    assert_eq!(max_size.group(), Some("db.pool"));
+
+    // Options in an optional flattened group are not required
+    let replica_url = info.get_option("replica.url").unwrap();
+    assert!(!replica_url.is_required);
+    assert_eq!(
+        replica_url.description.as_deref(),
+        Some("Replica: Database url")
+    );
+
+    assert_eq!(info.subcommands.len(), 1);
+    let run = info.get_subcommand("run").unwrap();
+    let port = run.get_option("port").unwrap();
+    assert_eq!(port.short, Some('p'));
+    assert_eq!(port.default_value.as_deref(), Some("8080"));
+    assert!(run.subcommands.is_empty());
+}
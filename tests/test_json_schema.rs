@@ -0,0 +1,194 @@
+#![cfg(feature = "schema")]
+
+use conf::{Conf, ConfSerde, Subcommands};
+use serde_json::json;
+
+/// Database settings
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct DbConfig {
+    /// Database url
+    #[arg(long, env)]
+    pub url: String,
+    /// Number of retries
+    #[arg(long, env, default_value = "5")]
+    pub retries: u32,
+    #[arg(long, env, default_value = "1.5")]
+    #[conf(serde(rename = "backoff_secs"))]
+    pub backoff: f64,
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde(allow_unknown_fields))]
+pub struct RunConfig {
+    #[arg(long, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct MigrateConfig {
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct ValidateConfig {
+    #[arg(long)]
+    pub strict: bool,
+}
+
+#[derive(Subcommands, Debug)]
+#[conf(serde)]
+pub enum Commands {
+    Run(RunConfig),
+    Migrate(MigrateConfig),
+    #[conf(serde(rename = "migrate"))]
+    Validate(ValidateConfig),
+}
+
+/// My app
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct AppConfig {
+    /// Verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    #[arg(long, default_value = "info")]
+    pub log_level: String,
+    #[arg(long)]
+    #[conf(serde(skip))]
+    pub secret_key: Option<String>,
+    #[arg(repeat, long)]
+    pub peers: Vec<String>,
+    #[arg(repeat, long)]
+    pub ports: Vec<u16>,
+    #[arg(long, default_value = "10", serde(use_value_parser))]
+    pub timeout: u64,
+    #[arg(long)]
+    pub listen_addr: Option<std::net::SocketAddr>,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(subcommands)]
+    pub command: Commands,
+}
+
+#[test]
+fn test_json_schema() {
+    let schema = AppConfig::json_schema().unwrap();
+
+    let db_schema = json!({
+        "title": "DbConfig",
+        "description": "Database settings",
+        "type": "object",
+        "properties": {
+            "url": {
+                "type": "string",
+                "description": "Database url",
+            },
+            "retries": {
+                "type": "integer",
+                "description": "Number of retries",
+                "default": 5,
+            },
+            "backoff_secs": {
+                "type": "number",
+                "default": 1.5,
+            },
+        },
+        "additionalProperties": false,
+    });
+
+    assert_eq!(
+        schema,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "AppConfig",
+            "description": "My app",
+            "type": "object",
+            "properties": {
+                "verbose": {
+                    "type": "boolean",
+                    "description": "Verbose output",
+                    "default": false,
+                },
+                "log_level": {
+                    "type": "string",
+                    "default": "info",
+                },
+                "peers": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                },
+                "ports": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                },
+                "timeout": {
+                    "type": "string",
+                    "default": "10",
+                },
+                "listen_addr": {},
+                "db": db_schema,
+                "run": {
+                    "title": "RunConfig",
+                    "type": "object",
+                    "properties": {
+                        "port": {
+                            "type": "integer",
+                            "default": 8080,
+                        },
+                    },
+                },
+                "migrate": {
+                    "anyOf": [
+                        {
+                            "title": "MigrateConfig",
+                            "type": "object",
+                            "properties": {
+                                "dry_run": {
+                                    "type": "boolean",
+                                    "default": false,
+                                },
+                            },
+                            "additionalProperties": false,
+                        },
+                        {
+                            "title": "ValidateConfig",
+                            "type": "object",
+                            "properties": {
+                                "strict": {
+                                    "type": "boolean",
+                                    "default": false,
+                                },
+                            },
+                            "additionalProperties": false,
+                        },
+                    ],
+                },
+            },
+            "additionalProperties": false,
+        })
+    );
+}
+
+#[test]
+fn test_json_schema_subcommand_sections() {
+    let schema = AppConfig::json_schema().unwrap();
+    let validator = jsonschema::JSONSchema::compile(&schema).unwrap();
+
+    // Any of the subcommand sections may appear, since the sections of subcommands which are
+    // not selected are ignored
+    assert!(validator.is_valid(&json!({ "db": { "url": "x" } })));
+    assert!(validator.is_valid(&json!({ "run": { "port": 80 } })));
+    assert!(validator.is_valid(&json!({ "migrate": { "strict": true } })));
+    assert!(validator.is_valid(&json!({ "run": { "port": 80 }, "migrate": { "strict": true } })));
+
+    // Each section is still checked
+    assert!(!validator.is_valid(&json!({ "run": { "port": "80" }, "migrate": { "strict": true } })));
+
+    // Other constraints are also checked
+    assert!(!validator.is_valid(&json!({ "verbose": "yes" })));
+    assert!(!validator.is_valid(&json!({ "unknown": 1 })));
+}
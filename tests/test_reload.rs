@@ -0,0 +1,112 @@
+#![cfg(feature = "serde")]
+#![allow(unused)]
+
+use conf::{reload::ConfReloader, Conf, ConfBuilder, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct Config {
+    #[arg(long, env)]
+    listen_addr: String,
+    #[arg(long, env, default_value = "4")]
+    workers: u32,
+}
+
+// A fresh temporary directory for a test
+fn temp_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "conf_test_reload_{}_{test_name}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn load(builder: ConfBuilder<Config>, path: &Path) -> Result<Config, conf::Error> {
+    let name = path.display().to_string();
+    let text =
+        std::fs::read_to_string(path).map_err(|err| conf::Error::document_load(&name, err))?;
+    let doc: serde_json::Value =
+        serde_json::from_str(&text).map_err(|err| conf::Error::document_load(&name, err))?;
+    builder.doc(&name, doc).try_parse()
+}
+
+#[test]
+fn test_reload() {
+    let dir = temp_dir("reload");
+    let path = dir.join("config.json");
+    std::fs::write(&path, r#"{ "workers": 8 }"#).unwrap();
+
+    let path2 = path.clone();
+    let mut reloader = ConfReloader::new(["."], [("LISTEN_ADDR", "0.0.0.0:80")], move |builder| {
+        load(builder, &path2)
+    })
+    .unwrap();
+    assert_eq!(reloader.current().listen_addr, "0.0.0.0:80");
+    assert_eq!(reloader.current().workers, 8);
+
+    std::fs::write(&path, r#"{ "workers": 16 }"#).unwrap();
+    let config = reloader.reload().unwrap();
+    assert_eq!(config.workers, 16);
+    assert_eq!(reloader.current().workers, 16);
+
+    // A bad value doesn't replace the current config
+    std::fs::write(&path, r#"{ "workers": "many" }"#).unwrap();
+    let err = reloader.reload().unwrap_err();
+    assert_eq!(err.items().next().unwrap().kind, ErrorKind::Document);
+    assert_eq!(reloader.current().workers, 16);
+
+    // Neither does a file which can't be loaded
+    std::fs::remove_file(&path).unwrap();
+    let err = reloader.reload().unwrap_err();
+    let item = err.items().next().unwrap();
+    assert_eq!(item.kind, ErrorKind::Document);
+    assert!(item.message.starts_with("Loading "), "{}", item.message);
+    assert_eq!(reloader.current().workers, 16);
+}
+
+#[test]
+fn test_reload_poll() {
+    let dir = temp_dir("poll");
+    let path = dir.join("config.json");
+    std::fs::write(&path, r#"{ "workers": 8 }"#).unwrap();
+
+    let path2 = path.clone();
+    let mut reloader = ConfReloader::new(
+        [".", "--listen-addr", "0.0.0.0:80"],
+        [("WORKERS", "2")],
+        move |builder| load(builder, &path2),
+    )
+    .unwrap()
+    .watch(&path);
+    // The args and env are the same on every reload, documents have lower priority
+    assert_eq!(reloader.current().workers, 2);
+    assert!(reloader.poll().is_none());
+
+    std::fs::write(&path, r#"{ "listen_addr": "127.0.0.1:80" }"#).unwrap();
+    let config = reloader.poll().unwrap().unwrap();
+    assert_eq!(config.listen_addr, "0.0.0.0:80");
+    assert!(reloader.poll().is_none());
+
+    // A failed reload isn't retried until the file changes again
+    std::fs::write(&path, "{ not json").unwrap();
+    assert!(reloader.poll().unwrap().is_err());
+    assert!(reloader.poll().is_none());
+    assert_eq!(reloader.current().listen_addr, "0.0.0.0:80");
+
+    std::fs::write(&path, "{}").unwrap();
+    assert!(reloader.poll().unwrap().is_ok());
+}
+
+#[test]
+fn test_reload_initial_failure() {
+    let dir = temp_dir("initial_failure");
+    let path = dir.join("missing.json");
+
+    let result = ConfReloader::new(["."], [("LISTEN_ADDR", "0.0.0.0:80")], move |builder| {
+        load(builder, &path)
+    });
+    assert!(result.is_err());
+}
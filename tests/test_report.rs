@@ -0,0 +1,272 @@
+#![cfg(feature = "serde")]
+
+mod common;
+use common::*;
+
+use conf::{
+    report::{ReportValue, ValueSource},
+    Conf, Subcommands,
+};
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct DbConfig {
+    #[arg(long, env, env_aliases = ["DATABASE_URL"])]
+    pub url: String,
+    #[arg(env, secret)]
+    #[conf(serde(skip))]
+    pub password: Option<String>,
+    #[arg(long, env, default_value = "5")]
+    pub retries: u32,
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct RunConfig {
+    #[arg(long, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Subcommands, Debug)]
+#[conf(serde)]
+pub enum Commands {
+    Run(RunConfig),
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde, name = "my-app", print_config_flag)]
+pub struct AppConfig {
+    #[arg(short, long)]
+    pub verbose: bool,
+    #[arg(repeat, long, env)]
+    pub peers: Vec<String>,
+    #[arg(long)]
+    pub listen_addr: Option<String>,
+    #[arg(long, serde(use_value_parser))]
+    pub timeout: Option<u64>,
+    #[arg(env, secret)]
+    pub api_key: Option<String>,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(flatten, prefix)]
+    pub replica: Option<DbConfig>,
+    #[conf(subcommands)]
+    pub command: Option<Commands>,
+}
+
+#[test]
+fn test_report_sources() {
+    let (_app, report) = AppConfig::conf_builder()
+        .args(["my-app", "-v", "--peers=a", "--peers=b"])
+        .env([
+            ("DB_DATABASE_URL", "postgres://db"),
+            ("DB_PASSWORD", "hunter2"),
+        ])
+        .try_parse_with_report()
+        .unwrap();
+
+    assert_eq!(report.command, "my-app");
+    assert!(report.subcommand.is_none());
+
+    let verbose = report.get("verbose").unwrap();
+    assert_eq!(verbose.value, ReportValue::Bool(true));
+    assert_eq!(verbose.source, Some(ValueSource::Args));
+
+    let peers = report.get("peers").unwrap();
+    assert_eq!(peers.value, ReportValue::List(vec_str(["a", "b"])));
+    assert_eq!(peers.source, Some(ValueSource::Args));
+
+    let listen_addr = report.get("listen_addr").unwrap();
+    assert_eq!(listen_addr.value, ReportValue::Unset);
+    assert_eq!(listen_addr.source, None);
+
+    let url = report.get("db.url").unwrap();
+    assert_eq!(url.value, ReportValue::String("postgres://db".into()));
+    assert_eq!(url.source, Some(ValueSource::Env("DB_DATABASE_URL".into())));
+
+    let retries = report.get("db.retries").unwrap();
+    assert_eq!(retries.value, ReportValue::String("5".into()));
+    assert_eq!(retries.source, Some(ValueSource::Default));
+
+    // Options in an optional group which is not enabled have no value
+    let replica_url = report.get("replica.url").unwrap();
+    assert_eq!(replica_url.value, ReportValue::Unset);
+    assert_eq!(replica_url.source, None);
+}
+
+#[test]
+fn test_report_secrets_are_redacted() {
+    let (app, report) = AppConfig::conf_builder()
+        .args(["my-app"])
+        .env([
+            ("DB_URL", "postgres://db"),
+            ("DB_PASSWORD", "hunter2"),
+            ("API_KEY", "sekrit"),
+        ])
+        .try_parse_with_report()
+        .unwrap();
+    assert_eq!(app.api_key.as_deref(), Some("sekrit"));
+
+    let password = report.get("db.password").unwrap();
+    assert_eq!(password.value, ReportValue::Redacted);
+    assert_eq!(
+        password.source,
+        Some(ValueSource::Env("DB_PASSWORD".into()))
+    );
+
+    let api_key = report.get("api_key").unwrap();
+    assert_eq!(api_key.value, ReportValue::Redacted);
+
+    let text = report.to_string();
+    assert!(!text.contains("hunter2"));
+    assert!(!text.contains("sekrit"));
+    let json = report.to_json();
+    assert!(!json.contains("hunter2"));
+    assert!(!json.contains("sekrit"));
+}
+
+#[test]
+fn test_report_document_and_subcommand() {
+    let (_app, report) = AppConfig::conf_builder()
+        .args(["my-app", "run"])
+        .env([("DB_URL", "postgres://db")])
+        .doc(
+            "config.json",
+            serde_json::json!({
+                "listen_addr": "0.0.0.0:80",
+                "timeout": "30",
+                "db": { "retries": 7 },
+                "run": { "port": 9090 },
+            }),
+        )
+        .try_parse_with_report()
+        .unwrap();
+
+    // Document values that were deserialized directly have no string form
+    let listen_addr = report.get("listen_addr").unwrap();
+    assert_eq!(listen_addr.value, ReportValue::Opaque);
+    assert_eq!(
+        listen_addr.source,
+        Some(ValueSource::Document("config.json".into()))
+    );
+
+    let timeout = report.get("timeout").unwrap();
+    assert_eq!(timeout.value, ReportValue::String("30".into()));
+    assert_eq!(
+        timeout.source,
+        Some(ValueSource::Document("config.json".into()))
+    );
+
+    // The document takes precedence over the default value
+    let retries = report.get("db.retries").unwrap();
+    assert_eq!(
+        retries.source,
+        Some(ValueSource::Document("config.json".into()))
+    );
+
+    // Env takes precedence over the document
+    let url = report.get("db.url").unwrap();
+    assert_eq!(url.source, Some(ValueSource::Env("DB_URL".into())));
+
+    let run = report.subcommand.as_ref().unwrap();
+    assert_eq!(run.command, "run");
+    let port = run.get("port").unwrap();
+    assert_eq!(
+        port.source,
+        Some(ValueSource::Document("config.json".into()))
+    );
+}
+
+#[test]
+fn test_report_text() {
+    let (_app, report) = AppConfig::conf_builder()
+        .args(["my-app", "--timeout=10", "run", "--port=81"])
+        .env([("DB_URL", "postgres://db"), ("PEERS", "x")])
+        .try_parse_with_report()
+        .unwrap();
+
+    assert_multiline_eq!(
+        &report.to_string(),
+        r#"verbose = false (default)
+peers = ["x"] (env PEERS)
+listen_addr = <unset>
+timeout = "10" (args)
+api_key = <unset>
+db.url = "postgres://db" (env DB_URL)
+db.password = <unset>
+db.retries = "5" (default)
+replica.url = <unset>
+replica.password = <unset>
+replica.retries = <unset>
+run:
+  port = "81" (args)
+"#
+    );
+}
+
+#[test]
+fn test_report_json() {
+    let (_app, report) = AppConfig::conf_builder()
+        .args(["my-app", "run"])
+        .env([("DB_URL", "postgres://db"), ("DB_PASSWORD", "hunter2")])
+        .try_parse_with_report()
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["command"], "my-app");
+    assert_eq!(json["options"][0]["id"], "verbose");
+    assert_eq!(json["options"][0]["value"], false);
+    assert_eq!(json["options"][0]["source"]["type"], "default");
+    assert_eq!(json["options"][2]["id"], "listen_addr");
+    assert_eq!(json["options"][2]["value"], serde_json::Value::Null);
+    assert_eq!(json["options"][2]["source"], serde_json::Value::Null);
+    assert_eq!(json["options"][5]["id"], "db.url");
+    assert_eq!(json["options"][5]["value"], "postgres://db");
+    assert_eq!(
+        json["options"][5]["source"],
+        serde_json::json!({"type": "env", "name": "DB_URL"})
+    );
+    assert_eq!(json["options"][6]["value"], "[REDACTED]");
+    assert_eq!(json["subcommand"]["command"], "run");
+    assert_eq!(json["subcommand"]["options"][0]["value"], "8080");
+    assert_eq!(json["subcommand"]["subcommand"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_print_config_flag() {
+    let err = AppConfig::try_parse_from(
+        ["my-app", "--print-config"],
+        [("DB_URL", "postgres://db"), ("DB_PASSWORD", "hunter2")],
+    )
+    .unwrap_err();
+    assert_eq!(err.exit_code(), 0);
+    let text = err.to_string();
+    assert!(text.contains("db.url = \"postgres://db\" (env DB_URL)\n"));
+    assert!(text.contains("db.password = [REDACTED] (env DB_PASSWORD)\n"));
+
+    let err = AppConfig::try_parse_from(
+        ["my-app", "run", "--print-config=json"],
+        [("DB_URL", "postgres://db")],
+    )
+    .unwrap_err();
+    assert_eq!(err.exit_code(), 0);
+    let json: serde_json::Value = serde_json::from_str(&err.to_string()).unwrap();
+    assert_eq!(json["subcommand"]["command"], "run");
+
+    // Values are still resolved and validated first
+    assert_error_contains_text!(
+        AppConfig::try_parse_from(["my-app", "--print-config"], [("X", "")]),
+        ["env 'DB_URL', or '--db-url', must be provided"]
+    );
+
+    // Unknown formats are rejected
+    assert!(AppConfig::try_parse_from(
+        ["my-app", "--print-config=yaml"],
+        [("DB_URL", "postgres://db")]
+    )
+    .is_err_and(|err| err.exit_code() != 0));
+
+    // Without the flag, parsing is not affected
+    let app = AppConfig::try_parse_from(["my-app"], [("DB_URL", "postgres://db")]).unwrap();
+    assert_eq!(app.db.url, "postgres://db");
+}
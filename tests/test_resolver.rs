@@ -0,0 +1,205 @@
+#![cfg(feature = "serde")]
+
+mod common;
+use common::*;
+
+use conf::{Conf, ResolveError};
+use std::collections::HashMap;
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct Config {
+    #[arg(long, env)]
+    pub db_url: String,
+    #[arg(env, secret)]
+    pub db_password: String,
+    #[arg(long, env, serde(use_value_parser))]
+    pub port: Option<u16>,
+    #[arg(repeat, long, env)]
+    pub peers: Vec<String>,
+    #[arg(long, default_value = "vault://kv/app#greeting")]
+    pub greeting: String,
+}
+
+fn vault() -> HashMap<String, String> {
+    [
+        ("vault://kv/app#password", "hunter2"),
+        ("vault://kv/app#url", "postgres://db"),
+        ("vault://kv/app#port", "5432"),
+        ("vault://kv/app#bad_port", "xyz"),
+        ("vault://kv/app#peer", "10.0.0.1"),
+        ("vault://kv/app#greeting", "hello"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+    .collect()
+}
+
+fn upper(reference: &str) -> Result<String, ResolveError> {
+    let rest = reference.strip_prefix("upper://").unwrap();
+    if rest.is_empty() {
+        return Err("nothing to uppercase".into());
+    }
+    Ok(rest.to_uppercase())
+}
+
+#[test]
+fn test_resolver_env_and_args() {
+    let config = Config::conf_builder()
+        .args([".", "--db-url=vault://kv/app#url", "--peers=upper://a"])
+        .env([
+            ("DB_PASSWORD", "vault://kv/app#password"),
+            ("PEERS", "ignored"),
+        ])
+        .resolver("vault", vault())
+        .resolver("upper", upper)
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.db_url, "postgres://db");
+    assert_eq!(config.db_password, "hunter2");
+    assert_eq!(config.port, None);
+    assert_eq!(config.peers, vec!["A"]);
+    assert_eq!(config.greeting, "hello");
+
+    let config = Config::conf_builder()
+        .args(["."])
+        .env([
+            ("DB_URL", "upper://x"),
+            ("DB_PASSWORD", "plain"),
+            ("PORT", "vault://kv/app#port"),
+            ("PEERS", "vault://kv/app#peer,upper://b"),
+        ])
+        .resolver("vault", vault())
+        .resolver("upper", upper)
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.db_url, "X");
+    assert_eq!(config.db_password, "plain");
+    assert_eq!(config.port, Some(5432));
+    assert_eq!(config.peers, vec!["10.0.0.1", "B"]);
+}
+
+#[test]
+fn test_resolver_document() {
+    let config = Config::conf_builder()
+        .args(["."])
+        .env([("DB_PASSWORD", "vault://kv/app#password")])
+        .doc(
+            "config.json",
+            serde_json::json!({
+                "db_url": "postgres://from-doc",
+                "port": "vault://kv/app#port",
+            }),
+        )
+        .resolver("vault", vault())
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.db_url, "postgres://from-doc");
+    assert_eq!(config.db_password, "hunter2");
+    assert_eq!(config.port, Some(5432));
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct DocConfig {
+    #[arg(env)]
+    pub db_password: String,
+    #[arg(env)]
+    pub api_key: Option<conf::Secret<String>>,
+    #[arg(repeat, env)]
+    pub peers: Vec<String>,
+    #[arg(env)]
+    pub port: u16,
+}
+
+#[test]
+fn test_resolver_document_without_value_parser() {
+    // Strings in document values are resolved whatever the type of the field is
+    let config = DocConfig::conf_builder()
+        .args(["."])
+        .env::<&str, &str>([])
+        .doc(
+            "config.json",
+            serde_json::json!({
+                "db_password": "vault://kv/app#password",
+                "api_key": "vault://kv/app#greeting",
+                "peers": ["vault://kv/app#peer", "10.0.0.2"],
+                "port": 5432,
+            }),
+        )
+        .resolver("vault", vault())
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.db_password, "hunter2");
+    assert_eq!(config.api_key.unwrap().expose(), "hello");
+    assert_eq!(config.peers, vec!["10.0.0.1", "10.0.0.2"]);
+    assert_eq!(config.port, 5432);
+
+    // Without a resolver for the scheme, the value is used as is
+    let config = DocConfig::conf_builder()
+        .args(["."])
+        .env::<&str, &str>([])
+        .doc(
+            "config.json",
+            serde_json::json!({ "db_password": "vault://kv/app#password", "port": 1 }),
+        )
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.db_password, "vault://kv/app#password");
+
+    assert_error_contains_text!(
+        DocConfig::conf_builder()
+            .args(["."])
+            .env::<&str, &str>([])
+            .doc(
+                "config.json",
+                serde_json::json!({
+                    "db_password": "vault://kv/app#nope",
+                    "peers": ["vault://kv/app#peer_nope"],
+                    "port": 1,
+                }),
+            )
+            .resolver("vault", vault())
+            .try_parse(),
+        [
+            "A reference could not be resolved",
+            "value 'vault://kv/app#nope': reference not found",
+            "value 'vault://kv/app#peer_nope': reference not found",
+        ]
+    );
+}
+
+#[test]
+fn test_resolver_without_registered_scheme() {
+    let config = Config::conf_builder()
+        .args([".", "--db-url=vault://kv/app#url"])
+        .env([("DB_PASSWORD", "x")])
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.db_url, "vault://kv/app#url");
+    assert_eq!(config.greeting, "vault://kv/app#greeting");
+}
+
+#[test]
+fn test_resolver_errors() {
+    assert_error_contains_text!(
+        Config::conf_builder()
+            .args([".", "--db-url=vault://kv/app#nope", "--peers=upper://"])
+            .env([
+                ("DB_PASSWORD", "vault://kv/app#secret_nope"),
+                ("PORT", "vault://kv/app#bad_port"),
+            ])
+            .resolver("vault", vault())
+            .resolver("upper", upper)
+            .try_parse(),
+        [
+            "A reference could not be resolved",
+            "when resolving '--db-url' value 'vault://kv/app#nope': reference not found",
+            "when resolving env 'DB_PASSWORD' value: reference not found",
+            "when resolving '--peers' value 'upper://': nothing to uppercase",
+            "Invalid value",
+            "when parsing env 'PORT' value 'vault://kv/app#bad_port': invalid digit",
+        ],
+        not["secret_nope", "xyz"]
+    );
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "serde")]
+
+mod common;
+use common::*;
+
+use conf::{Conf, ConfSerde, SampleValue, Subcommands};
+use serde_json::json;
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct DbConfig {
+    /// Database url
+    #[arg(long, env)]
+    pub url: String,
+    #[arg(long, env, default_value = "5")]
+    pub retries: u32,
+    #[arg(long, env, default_value = "1.5")]
+    #[conf(serde(rename = "backoff_secs"))]
+    pub backoff: f64,
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct RunConfig {
+    #[arg(long, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct MigrateConfig {
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommands, Debug)]
+#[conf(serde)]
+pub enum Commands {
+    Run(RunConfig),
+    #[conf(serde(rename = "migrations"))]
+    Migrate(MigrateConfig),
+    #[conf(serde(skip))]
+    Version(MigrateConfig),
+}
+
+#[derive(Conf, Debug)]
+#[conf(serde)]
+pub struct AppConfig {
+    #[arg(short, long)]
+    pub verbose: bool,
+    #[arg(long, default_value = "info")]
+    pub log_level: String,
+    #[arg(parameter, long, default_value = "true")]
+    pub color: bool,
+    #[arg(long)]
+    #[conf(serde(skip))]
+    pub secret_key: Option<String>,
+    #[arg(repeat, long)]
+    pub peers: Vec<String>,
+    #[arg(long, default_value = "10", serde(use_value_parser))]
+    pub timeout: u64,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(flatten, serde(rename = "cache_db"))]
+    pub cache: Option<DbConfig>,
+    #[conf(subcommands)]
+    pub command: Commands,
+}
+
+#[test]
+fn test_sample_document_json() {
+    let sample = AppConfig::sample_document().unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&sample).unwrap(),
+        json!({
+            "verbose": false,
+            "log_level": "info",
+            "color": true,
+            "peers": [],
+            "timeout": "10",
+            "db": {
+                "url": null,
+                "retries": 5,
+                "backoff_secs": 1.5,
+            },
+            "cache_db": {
+                "url": null,
+                "retries": 5,
+                "backoff_secs": 1.5,
+            },
+            "run": {
+                "port": 8080,
+            },
+            "migrations": {
+                "dry_run": false,
+            },
+        })
+    );
+
+    // Keys appear in declaration order
+    let SampleValue::Table(entries) = &sample else {
+        panic!("expected a table: {sample:?}");
+    };
+    let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(
+        keys,
+        vec![
+            "verbose",
+            "log_level",
+            "color",
+            "peers",
+            "timeout",
+            "db",
+            "cache_db",
+            "run",
+            "migrations"
+        ]
+    );
+    assert_eq!(
+        sample.get("db").unwrap().get("retries"),
+        Some(&SampleValue::Integer(5))
+    );
+}
+
+#[test]
+fn test_sample_document_toml() {
+    let sample = DbConfig::sample_document().unwrap();
+
+    // Null values are omitted in toml
+    assert_multiline_eq!(
+        &toml::to_string(&sample).unwrap(),
+        "retries = 5\nbackoff_secs = 1.5\n"
+    );
+}
+
+#[test]
+fn test_sample_document_round_trip() {
+    // A sample document, filled in with the missing required values, should parse successfully
+    let mut sample = serde_json::to_value(AppConfig::sample_document().unwrap()).unwrap();
+    sample["db"]["url"] = json!("postgres://localhost");
+    sample["cache_db"]["url"] = json!("postgres://cache");
+
+    let result = AppConfig::conf_builder()
+        .args([".", "run"])
+        .env::<&str, &str>([])
+        .doc("sample", sample)
+        .try_parse()
+        .unwrap();
+
+    assert_eq!(result.db.url, "postgres://localhost");
+    assert_eq!(result.db.retries, 5);
+    assert_eq!(result.cache.unwrap().url, "postgres://cache");
+    assert_eq!(result.timeout, 10);
+    assert!(result.color);
+    assert!(matches!(
+        result.command,
+        Commands::Run(RunConfig { port: 8080 })
+    ));
+}
@@ -96,3 +96,86 @@ fn test_secret_b_get_program_options() {
 
     assert_eq!(iter.next(), None);
 }
+
+#[derive(Conf, Debug)]
+struct D {
+    /// API token
+    #[conf(long, env, secret(allow_cli))]
+    api_token: String,
+    #[conf(long, env, secret(allow_cli, warn_cli))]
+    port: u16,
+}
+
+#[test]
+fn test_secret_allow_cli_get_program_options() {
+    let opts = D::get_program_options().unwrap();
+
+    let opt = &opts[0];
+    assert_eq!(opt.long_form.as_deref(), Some("api-token"));
+    assert!(opt.is_secret());
+    assert!(opt.secret_allow_cli);
+    assert!(!opt.secret_warn_cli);
+
+    let opt = &opts[1];
+    assert!(opt.is_secret());
+    assert!(opt.secret_allow_cli);
+    assert!(opt.secret_warn_cli);
+
+    // Options without `allow_cli` don't allow CLI
+    let opts = A::get_program_options().unwrap();
+    assert!(opts.iter().all(|opt| !opt.secret_allow_cli));
+}
+
+#[test]
+fn test_secret_allow_cli_parse() {
+    let d = D::try_parse_from::<&str, &str, &str>(
+        vec![".", "--api-token", "sekrit", "--port", "8080"],
+        vec![],
+    )
+    .unwrap();
+    assert_eq!(d.api_token, "sekrit");
+    assert_eq!(d.port, 8080);
+
+    let d = D::try_parse_from(vec!["."], vec![("API_TOKEN", "sekrit"), ("PORT", "8080")]).unwrap();
+    assert_eq!(d.api_token, "sekrit");
+    assert_eq!(d.port, 8080);
+}
+
+#[test]
+fn test_secret_allow_cli_redacted_in_errors() {
+    let err = D::try_parse_from::<&str, &str, &str>(
+        vec![".", "--api-token", "sekrit", "--port", "80x80"],
+        vec![],
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("when parsing '--port' value"), "{err}");
+    assert!(!err.contains("80x80"), "{err}");
+}
+
+#[test]
+fn test_secret_allow_cli_redacted_in_help() {
+    let err = D::try_parse_from(
+        vec![".", "--help"],
+        vec![("API_TOKEN", "sekrit"), ("PORT", "8080")],
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("[env API_TOKEN]"), "{err}");
+    assert!(err.contains("[secret]"), "{err}");
+    assert!(!err.contains("sekrit"), "{err}");
+    assert!(!err.contains("8080"), "{err}");
+}
+
+#[derive(Conf, Debug)]
+#[allow(unused)]
+struct BadD {
+    #[conf(long, env, secret)]
+    api_token: String,
+}
+
+#[test]
+#[should_panic(expected = "unless `secret(allow_cli)` is used")]
+fn test_secret_without_allow_cli_on_cli_panics() {
+    let _ = BadD::try_parse_from::<&str, &str, &str>(vec!["."], vec![]);
+}
@@ -0,0 +1,122 @@
+use conf::{introspect::secret_lint, Conf, Subcommands};
+
+#[derive(Conf, Debug)]
+pub struct DbConfig {
+    #[arg(long, env)]
+    pub url: String,
+    #[arg(env)]
+    pub password: String,
+}
+
+#[derive(Conf, Debug)]
+pub struct RunConfig {
+    #[arg(env)]
+    pub signing_key: Option<String>,
+}
+
+#[derive(Subcommands, Debug)]
+pub enum Commands {
+    Run(RunConfig),
+}
+
+#[derive(Conf, Debug)]
+#[conf(name = "app", secret_heuristics)]
+pub struct AppConfig {
+    #[arg(long, env)]
+    pub api_token: String,
+    #[arg(env = "GITHUB_PAT", env_aliases = ["GITHUB_CREDENTIALS"])]
+    pub github: Option<String>,
+    #[arg(long, env, secret = false)]
+    pub public_key: Option<String>,
+    #[arg(long)]
+    pub keyboard_layout: Option<String>,
+    #[arg(long)]
+    pub skip_password_check: bool,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(subcommands)]
+    pub command: Option<Commands>,
+}
+
+#[test]
+fn test_secret_heuristics_program_options() {
+    let opts = AppConfig::get_program_options().unwrap();
+    let get = |id: &str| opts.iter().find(|opt| opt.id == id).unwrap();
+
+    // Has a long switch, so it is allowed on the CLI, with a warning
+    let opt = get("api_token");
+    assert!(opt.is_secret());
+    assert!(opt.secret_allow_cli);
+    assert!(opt.secret_warn_cli);
+
+    // Matched via env alias
+    let opt = get("github");
+    assert!(opt.is_secret());
+    assert!(!opt.secret_allow_cli);
+
+    // Explicit marking takes precedence
+    assert!(!get("public_key").is_secret());
+
+    // Only whole words match, and flags are never secrets
+    assert!(!get("keyboard_layout").is_secret());
+    assert!(!get("skip_password_check").is_secret());
+
+    // Applies to flattened structures
+    assert!(!get("db.url").is_secret());
+    assert!(get("db.password").is_secret());
+
+    // But not to structures that are used on their own
+    let opts = DbConfig::get_program_options().unwrap();
+    assert!(opts.iter().all(|opt| !opt.is_secret()));
+}
+
+#[test]
+fn test_secret_heuristics_parse() {
+    let config = AppConfig::try_parse_from(
+        vec![".", "--api-token=abc"],
+        vec![("DB_URL", "postgres://"), ("DB_PASSWORD", "hunter2")],
+    )
+    .unwrap();
+    assert_eq!(config.api_token, "abc");
+    assert_eq!(config.db.password, "hunter2");
+
+    let err = AppConfig::try_parse_from(
+        vec![".", "--help"],
+        vec![("API_TOKEN", "abc"), ("PUBLIC_KEY", "xyz")],
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("[env API_TOKEN]"), "{err}");
+    assert!(err.contains("[env PUBLIC_KEY=xyz]"), "{err}");
+}
+
+#[test]
+fn test_secret_lint() {
+    let lints = secret_lint::<AppConfig>().unwrap();
+    let summary: Vec<(&str, &str, &str, bool)> = lints
+        .iter()
+        .map(|lint| {
+            (
+                lint.command.as_str(),
+                lint.id.as_str(),
+                lint.matched,
+                lint.is_secret,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("app", "api_token", "token", true),
+            ("app", "github", "credential", true),
+            ("app", "public_key", "key", false),
+            ("app", "db.password", "password", true),
+            ("app run", "signing_key", "key", false),
+        ]
+    );
+
+    let lints = secret_lint::<DbConfig>().unwrap();
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].id, "password");
+    assert!(!lints[0].is_secret);
+}
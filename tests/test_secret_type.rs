@@ -0,0 +1,109 @@
+mod common;
+use common::*;
+
+use conf::{Conf, Secret};
+
+#[derive(Conf, Debug)]
+struct Config {
+    #[arg(env)]
+    api_key: Secret<String>,
+    #[arg(env)]
+    pin: Option<conf::Secret<u32>>,
+    #[arg(repeat, env)]
+    tokens: Vec<Secret<String>>,
+    #[arg(env, secret = false)]
+    not_really_secret: Option<Secret<String>>,
+}
+
+#[test]
+fn test_secret_type_parse() {
+    let config = Config::try_parse_from(
+        ["."],
+        [("API_KEY", "sekrit"), ("PIN", "1234"), ("TOKENS", "a,b")],
+    )
+    .unwrap();
+
+    assert_eq!(config.api_key.expose(), "sekrit");
+    assert_eq!(config.pin.as_ref().map(|pin| *pin.expose()), Some(1234));
+    assert_eq!(config.tokens.len(), 2);
+    assert_eq!(config.tokens[1].expose(), "b");
+    assert!(config.not_really_secret.is_none());
+
+    let debug = format!("{config:?}");
+    assert_eq!(
+        debug,
+        "Config { api_key: [REDACTED], pin: Some([REDACTED]), tokens: [[REDACTED], [REDACTED]], not_really_secret: None }"
+    );
+    assert_eq!(config.api_key.to_string(), "[REDACTED]");
+}
+
+#[test]
+fn test_secret_type_implies_secret() {
+    let opts = Config::get_program_options().unwrap();
+
+    assert_eq!(opts[0].id, "api_key");
+    assert_eq!(opts[0].secret, Some(true));
+    assert_eq!(opts[1].id, "pin");
+    assert_eq!(opts[1].secret, Some(true));
+    assert_eq!(opts[2].id, "tokens");
+    assert_eq!(opts[2].secret, Some(true));
+    // Explicit secret = false takes precedence
+    assert_eq!(opts[3].id, "not_really_secret");
+    assert_eq!(opts[3].secret, Some(false));
+}
+
+#[derive(Conf, Debug)]
+struct CliConfig {
+    // A `Secret<T>` with a switch requires `secret(allow_cli)`, otherwise the derive fails
+    #[arg(long, env, secret(allow_cli))]
+    api_key: Secret<String>,
+}
+
+#[test]
+fn test_secret_type_allow_cli() {
+    let opts = CliConfig::get_program_options().unwrap();
+    assert_eq!(opts[0].secret, Some(true));
+    assert!(opts[0].secret_allow_cli);
+
+    let config = CliConfig::try_parse_from([".", "--api-key", "sekrit"], [("", ""); 0]).unwrap();
+    assert_eq!(config.api_key.expose(), "sekrit");
+}
+
+#[test]
+fn test_secret_type_value_not_in_errors() {
+    assert_error_contains_text!(
+        Config::try_parse_from(["."], [("API_KEY", "sekrit"), ("PIN", "12x4")]),
+        ["Invalid value", "when parsing env 'PIN' value"],
+        not["12x4"]
+    );
+}
+
+#[test]
+fn test_secret_new_and_from() {
+    let secret = Secret::new(String::from("abc"));
+    assert_eq!(secret.expose(), "abc");
+
+    let mut secret: Secret<String> = String::from("abc").into();
+    secret.expose_mut().push('d');
+    assert_eq!(secret.expose(), "abcd");
+    assert_eq!(format!("{secret:?}"), "[REDACTED]");
+}
+
+#[test]
+fn test_secret_type_redacted_in_report() {
+    let (_config, report) = Config::conf_builder()
+        .args(["."])
+        .env([("API_KEY", "sekrit"), ("TOKENS", "a,b")])
+        .try_parse_with_report()
+        .unwrap();
+
+    assert_eq!(
+        report.get("api_key").unwrap().value,
+        conf::report::ReportValue::Redacted
+    );
+    assert_eq!(
+        report.get("tokens").unwrap().value,
+        conf::report::ReportValue::Redacted
+    );
+    assert!(!report.to_string().contains("sekrit"));
+}
@@ -143,7 +143,10 @@ fn test_basic_serde() {
                 }),
             )
             .try_parse(),
-        ["Parsing test_doc (@ A): unknown field `wobbly`"]
+        [
+            "Parsing test_doc: unknown key 'wobbly'",
+            "did you mean 'wobble' or 'bobble'?"
+        ]
     );
 
     assert_error_contains_text!(
@@ -164,8 +167,8 @@ fn test_basic_serde() {
         [
             "when parsing '--wiggle' value 'o': invalid digit found in string",
             "when parsing '--bobble' value 'x': invalid digit found in string",
-            "Parsing test_doc (@ A): unknown field `wobbly`",
-            "Parsing test_doc (@ A): unknown field `wubbly`",
+            "Parsing test_doc: unknown key 'wobbly'",
+            "Parsing test_doc: unknown key 'wubbly'",
         ]
     );
 }
@@ -192,7 +195,7 @@ fn test_serde_nested() {
                 }),
             )
             .try_parse(),
-        ["Parsing test_doc (@ B): unknown field `bobble`"]
+        ["Parsing test_doc: unknown key 'bobble'\n    expected one of: 'a', 'f'"]
     );
 
     let result = B::conf_builder()
@@ -251,8 +254,46 @@ fn test_serde_nested() {
             "when parsing '--wiggle' value 'q': invalid digit found in string",
             "Parsing test_doc3 (@ bobble): invalid type: string \"xxx\", expected i16",
             "Parsing test_doc3 (@ f): invalid type: integer `7`, expected a boolean",
-            "Parsing test_doc3 (@ B): unknown field `n`, expected `a` or `f`"
-        ]
+            "Parsing test_doc3: unknown key 'n'"
+        ],
+        not["did you mean"]
+    );
+}
+
+#[test]
+fn test_serde_unknown_key_paths() {
+    // Unknown keys at different depths are all reported, with their full path
+    let err = B::conf_builder()
+        .args([".", "--wiggle=8"])
+        .env([("WOBBLE", "xxx")])
+        .doc(
+            "test_doc",
+            json! ({
+              "a": {
+                "wobbel": "yyy"
+              },
+              "ff": true
+            }),
+        )
+        .try_parse()
+        .unwrap_err();
+
+    assert_multiline_eq!(
+        &err.to_string(),
+        "error: Unknown document key
+  Parsing test_doc: unknown key 'a.wobbel'
+    note: did you mean 'wobble'?
+  Parsing test_doc: unknown key 'ff'
+    expected one of: 'a', 'f'
+"
+    );
+
+    let items = err.items().collect::<Vec<_>>();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].kind, conf::ErrorKind::UnknownDocumentKey);
+    assert_eq!(
+        items[0].source,
+        Some(conf::report::ValueSource::Document("test_doc".into()))
     );
 }
 
@@ -471,7 +512,7 @@ fn test_serde_skip() {
             .env([("MY_PARAM", "Asdf")])
             .doc("test", json!({"f": true, "val": 2}))
             .try_parse(),
-        ["Parsing test (@ TestSerdeSkip): unknown field `f`, expected `val`"]
+        ["Parsing test: unknown key 'f'\n    expected one of: 'val'"]
     );
 
     assert_error_contains_text!(
@@ -483,7 +524,7 @@ fn test_serde_skip() {
                 json!({"not_serde": { "my_param": "Foo" }, "val": 2})
             )
             .try_parse(),
-        ["Parsing test (@ TestSerdeSkip): unknown field `not_serde`, expected `val`"]
+        ["Parsing test: unknown key 'not_serde'\n    expected one of: 'val'"]
     );
 
     assert_error_contains_text!(
@@ -492,7 +533,7 @@ fn test_serde_skip() {
             .env([("MY_PARAM", "Asdf")])
             .doc("test", json!({"pair": "2:3", "val": 2}))
             .try_parse(),
-        ["Parsing test (@ TestSerdeSkip): unknown field `pair`, expected `val`"]
+        ["Parsing test: unknown key 'pair'\n    expected one of: 'val'"]
     );
 
     assert_error_contains_text!(
@@ -501,7 +542,7 @@ fn test_serde_skip() {
             .env([("MY_PARAM", "Asdf")])
             .doc("test", json!({"pairs": ["2:3"], "val": 2}))
             .try_parse(),
-        ["Parsing test (@ TestSerdeSkip): unknown field `pairs`, expected `val`"]
+        ["Parsing test: unknown key 'pairs'\n    expected one of: 'val'"]
     );
 
     let result = TestSerdeSkip::conf_builder()
@@ -132,6 +132,8 @@ Options:
       --telemetry-retries <telemetry.retries>
           Telemetry endpoint: Number of retries
           [env MYCO_TELEMETRY_RETRIES=]
+      --help-env
+          Print help for environment variables, and exit
   -h, --help
           Print help
 "[1..];
@@ -279,3 +281,33 @@ Config {
     assert_multiline_eq!(from_utf8(&output.stdout).unwrap(), &expected);
     assert_eq!(output.status.code(), Some(0));
 }
+
+#[test]
+fn test_showcase_example_json_errors() {
+    let mut command = ShowcaseExample::get_command();
+    let output = command
+        .args(["--auth-retries=-3"])
+        .envs([
+            ("CONF_ERROR_FORMAT", "json"),
+            ("MYCO_BASIC_SOLVER_BRANCHING_FACTOR", "2"),
+            ("MYCO_BASIC_SOLVER_EPSILON", "0.1"),
+            ("MYCO_BASIC_SOLVER_MATRIX_SIZE", "10"),
+            ("MYCO_HIGH_PRIORITY_SOLVER_BRANCHING_FACTOR", "2"),
+            ("MYCO_HIGH_PRIORITY_SOLVER_EPSILON", "0.1"),
+            ("MYCO_HIGH_PRIORITY_SOLVER_MATRIX_SIZE", "10"),
+            ("MYCO_ARTIFACT_RETRIES", "1"),
+            ("MYCO_ARTIFACT_URL", "http://artifact"),
+            ("MYCO_AUTH_URL", "http://auth"),
+            ("MYCO_TELEMETRY_RETRIES", "1"),
+        ])
+        .output()
+        .unwrap();
+
+    let expected = &r#"
+{"kind":"missing_required","id":"telemetry.url","switch":"--telemetry-url","env":"MYCO_TELEMETRY_URL","source":null,"value":null,"constraint_ids":[],"message":"env 'MYCO_TELEMETRY_URL', or '--telemetry-url', must be provided"}
+{"kind":"invalid_value","id":"solver_service.auth.retries","switch":"--auth-retries","env":"MYCO_AUTH_RETRIES","source":{"type":"args"},"value":"-3","constraint_ids":[],"message":"when parsing '--auth-retries' value '-3': invalid digit found in string"}
+"#[1..];
+
+    assert_multiline_eq!(from_utf8(&output.stderr).unwrap(), &expected);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,85 @@
+use conf::{report::ValueSource, Conf, Subcommands};
+
+#[derive(Conf, Debug)]
+pub struct TlsConfig {
+    #[arg(long, env, env_aliases = ["TLS_PRIVATE_KEY"])]
+    pub key: Option<String>,
+    #[arg(long, env, default_value = "cert.pem")]
+    pub cert: String,
+}
+
+#[derive(Conf, Debug)]
+pub struct ServeConfig {
+    #[arg(long, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Subcommands, Debug)]
+pub enum Commands {
+    Serve(ServeConfig),
+}
+
+#[derive(Conf, Debug)]
+#[conf(name = "app")]
+pub struct AppConfig {
+    #[arg(long, env, default_value = "dev-secret")]
+    pub jwt_secret: String,
+    #[arg(short, long)]
+    pub verbose: bool,
+    #[conf(flatten, prefix)]
+    pub tls: TlsConfig,
+    #[conf(subcommands)]
+    pub command: Option<Commands>,
+}
+
+#[test]
+fn test_sources() {
+    let (app, sources) = AppConfig::conf_builder()
+        .args(["app", "--tls-key=abc", "serve", "--port=9000"])
+        .env([("TLS_TLS_PRIVATE_KEY", "def")])
+        .try_parse_with_sources()
+        .unwrap();
+    assert_eq!(app.tls.key.as_deref(), Some("abc"));
+
+    assert_eq!(sources.command(), "app");
+    assert!(sources.get("jwt_secret").unwrap().is_default());
+    assert!(sources.get("verbose").unwrap().is_default());
+    assert!(sources.get("tls.key").unwrap().is_args());
+    assert_eq!(sources.get("tls.cert"), Some(&ValueSource::Default));
+    assert_eq!(sources.get("nonexistent"), None);
+
+    let serve = sources.subcommand().unwrap();
+    assert_eq!(serve.command(), "serve");
+    assert_eq!(serve.get("port"), Some(&ValueSource::Args));
+}
+
+#[test]
+fn test_sources_env_alias() {
+    let (_app, sources) = AppConfig::conf_builder()
+        .args(["app"])
+        .env([("TLS_TLS_PRIVATE_KEY", "def"), ("JWT_SECRET", "xyz")])
+        .try_parse_with_sources()
+        .unwrap();
+
+    assert_eq!(
+        sources.get("tls.key").unwrap().env_name(),
+        Some("TLS_TLS_PRIVATE_KEY")
+    );
+    assert_eq!(
+        sources.get("jwt_secret"),
+        Some(&ValueSource::Env("JWT_SECRET".into()))
+    );
+    assert!(sources.subcommand().is_none());
+
+    // Options without a value have no source
+    let (_app, sources) = AppConfig::conf_builder()
+        .args(["app"])
+        .env([("OTHER", "")])
+        .try_parse_with_sources()
+        .unwrap();
+    assert_eq!(sources.get("tls.key"), None);
+
+    let mut ids: Vec<&str> = sources.iter().map(|(id, _src)| id).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["jwt_secret", "tls.cert", "verbose"]);
+}
@@ -73,6 +73,7 @@ Options:
                                      [env DB_URL=]
       --db-retries <db.retries>      Database: Number of retries
                                      [env DB_RETRIES=]
+      --help-env                     Print help for environment variables, and exit
   -h, --help                         Print help
 "[1..];
 
@@ -155,6 +156,7 @@ Usage: subcommands_example run-migrations [OPTIONS]
 Options:
       --migrations <migrations>  Path to migrations file (instead of embedded migrations)
                                  [env MIGRATIONS=]
+      --help-env                 Print help for environment variables, and exit
   -h, --help                     Print help
 "[1..];
 
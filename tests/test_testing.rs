@@ -0,0 +1,133 @@
+#![cfg(feature = "testing")]
+#![allow(unused)]
+
+use conf::{testing::*, Conf, ErrorKind, Subcommands};
+
+#[derive(Conf, Debug)]
+pub struct DbConfig {
+    /// Url of the database
+    #[arg(long, env)]
+    pub url: String,
+    /// Password of the database
+    #[arg(env, secret)]
+    pub password: Option<String>,
+}
+
+#[derive(Conf, Debug)]
+pub struct RunConfig {
+    /// Port to listen on
+    #[arg(long, env, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Subcommands, Debug)]
+pub enum Commands {
+    Run(RunConfig),
+}
+
+#[derive(Conf, Debug)]
+#[conf(env_prefix = "APP_")]
+pub struct AppConfig {
+    /// Print more output
+    #[arg(short, long, env)]
+    pub verbose: bool,
+    /// Number of worker threads
+    #[arg(long, env, default_value = "4")]
+    pub workers: u32,
+    /// Directory to write logs to
+    #[arg(long, env)]
+    pub log_dir: Option<String>,
+    #[conf(flatten, prefix, help_prefix)]
+    pub db: DbConfig,
+    #[conf(subcommands)]
+    pub command: Option<Commands>,
+}
+
+#[test]
+fn test_fixture() {
+    let fixture = Fixture::new()
+        .env("APP_DB_URL", "postgres://a")
+        .env("APP_WORKERS", "2");
+    let config: AppConfig = fixture.parse_ok();
+    assert_eq!(config.db.url, "postgres://a");
+    assert_eq!(config.workers, 2);
+    assert!(!config.verbose);
+
+    // Args take precedence, and later env values replace earlier ones
+    let config: AppConfig = fixture
+        .clone()
+        .args(["-v", "--workers", "8"])
+        .env("APP_DB_URL", "postgres://b")
+        .arg("run")
+        .parse_ok();
+    assert!(config.verbose);
+    assert_eq!(config.workers, 8);
+    assert_eq!(config.db.url, "postgres://b");
+    assert!(config.command.is_some());
+
+    let err = fixture.remove_env("APP_DB_URL").parse_err::<AppConfig>();
+    assert_error_kind(&err, ErrorKind::MissingRequired, "db.url");
+}
+
+#[test]
+fn test_error_assertions() {
+    let err = Fixture::new()
+        .envs([("APP_DB_URL", "postgres://a"), ("APP_WORKERS", "many")])
+        .parse_err::<AppConfig>();
+    let item = assert_error_kind(&err, ErrorKind::InvalidValue, "workers");
+    assert_eq!(item.env.as_deref(), Some("APP_WORKERS"));
+    assert_error_contains(&err, "invalid digit");
+}
+
+#[test]
+#[should_panic(expected = "Expected an error item of kind MissingRequired for 'workers'")]
+fn test_assert_error_kind_fails() {
+    let err = Fixture::new().parse_err::<AppConfig>();
+    assert_error_kind(&err, ErrorKind::MissingRequired, "workers");
+}
+
+#[test]
+#[should_panic(expected = "but an error was expected")]
+fn test_parse_err_fails() {
+    Fixture::new()
+        .env("APP_DB_URL", "postgres://a")
+        .parse_err::<AppConfig>();
+}
+
+#[test]
+fn test_help_renderer() {
+    let help = HelpRenderer::new()
+        .bin_name("app")
+        .width(60)
+        .render::<AppConfig>()
+        .unwrap();
+    assert!(help.starts_with("Usage: app [OPTIONS] [COMMAND]"), "{help}");
+    assert!(help.lines().all(|line| line.len() <= 60), "{help}");
+
+    let help = HelpRenderer::new()
+        .bin_name("app")
+        .subcommand("run")
+        .render::<AppConfig>()
+        .unwrap();
+    assert!(help.starts_with("Usage: app run [OPTIONS]"), "{help}");
+    assert!(help.contains("--port"), "{help}");
+}
+
+#[test]
+fn test_snapshots() {
+    assert_help_snapshot::<AppConfig>("tests/snapshots/test_testing_help.txt");
+    assert_env_template_snapshot::<AppConfig>("tests/snapshots/test_testing_env.txt");
+}
+
+#[test]
+#[should_panic(expected = "differs at line 2")]
+fn test_snapshot_mismatch() {
+    let dir = std::env::temp_dir().join(format!("conf_test_testing_{}", std::process::id()));
+    let path = dir.join("snapshot.txt");
+    let _ = std::fs::remove_file(&path);
+
+    // The first run writes the snapshot
+    assert_snapshot(&path, "a\nb\n");
+    assert_snapshot(&path, "a  \nb\n");
+    assert_snapshot(&path, "a\nc\n");
+}
@@ -0,0 +1,181 @@
+#![allow(unused)]
+
+mod common;
+use common::*;
+
+use conf::{Conf, ConfToArgs, Secret, Subcommands};
+use std::{ffi::OsString, path::PathBuf, time::Duration};
+
+#[derive(Conf, Debug)]
+#[conf(to_args)]
+pub struct DbConfig {
+    #[arg(long, env)]
+    pub url: String,
+    #[arg(env, secret)]
+    pub password: Option<String>,
+}
+
+#[derive(Conf, Debug)]
+#[conf(to_args)]
+pub struct RunConfig {
+    #[arg(long, env, default_value = "8080")]
+    pub port: u16,
+}
+
+#[derive(Subcommands, Debug)]
+#[conf(to_args)]
+pub enum Commands {
+    Run(RunConfig),
+}
+
+fn parse_secs(src: &str) -> Result<Duration, std::num::ParseIntError> {
+    Ok(Duration::from_secs(src.parse()?))
+}
+
+fn format_secs(val: &Duration) -> String {
+    val.as_secs().to_string()
+}
+
+#[derive(Conf, Debug)]
+#[conf(name = "worker", env_prefix = "APP_", to_args)]
+pub struct AppConfig {
+    #[arg(short, long, env)]
+    pub verbose: bool,
+    #[arg(short)]
+    pub quiet: bool,
+    #[arg(repeat, long, env)]
+    pub peers: Vec<String>,
+    #[arg(long, env)]
+    pub log_dir: Option<PathBuf>,
+    #[arg(long, env, value_parser = parse_secs, value_formatter = format_secs)]
+    pub timeout: Duration,
+    #[arg(env)]
+    pub api_key: Secret<String>,
+    #[conf(flatten, prefix)]
+    pub db: DbConfig,
+    #[conf(flatten, prefix)]
+    pub replica: Option<DbConfig>,
+    #[conf(subcommands)]
+    pub command: Option<Commands>,
+}
+
+// Parse the args and env vars of config, and check that the result is the same
+fn assert_round_trip(config: &AppConfig) {
+    let parsed = AppConfig::try_parse_from(config.to_args(), config.to_env_vars()).unwrap();
+    assert_eq!(config.diff(&parsed), vec![]);
+    assert_eq!(parsed.api_key.expose(), config.api_key.expose());
+}
+
+#[test]
+fn test_to_args() {
+    let config = AppConfig::try_parse_from(
+        [
+            ".",
+            "-v",
+            "--peers=a",
+            "--peers=b",
+            "--log-dir=/var/log",
+            "--timeout=30",
+        ],
+        [
+            ("APP_API_KEY", "sekrit"),
+            ("APP_DB_URL", "postgres://db"),
+            ("APP_DB_PASSWORD", "hunter2"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.to_args(),
+        vec_os_str([
+            "worker",
+            "--verbose",
+            "--peers=a",
+            "--peers=b",
+            "--log-dir=/var/log",
+            "--timeout=30",
+            "--db-url=postgres://db",
+        ])
+    );
+    assert_eq!(
+        config.to_env_vars(),
+        vec![
+            ("APP_VERBOSE".into(), "true".into()),
+            ("APP_PEERS".into(), "a,b".into()),
+            ("APP_LOG_DIR".into(), "/var/log".into()),
+            ("APP_TIMEOUT".into(), "30".into()),
+            ("APP_API_KEY".into(), "sekrit".into()),
+            ("APP_DB_URL".into(), "postgres://db".into()),
+            ("APP_DB_PASSWORD".into(), "hunter2".into()),
+        ]
+    );
+
+    assert_round_trip(&config);
+}
+
+#[test]
+fn test_to_args_flatten_optional_and_subcommands() {
+    let config = AppConfig::try_parse_from(
+        [
+            ".",
+            "-q",
+            "--timeout=5",
+            "--replica-url=postgres://r",
+            "run",
+            "--port=9090",
+        ],
+        [("APP_API_KEY", "sekrit"), ("APP_DB_URL", "postgres://db")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.to_args(),
+        vec_os_str([
+            "worker",
+            "-q",
+            "--timeout=5",
+            "--db-url=postgres://db",
+            "--replica-url=postgres://r",
+            "run",
+            "--port=9090",
+        ])
+    );
+    // Options of the subcommand have env vars too
+    assert!(config
+        .to_env_vars()
+        .contains(&("PORT".into(), "9090".into())));
+
+    assert_round_trip(&config);
+}
+
+fn vec_os_str(list: impl IntoIterator<Item = &'static str>) -> Vec<OsString> {
+    list.into_iter().map(Into::into).collect()
+}
+
+#[derive(Conf, Debug)]
+#[conf(to_args)]
+pub struct PathConfig {
+    #[arg(long, env)]
+    pub path: PathBuf,
+    #[arg(repeat, long, env)]
+    pub includes: Vec<PathBuf>,
+}
+
+#[cfg(unix)]
+#[test]
+fn test_to_args_not_utf8() {
+    use std::os::unix::ffi::OsStringExt;
+
+    // Paths which are not valid UTF-8 are formatted without loss
+    let path = OsString::from_vec(b"/tmp/\xff".to_vec());
+    let config = PathConfig {
+        path: path.clone().into(),
+        includes: vec![path.clone().into()],
+    };
+
+    let mut arg = OsString::from("--path=");
+    arg.push(&path);
+    assert_eq!(config.to_args()[1], arg);
+    assert_eq!(config.to_env_vars()[0], ("PATH".into(), path.clone()));
+    assert_eq!(config.to_env_vars()[1], ("INCLUDES".into(), path));
+}
@@ -0,0 +1,186 @@
+#![allow(unused)]
+
+use conf::{report::ValueSource, Conf, Warning, WarningKind};
+use std::sync::{Arc, Mutex};
+
+#[derive(Conf, Debug)]
+#[conf(env_prefix = "APP_")]
+struct Config {
+    #[arg(long, env, env_aliases = ["DATABASE_URL"])]
+    db_url: String,
+    #[arg(long, env, secret(allow_cli, warn_cli))]
+    api_token: Option<String>,
+    #[arg(long, env, deprecated = "it has no effect")]
+    retries: Option<u32>,
+    #[arg(long, env)]
+    max_connections: Option<u32>,
+}
+
+#[test]
+fn test_no_warnings() {
+    let (config, warnings) = Config::conf_builder()
+        .args(["."])
+        .env([("APP_DB_URL", "postgres://"), ("APP_OTHER", "1")])
+        .try_parse_with_warnings()
+        .unwrap();
+    assert_eq!(config.db_url, "postgres://");
+    assert_eq!(warnings, vec![]);
+}
+
+#[test]
+fn test_warnings() {
+    let (config, warnings) = Config::conf_builder()
+        .args([".", "--api-token", "hunter2", "--retries", "3"])
+        .env([
+            ("APP_DB_URL", "postgres://a"),
+            ("APP_DATABASE_URL", "postgres://b"),
+            ("APP_MAX_CONNECTION", "10"),
+        ])
+        .try_parse_with_warnings()
+        .unwrap();
+    assert_eq!(config.db_url, "postgres://a");
+    assert_eq!(config.max_connections, None);
+
+    let kinds = warnings
+        .iter()
+        .map(|warning| warning.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            WarningKind::ShadowedEnv,
+            WarningKind::SecretOnCli,
+            WarningKind::UnknownEnv,
+            WarningKind::Deprecated
+        ]
+    );
+
+    assert_eq!(warnings[0].id.as_deref(), Some("db_url"));
+    assert_eq!(warnings[0].env.as_deref(), Some("APP_DATABASE_URL"));
+    assert_eq!(
+        warnings[0].source,
+        Some(ValueSource::Env("APP_DB_URL".into()))
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "env 'APP_DATABASE_URL' is set, but is ignored because env 'APP_DB_URL' is also set"
+    );
+
+    assert_eq!(warnings[1].switch.as_deref(), Some("--api-token"));
+    assert!(!warnings[1].message.contains("hunter2"));
+
+    assert_eq!(warnings[2].env.as_deref(), Some("APP_MAX_CONNECTION"));
+    assert_eq!(
+        warnings[2].message,
+        "env 'APP_MAX_CONNECTION' is set, but is not read by any option\n  note: did you mean 'APP_MAX_CONNECTIONS'?"
+    );
+
+    assert_eq!(warnings[3].id.as_deref(), Some("retries"));
+    assert_eq!(
+        warnings[3].message,
+        "'--retries' is deprecated: it has no effect"
+    );
+}
+
+#[test]
+fn test_warning_handler() {
+    let seen = Arc::new(Mutex::new(Vec::<Warning>::new()));
+
+    let seen2 = seen.clone();
+    let config = Config::conf_builder()
+        .args([".", "--retries", "3"])
+        .env([("APP_DB_URL", "postgres://")])
+        .on_warning(move |warning| seen2.lock().unwrap().push(warning.clone()))
+        .try_parse()
+        .unwrap();
+    assert_eq!(config.retries, Some(3));
+    assert_eq!(seen.lock().unwrap().len(), 1);
+    assert_eq!(seen.lock().unwrap()[0].kind, WarningKind::Deprecated);
+
+    // A builder with a warning handler can be sent to another thread
+    seen.lock().unwrap().clear();
+    let seen2 = seen.clone();
+    let builder = Config::conf_builder()
+        .args([".", "--retries", "3"])
+        .env([("APP_DB_URL", "postgres://")])
+        .on_warning(move |warning| seen2.lock().unwrap().push(warning.clone()));
+    std::thread::spawn(move || builder.try_parse().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 1);
+
+    // The handler is also called when parsing fails
+    seen.lock().unwrap().clear();
+    let seen2 = seen.clone();
+    assert!(Config::conf_builder()
+        .args([".", "--retries", "3"])
+        .env([("APP_DB_UR", "postgres://")])
+        .on_warning(move |warning| seen2.lock().unwrap().push(warning.clone()))
+        .try_parse()
+        .is_err());
+    let kinds = seen
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|warning| warning.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![WarningKind::UnknownEnv, WarningKind::Deprecated]
+    );
+}
+
+#[test]
+fn test_denied_warnings() {
+    // Problems which are errors are not also warnings
+    assert!(Config::conf_builder()
+        .args([".", "--retries", "3"])
+        .env([("APP_DB_URL", "postgres://"), ("APP_MAX_CONNECTION", "10")])
+        .deny_deprecated(true)
+        .deny_unknown_env(true)
+        .try_parse_with_warnings()
+        .is_err());
+}
+
+// Parses with a deprecated option and no warning handler, when run as a child process by
+// test_warnings_printed_by_parse_only. Otherwise it does nothing.
+#[test]
+fn test_warnings_child() {
+    let Ok(mode) = std::env::var("CONF_TEST_WARNINGS_CHILD") else {
+        return;
+    };
+    let builder = Config::conf_builder()
+        .args([".", "--retries", "3"])
+        .env([("APP_DB_URL", "postgres://")]);
+    match mode.as_str() {
+        "parse" => {
+            builder.parse();
+        }
+        "try_parse" => {
+            builder.try_parse().unwrap();
+        }
+        _ => panic!("unknown mode {mode}"),
+    }
+}
+
+#[test]
+fn test_warnings_printed_by_parse_only() {
+    let child_stderr = |mode: &str| {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["test_warnings_child", "--exact", "--nocapture"])
+            .env("CONF_TEST_WARNINGS_CHILD", mode)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let stderr = child_stderr("parse");
+    assert!(
+        stderr.contains("warning: '--retries' is deprecated"),
+        "{stderr}"
+    );
+
+    let stderr = child_stderr("try_parse");
+    assert!(!stderr.contains("warning:"), "{stderr}");
+}